@@ -1,17 +1,27 @@
+use crate::config::{OutcomeMapping, OutcomeSide, PolymarketConfig};
+use crate::display::short;
+use crate::error::ApiError;
 use crate::models::*;
 use anyhow::{Context, Result};
-use reqwest::Client;
+use reqwest::header::RETRY_AFTER;
+use reqwest::{Client, RequestBuilder, Response, StatusCode};
 use serde_json::Value;
+use rust_decimal::prelude::ToPrimitive;
 use std::str::FromStr;
-use std::sync::OnceLock;
-use hex;
-use log::{info, warn};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+use tokio::sync::Semaphore;
+use log::{debug, error, info, warn};
 
 // Official SDK imports for proper order signing
 use polymarket_client_sdk::clob::{Client as ClobClient, Config as ClobConfig};
-use polymarket_client_sdk::clob::types::{Side, OrderType, SignatureType};
+use polymarket_client_sdk::clob::types::{Side, OrderType, SignatureType, SignedOrder};
+use polymarket_client_sdk::clob::types::request::OrdersRequest;
 use polymarket_client_sdk::auth::state::Authenticated;
 use polymarket_client_sdk::auth::Normal;
+use polymarket_client_sdk::error::{Error as SdkError, Kind as SdkErrorKind, Status as SdkStatus};
 use polymarket_client_sdk::POLYGON;
 use alloy::signers::local::{LocalSigner, PrivateKeySigner};
 use alloy::signers::Signer as _;
@@ -19,9 +29,29 @@ use alloy::primitives::Address as AlloyAddress;
 use alloy::primitives::{Address, B256, U256, Bytes};
 use alloy::primitives::keccak256;
 use alloy::providers::{Provider, ProviderBuilder};
-use alloy::rpc::types::eth::TransactionRequest;
+use alloy::rpc::types::eth::{BlockId, TransactionReceipt, TransactionRequest};
 use alloy::sol;
-use alloy_sol_types::SolCall;
+use alloy_sol_types::{SolCall, SolEvent};
+
+sol! {
+    interface IAggregatorV3 {
+        function decimals() external view returns (uint8);
+        function latestRoundData() external view returns (
+            uint80 roundId,
+            int256 answer,
+            uint256 startedAt,
+            uint256 updatedAt,
+            uint80 answeredInRound
+        );
+        function getRoundData(uint80 _roundId) external view returns (
+            uint80 roundId,
+            int256 answer,
+            uint256 startedAt,
+            uint256 updatedAt,
+            uint80 answeredInRound
+        );
+    }
+}
 
 sol! {
     interface IConditionalTokens {
@@ -31,42 +61,325 @@ sol! {
             bytes32 conditionId,
             uint256[] indexSets
         ) external;
+        function getCollectionId(
+            bytes32 parentCollectionId,
+            bytes32 conditionId,
+            uint256 indexSet
+        ) external view returns (bytes32);
+    }
+}
+
+sol! {
+    interface IGnosisSafe {
+        function nonce() external view returns (uint256);
+        function getThreshold() external view returns (uint256);
+        function getOwners() external view returns (address[]);
+        function getTransactionHash(
+            address to,
+            uint256 value,
+            bytes data,
+            uint8 operation,
+            uint256 safeTxGas,
+            uint256 baseGas,
+            uint256 gasPrice,
+            address gasToken,
+            address refundReceiver,
+            uint256 _nonce
+        ) external view returns (bytes32);
+        function execTransaction(
+            address to,
+            uint256 value,
+            bytes data,
+            uint8 operation,
+            uint256 safeTxGas,
+            uint256 baseGas,
+            uint256 gasPrice,
+            address gasToken,
+            address refundReceiver,
+            bytes signatures
+        ) external payable returns (bool success);
+
+        event ExecutionFailure(bytes32 txHash, uint256 payment);
     }
 }
 
+sol! {
+    interface IERC20 {
+        function decimals() external view returns (uint8);
+        function balanceOf(address account) external view returns (uint256);
+    }
+}
+
+/// USDC.e on Polygon: the CTF collateral token.
+const USDC_ADDRESS: Address = alloy::primitives::address!("2791Bca1f2de4661ED88A30C99A7a9449Aa84174");
+/// Native USDC on Polygon, which some markets have migrated to as collateral.
+const NATIVE_USDC_ADDRESS: Address = alloy::primitives::address!("3c499c542cEF5E3811e1192ce70d8cC03d5c3359");
+/// Gnosis Conditional Tokens (CTF) contract on Polygon.
+const CTF_ADDRESS: Address = alloy::primitives::address!("4d97dcd97ec945f40cf65f87097ace5ea0476045");
+
+/// Max aggregator rounds to walk back from `latestRoundData` when looking up a historical price.
+const MAX_CHAINLINK_ROUND_STEPS: u32 = 60;
+
+/// Max retries of a REST request after HTTP 429.
+const MAX_RATE_LIMIT_RETRIES: u32 = 3;
+/// Wait used when a 429 has no parseable `Retry-After` header.
+const DEFAULT_RETRY_AFTER: Duration = Duration::from_secs(1);
+/// Upper bound on a single `Retry-After` wait, so a bad header can't stall a round.
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(10);
+
+/// CLOB cursor marking the last page of a paged response.
+const TERMINAL_CURSOR: &str = "LTE=";
+/// Page cap for `get_open_orders`.
+const MAX_ORDER_PAGES: usize = 20;
+
+/// Gamma `/events` page size and page cap for `discover_active_updown_markets`.
+const EVENTS_PAGE_SIZE: usize = 500;
+const MAX_EVENT_PAGES: usize = 10;
+
 pub struct PolymarketApi {
     client: Client,
     gamma_url: String,
     clob_url: String,
+    data_api_url: String,
     private_key: Option<String>,
     proxy_wallet_address: Option<String>,
     signature_type: Option<u8>,
+    safe_cosigner_keys: Vec<String>,
     rpc_urls: Vec<String>,
-    clob_auth: OnceLock<(PrivateKeySigner, ClobClient<Authenticated<Normal>>)>,
+    /// How long to wait for a redemption to mine before replacing it.
+    redeem_confirm_timeout: Duration,
+    redeem_max_replacements: u32,
+    redeem_gas_bump_pct: u64,
+    /// Wait between the two resolution checks in `verify_resolution`.
+    redeem_resolution_delay: Duration,
+    /// Preferred `redeemPositions` collateral (`polymarket.collateral_token`).
+    collateral_token: String,
+    /// `redeemPositions` parent collection; zero for plain binary markets.
+    redeem_parent_collection_id: B256,
+    outcomes: OutcomeMapping,
+    /// Per-order deadline for building/signing and for posting a FOK order.
+    order_timeout: Duration,
+    /// Per-request timeouts for book fetches and data API calls; other requests use the
+    /// client's `http_timeout_ms`.
+    book_timeout: Duration,
+    data_api_timeout: Duration,
+    /// Deadline for each `eth_call` attempt before moving on to the next RPC URL.
+    rpc_timeout: Duration,
+    /// Limits concurrent Gamma/CLOB market lookups so period boundaries don't burst the API.
+    request_limit: Semaphore,
+    /// Count of HTTP 429 responses seen (rate-limit metric).
+    rate_limited: AtomicU64,
+    /// Authenticated CLOB client; replaced by `reauthenticate`, cleared if that fails.
+    clob_auth: RwLock<Option<Arc<ClobAuth>>>,
+    /// FOK buys signed before close, keyed by (token_id, size, price) strings.
+    presigned: Mutex<HashMap<(String, String, String), SignedOrder>>,
+}
+
+type ClobAuth = (PrivateKeySigner, ClobClient<Authenticated<Normal>>);
+
+/// Classify an SDK error from `post_order`. Status / validation / geoblock kinds carry a typed
+/// cause, except 5xx: a gateway or server error can come back after the order reached the book,
+/// so it is ambiguous. For internal (transport) errors a connect-phase failure means the order
+/// was never sent; anything else may have reached the server and is ambiguous.
+fn classify_sdk_error(e: &SdkError) -> ApiError {
+    let msg = e.to_string();
+    match e.kind() {
+        SdkErrorKind::Status => {
+            return match e.downcast_ref::<SdkStatus>().map(|s| s.status_code.as_u16()) {
+                Some(429) => ApiError::RateLimited(msg),
+                Some(401) | Some(403) => ApiError::Auth(msg),
+                Some(500..=599) => ApiError::NetworkAmbiguous(msg),
+                _ => ApiError::Rejected(msg),
+            };
+        }
+        SdkErrorKind::Validation => return ApiError::Rejected(msg),
+        SdkErrorKind::Geoblock => return ApiError::Auth(msg),
+        _ => {}
+    }
+    if is_connect_failure(e) {
+        ApiError::Rejected(msg)
+    } else {
+        ApiError::NetworkAmbiguous(msg)
+    }
+}
+
+/// True if the source chain shows the request failed while connecting (never sent).
+/// Checks typed io errors first; message matching is only a last resort for transports that
+/// don't expose an io cause.
+fn is_connect_failure(e: &SdkError) -> bool {
+    let chain = || std::iter::successors(Some(e as &(dyn std::error::Error + 'static)), |err| err.source());
+    let typed = chain().any(|err| {
+        err.downcast_ref::<std::io::Error>().is_some_and(|io| {
+            matches!(
+                io.kind(),
+                std::io::ErrorKind::ConnectionRefused
+                    | std::io::ErrorKind::AddrNotAvailable
+                    | std::io::ErrorKind::NotConnected
+            )
+        })
+    });
+    typed
+        || chain().any(|err| {
+            let msg = err.to_string().to_lowercase();
+            msg.contains("dns error") || msg.contains("(connect)") || msg.contains("error trying to connect")
+        })
+}
+
+/// Decode revert data (`Error(string)`, `Panic(uint256)` or a custom error selector), adding a
+/// hint for Gnosis Safe `GSxxx` codes.
+fn describe_revert_data(data: &[u8]) -> String {
+    if data.is_empty() {
+        return "reverted without a reason".to_string();
+    }
+    match alloy_sol_types::decode_revert_reason(data) {
+        Some(reason) => match safe_error_hint(&reason) {
+            Some(hint) => format!("{} ({})", reason, hint),
+            None => reason,
+        },
+        None => format!("undecoded revert data 0x{}", hex::encode(data)),
+    }
+}
+
+/// Meaning of the Safe contract's short revert codes we can hit when redeeming.
+fn safe_error_hint(reason: &str) -> Option<&'static str> {
+    let code = reason.split_whitespace().find(|w| w.starts_with("GS"))?;
+    Some(match code {
+        "GS010" => "not enough gas to execute the Safe transaction",
+        "GS013" => "inner call failed with safeTxGas and gasPrice both 0",
+        "GS020" => "signatures data too short: fewer signatures than the Safe threshold",
+        "GS021" | "GS022" | "GS023" => "invalid contract signature",
+        "GS024" => "invalid contract signature provided",
+        "GS025" => "hash not approved by the signer",
+        "GS026" => "signer is not a Safe owner, or signatures are not sorted by owner address",
+        _ => return None,
+    })
+}
+
+/// Signature blob for `Safe.execTransaction`: one 65-byte `eth_sign` signature per signer
+/// (v + 4 marks an EIP-191 signed hash), ordered by ascending owner address as the Safe requires.
+async fn safe_signatures(signers: &[PrivateKeySigner], safe_tx_hash: B256) -> Result<Vec<u8>> {
+    const EIP191_PREFIX: &[u8] = b"\x19Ethereum Signed Message:\n32";
+    let mut eip191_message = Vec::with_capacity(EIP191_PREFIX.len() + 32);
+    eip191_message.extend_from_slice(EIP191_PREFIX);
+    eip191_message.extend_from_slice(safe_tx_hash.as_slice());
+    let hash_to_sign = keccak256(&eip191_message);
+
+    let mut sorted: Vec<&PrivateKeySigner> = signers.iter().collect();
+    sorted.sort_by_key(|s| s.address());
+    let mut packed = Vec::with_capacity(65 * sorted.len());
+    for signer in sorted {
+        let sig = signer.sign_hash(&hash_to_sign).await
+            .context(format!("Failed to sign Safe transaction hash with {}", signer.address()))?;
+        let mut sig_bytes = sig.as_bytes();
+        let v = sig_bytes[64];
+        sig_bytes[64] = if v == 27 || v == 28 { v + 4 } else { v };
+        packed.extend_from_slice(&sig_bytes);
+    }
+    Ok(packed)
+}
+
+/// Price-to-beat from a Gamma event's structured `eventMetadata.priceToBeat`, on the market or
+/// its event. Values may be JSON numbers or strings; zero counts as absent. Nothing else is a
+/// price: `groupItemThreshold`, for one, only orders markets within a group.
+fn structured_price_to_beat(event: &Value, market: &Value) -> Option<rust_decimal::Decimal> {
+    let number = |v: &Value| match v {
+        Value::Number(n) => rust_decimal::Decimal::from_str(&n.to_string()).ok(),
+        Value::String(s) => rust_decimal::Decimal::from_str(s.trim()).ok(),
+        _ => None,
+    };
+    [
+        market.pointer("/eventMetadata/priceToBeat"),
+        event.pointer("/eventMetadata/priceToBeat"),
+    ]
+    .into_iter()
+    .flatten()
+    .filter_map(number)
+    .find(|p| *p > rust_decimal::Decimal::ZERO)
+}
+
+/// Whether a market can no longer trade: closed, or past its end date.
+fn market_has_ended(market: &MarketDetails, now: i64) -> bool {
+    market.closed
+        || chrono::DateTime::parse_from_rfc3339(&market.end_date_iso).is_ok_and(|end| end.timestamp() <= now)
+}
+
+/// `safeTxGas` for a Safe redemption. Non-zero, so `execTransaction` returns false instead of
+/// reverting when the inner call fails.
+const SAFE_TX_GAS: u64 = 300_000;
+
+/// `Safe.execTransaction` calldata for a plain CALL of `data` on `to`, with no refund and the
+/// same parameters that were hashed by `getTransactionHash`.
+fn safe_exec_calldata(to: Address, data: Vec<u8>, signatures: Vec<u8>) -> Vec<u8> {
+    IGnosisSafe::execTransactionCall {
+        to,
+        value: U256::ZERO,
+        data: Bytes::from(data),
+        operation: 0,
+        safeTxGas: U256::from(SAFE_TX_GAS),
+        baseGas: U256::ZERO,
+        gasPrice: U256::ZERO,
+        gasToken: Address::ZERO,
+        refundReceiver: Address::ZERO,
+        signatures: Bytes::from(signatures),
+    }
+    .abi_encode()
+}
+
+/// Proxy Wallet Factory calldata forwarding one CALL of `data` on `to` through the sender's
+/// proxy wallet: `proxy((uint8,address,uint256,bytes)[])` with a single `(1, to, 0, data)` entry.
+fn proxy_factory_calldata(to: Address, data: &[u8]) -> Vec<u8> {
+    let selector = keccak256("proxy((uint8,address,uint256,bytes)[])".as_bytes());
+    let padded_len = data.len().div_ceil(32) * 32;
+    let mut calldata = Vec::with_capacity(4 + 32 * 7 + padded_len);
+    calldata.extend_from_slice(&selector.as_slice()[..4]);
+    // Offset of the array, its length, then the offset of the only tuple (relative to the array body).
+    calldata.extend_from_slice(&U256::from(32u32).to_be_bytes::<32>());
+    calldata.extend_from_slice(&U256::from(1u32).to_be_bytes::<32>());
+    calldata.extend_from_slice(&U256::from(32u32).to_be_bytes::<32>());
+    // Tuple head: typeCode (1 = CALL), to, value, offset of `data` within the tuple.
+    calldata.extend_from_slice(&U256::from(1u32).to_be_bytes::<32>());
+    let mut to_bytes = [0u8; 32];
+    to_bytes[12..].copy_from_slice(to.as_slice());
+    calldata.extend_from_slice(&to_bytes);
+    calldata.extend_from_slice(&U256::ZERO.to_be_bytes::<32>());
+    calldata.extend_from_slice(&U256::from(128u32).to_be_bytes::<32>());
+    calldata.extend_from_slice(&U256::from(data.len()).to_be_bytes::<32>());
+    calldata.extend_from_slice(data);
+    calldata.resize(calldata.len() + padded_len - data.len(), 0);
+    calldata
 }
 
 impl PolymarketApi {
-    pub fn new(
-        gamma_url: String,
-        clob_url: String,
-        private_key: Option<String>,
-        proxy_wallet_address: Option<String>,
-        signature_type: Option<u8>,
-        rpc_urls: Vec<String>,
-    ) -> Self {
+    pub fn new(config: &PolymarketConfig) -> Self {
         let client = Client::builder()
-            .timeout(std::time::Duration::from_secs(10))
+            .timeout(Duration::from_millis(config.http_timeout_ms))
             .build()
             .expect("Failed to create HTTP client");
         Self {
             client,
-            gamma_url,
-            clob_url,
-            private_key,
-            proxy_wallet_address,
-            signature_type,
-            rpc_urls,
-            clob_auth: OnceLock::new(),
+            gamma_url: config.gamma_api_url.clone(),
+            clob_url: config.clob_api_url.clone(),
+            data_api_url: config.data_api_url.clone(),
+            private_key: config.private_key.clone(),
+            proxy_wallet_address: config.proxy_wallet_address.clone(),
+            signature_type: config.signature_type,
+            safe_cosigner_keys: config.safe_cosigner_keys.clone(),
+            rpc_urls: config.rpc_urls.clone(),
+            redeem_confirm_timeout: Duration::from_secs(config.redeem_confirm_timeout_secs),
+            redeem_max_replacements: config.redeem_max_replacements,
+            redeem_gas_bump_pct: config.redeem_gas_bump_pct,
+            redeem_resolution_delay: Duration::from_secs(config.redeem_resolution_delay_secs),
+            collateral_token: config.collateral_token.clone(),
+            redeem_parent_collection_id: config.redeem_parent_collection_id.unwrap_or(B256::ZERO),
+            outcomes: config.outcomes.clone(),
+            order_timeout: Duration::from_millis(config.order_timeout_ms),
+            book_timeout: Duration::from_millis(config.book_timeout_ms),
+            data_api_timeout: Duration::from_millis(config.data_api_timeout_ms),
+            rpc_timeout: Duration::from_millis(config.rpc_timeout_ms),
+            request_limit: Semaphore::new(config.max_concurrent_requests.max(1)),
+            rate_limited: AtomicU64::new(0),
+            clob_auth: RwLock::new(None),
+            presigned: Mutex::new(HashMap::new()),
         }
     }
 
@@ -121,10 +434,14 @@ impl PolymarketApi {
 
     // Authenticate with Polymarket CLOB API and cache the client for reuse.
     pub async fn authenticate(&self) -> Result<()> {
-        let (signer, client) = self.build_clob_client().await?;
-        self.clob_auth
-            .set((signer, client))
-            .map_err(|_| anyhow::anyhow!("CLOB client already initialized"))?;
+        let auth = self.build_clob_client().await?;
+        {
+            let mut slot = self.clob_auth.write().unwrap();
+            if slot.is_some() {
+                anyhow::bail!("CLOB client already initialized");
+            }
+            *slot = Some(Arc::new(auth));
+        }
 
         eprintln!("   Successfully authenticated with Polymarket CLOB API");
         eprintln!("   Private key: Valid");
@@ -137,21 +454,151 @@ impl PolymarketApi {
         Ok(())
     }
 
+    /// Polygon RPC endpoints in failover order. There is no hidden fallback endpoint: an empty
+    /// `rpc_urls` is an error (omit the field to get the defaults).
+    fn rpc_urls(&self) -> Result<Vec<&str>> {
+        if self.rpc_urls.is_empty() {
+            anyhow::bail!("polymarket.rpc_urls is empty: configure at least one Polygon RPC URL");
+        }
+        Ok(self.rpc_urls.iter().map(|s| s.as_str()).collect())
+    }
+
+    /// Read-only `eth_call` of `calldata` against `to`, trying each RPC URL in order.
+    async fn eth_call(&self, to: Address, calldata: Vec<u8>) -> Result<Vec<u8>> {
+        self.eth_call_as(None, to, calldata, None).await
+    }
+
+    /// `eth_call` with an optional sender and block number. Transport failures and non-revert
+    /// error responses move on to the next URL; a revert is the node's answer and is returned
+    /// with its decoded reason.
+    async fn eth_call_as(
+        &self,
+        from: Option<Address>,
+        to: Address,
+        calldata: Vec<u8>,
+        block: Option<u64>,
+    ) -> Result<Vec<u8>> {
+        let mut last_err = anyhow::anyhow!("no RPC URLs configured for eth_call");
+        for rpc_url in self.rpc_urls()? {
+            let provider = match ProviderBuilder::new().connect(rpc_url).await {
+                Ok(p) => p,
+                Err(e) => {
+                    warn!("eth_call: connect to {} failed: {}", rpc_url, e);
+                    last_err = anyhow::anyhow!("connect to {} failed: {}", rpc_url, e);
+                    continue;
+                }
+            };
+            let mut tx = TransactionRequest::default()
+                .to(to)
+                .input(Bytes::from(calldata.clone()).into());
+            if let Some(from) = from {
+                tx = tx.from(from);
+            }
+            let mut call = provider.call(tx);
+            if let Some(block) = block {
+                call = call.block(BlockId::number(block));
+            }
+            let Ok(result) = tokio::time::timeout(self.rpc_timeout, call).await else {
+                warn!("eth_call via {} timed out after {:?}", rpc_url, self.rpc_timeout);
+                last_err = anyhow::anyhow!("eth_call via {} timed out after {:?}", rpc_url, self.rpc_timeout);
+                continue;
+            };
+            match result {
+                Ok(out) => return Ok(out.to_vec()),
+                Err(e) => {
+                    if let Some(payload) = e.as_error_resp() {
+                        let reason = match payload.as_revert_data() {
+                            Some(data) => Some(describe_revert_data(&data)),
+                            None if payload.message.contains("revert") => Some(payload.message.to_string()),
+                            None => None,
+                        };
+                        if let Some(reason) = reason {
+                            anyhow::bail!("call to {} reverted: {}", to, reason);
+                        }
+                    }
+                    warn!("eth_call via {} failed: {}", rpc_url, e);
+                    last_err = anyhow::anyhow!("eth_call via {} failed: {}", rpc_url, e);
+                }
+            }
+        }
+        Err(last_err)
+    }
+
+    /// Why a mined call failed, found by replaying it with `eth_call` against the state of its
+    /// block. Best effort: the replay can pass if state changed, or fail on a pruned node.
+    async fn replay_revert_reason(&self, from: Address, to: Address, calldata: Vec<u8>, block: Option<u64>) -> String {
+        match self.eth_call_as(Some(from), to, calldata, block).await {
+            Ok(_) => "replay did not revert (state may have changed since)".to_string(),
+            Err(e) => e.to_string(),
+        }
+    }
+
+    /// Typed `eth_call` of a `sol!` view function.
+    async fn view_call<C: SolCall>(&self, to: Address, call: C) -> Result<C::Return> {
+        let raw = self.eth_call(to, call.abi_encode()).await?;
+        C::abi_decode_returns(&raw)
+            .map_err(|e| anyhow::anyhow!("Failed to decode {} return: {}", C::SIGNATURE, e))
+    }
+
+    /// Configured outcome label → Up/Down mapping.
+    pub fn outcome_mapping(&self) -> &OutcomeMapping {
+        &self.outcomes
+    }
+
+    pub fn has_private_key(&self) -> bool {
+        self.private_key.is_some()
+    }
+
     pub fn is_authenticated(&self) -> bool {
-        self.clob_auth.get().is_some()
+        self.clob_auth.read().unwrap().is_some()
+    }
+
+    /// Rebuild the CLOB client after the API rejected our credentials (e.g. a rotated API key).
+    /// If that fails the cached client is dropped, so `is_authenticated()` turns false and the
+    /// bot keeps running monitor-only.
+    pub async fn reauthenticate(&self) -> bool {
+        warn!("CLOB credentials rejected, re-authenticating");
+        match self.build_clob_client().await {
+            Ok(auth) => {
+                *self.clob_auth.write().unwrap() = Some(Arc::new(auth));
+                // Signed under the old credentials.
+                self.clear_presigned();
+                info!("Re-authenticated with Polymarket CLOB API");
+                true
+            }
+            Err(e) => {
+                *self.clob_auth.write().unwrap() = None;
+                self.clear_presigned();
+                error!("Re-authentication failed, continuing monitor-only: {:#}", e);
+                false
+            }
+        }
+    }
+
+    /// Address that holds positions: the proxy wallet if configured, else the private key's EOA.
+    pub fn wallet_address(&self) -> Option<String> {
+        if let Some(proxy) = &self.proxy_wallet_address {
+            return Some(proxy.clone());
+        }
+        let signer = LocalSigner::from_str(self.private_key.as_ref()?).ok()?;
+        Some(signer.address().to_string())
     }
 
     /// Get the cached authenticated CLOB client. Errors if `authenticate()` hasn't been called.
-    fn get_clob_client(&self) -> Result<&(PrivateKeySigner, ClobClient<Authenticated<Normal>>)> {
+    fn get_clob_client(&self) -> Result<Arc<ClobAuth>> {
         self.clob_auth
-            .get()
+            .read()
+            .unwrap()
+            .clone()
             .ok_or_else(|| anyhow::anyhow!("CLOB client not initialized. Call authenticate() first."))
     }
 
     /// Pre-warm the SDK's DashMap cache for fee_rate_bps and tick_size for a token.
     /// Call this during market discovery so the values are cached before the sweep critical path.
-    pub async fn warm_order_cache(&self, token_id: &str) -> Result<()> {
-        let (_, client) = self.get_clob_client()?;
+    /// Returns the token's minimum tick size.
+    pub async fn warm_order_cache(&self, token_id: &str) -> Result<rust_decimal::Decimal> {
+        let auth = self.get_clob_client()?;
+        let (_, client) = &*auth;
         let token_id_u256 = if token_id.starts_with("0x") {
             U256::from_str_radix(token_id.trim_start_matches("0x"), 16)
         } else {
@@ -159,20 +606,108 @@ impl PolymarketApi {
         }
         .context(format!("Failed to parse token_id as U256: {}", token_id))?;
 
-        let _ = client.tick_size(token_id_u256).await?;
+        let tick_size = client.tick_size(token_id_u256).await?.minimum_tick_size.as_decimal();
         let _ = client.fee_rate_bps(token_id_u256).await?;
         info!(
-            "Warmed order cache for token {}",
-            &token_id[..token_id.len().min(20)]
+            "Warmed order cache for token {} (tick {})",
+            short(token_id), tick_size
         );
-        Ok(())
+        Ok(tick_size)
+    }
+
+    /// Send a REST request, retrying on HTTP 429 after the server's `Retry-After`
+    /// (seconds, capped) up to `MAX_RATE_LIMIT_RETRIES` times. The final 429 is returned as-is.
+    async fn send_with_rate_limit(&self, request: RequestBuilder) -> Result<Response> {
+        self.send_retrying(request, None).await
+    }
+
+    /// `send_with_rate_limit` under the shared `request_limit`. The permit is held for each
+    /// attempt and released while waiting out a `Retry-After`, so one 429 doesn't stall every
+    /// other lookup.
+    async fn send_limited(&self, request: RequestBuilder) -> Result<Response> {
+        self.send_retrying(request, Some(&self.request_limit)).await
+    }
+
+    async fn send_retrying(&self, request: RequestBuilder, limit: Option<&Semaphore>) -> Result<Response> {
+        let mut attempt = 0;
+        loop {
+            let req = request
+                .try_clone()
+                .ok_or_else(|| anyhow::anyhow!("request body is not cloneable"))?;
+            let permit = match limit {
+                Some(limit) => Some(limit.acquire().await?),
+                None => None,
+            };
+            let response = req.send().await?;
+            drop(permit);
+            if response.status() != StatusCode::TOO_MANY_REQUESTS || attempt >= MAX_RATE_LIMIT_RETRIES {
+                return Ok(response);
+            }
+            attempt += 1;
+            let wait = response
+                .headers()
+                .get(RETRY_AFTER)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.trim().parse::<u64>().ok())
+                .map(Duration::from_secs)
+                .unwrap_or(DEFAULT_RETRY_AFTER)
+                .min(MAX_RETRY_AFTER);
+            let total = self.rate_limited.fetch_add(1, Ordering::Relaxed) + 1;
+            warn!(
+                "Rate limited (429) on {}, retry {}/{} in {:?} (total 429s: {})",
+                response.url().path(), attempt, MAX_RATE_LIMIT_RETRIES, wait, total
+            );
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Number of HTTP 429 responses received since startup.
+    pub fn rate_limited_count(&self) -> u64 {
+        self.rate_limited.load(Ordering::Relaxed)
+    }
+
+    /// Symbols (lowercase slug prefixes) with an open `{symbol}-updown-{duration}-{ts}` event on
+    /// Gamma closing within the next hour, e.g. `["btc", "eth"]` for `duration` "5m".
+    pub async fn discover_active_updown_markets(&self, duration: &str) -> Result<Vec<String>> {
+        let url = format!("{}/events", self.gamma_url);
+        let now = chrono::Utc::now();
+        let end_min = now.to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+        let end_max = (now + chrono::Duration::hours(1)).to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+        let limit = EVENTS_PAGE_SIZE.to_string();
+        let mut symbols = std::collections::BTreeSet::new();
+        for page in 0..MAX_EVENT_PAGES {
+            let offset = (page * EVENTS_PAGE_SIZE).to_string();
+            let request = self.client.get(&url).query(&[
+                ("active", "true"),
+                ("closed", "false"),
+                ("end_date_min", end_min.as_str()),
+                ("end_date_max", end_max.as_str()),
+                ("limit", limit.as_str()),
+                ("offset", offset.as_str()),
+            ]);
+            let response = self.send_limited(request).await.context("Failed to list Gamma events")?;
+            if !response.status().is_success() {
+                anyhow::bail!("Failed to list Gamma events (status: {})", response.status());
+            }
+            let events: Vec<Value> = response.json().await.context("Failed to parse Gamma events")?;
+            symbols.extend(
+                events
+                    .iter()
+                    .filter_map(|e| e["slug"].as_str())
+                    .filter_map(|slug| crate::discovery::updown_symbol(slug, duration)),
+            );
+            if events.len() < EVENTS_PAGE_SIZE {
+                break;
+            }
+        }
+        Ok(symbols.into_iter().collect())
     }
 
     // Get market by slug (e.g., "btc-updown-5m-1767726000")
     pub async fn get_market_by_slug(&self, slug: &str) -> Result<Market> {
         let url = format!("{}/events/slug/{}", self.gamma_url, slug);
 
-        let response = self.client.get(&url).send().await
+        let response = self.send_limited(self.client.get(&url)).await
             .context(format!("Failed to fetch market by slug: {}", slug))?;
 
         let status = response.status();
@@ -185,7 +720,8 @@ impl PolymarketApi {
 
         if let Some(markets) = json.get("markets").and_then(|m| m.as_array()) {
             if let Some(market_json) = markets.first() {
-                if let Ok(market) = serde_json::from_value::<Market>(market_json.clone()) {
+                if let Ok(mut market) = serde_json::from_value::<Market>(market_json.clone()) {
+                    market.price_to_beat = structured_price_to_beat(&json, market_json);
                     return Ok(market);
                 }
             }
@@ -199,9 +735,7 @@ impl PolymarketApi {
         let url = format!("{}/markets/{}", self.clob_url, condition_id);
 
         let response = self
-            .client
-            .get(&url)
-            .send()
+            .send_limited(self.client.get(&url))
             .await
             .context(format!("Failed to fetch market for condition_id: {}", condition_id))?;
 
@@ -223,20 +757,51 @@ impl PolymarketApi {
         Ok(market)
     }
 
-    /// Place a Fill-or-Kill buy order. Returns Ok(Some(response)) if filled, Ok(None) if not fillable.
-    pub async fn place_fok_buy(&self, token_id: &str, size: &str, price: &str) -> Result<Option<OrderResponse>> {
-        let (signer, client) = self.get_clob_client()?;
+    /// Current orderbooks for several tokens in one round-trip (CLOB `POST /books`), by token
+    /// ID. Tokens the CLOB has no book for are missing from the map.
+    pub async fn get_orderbooks(&self, token_ids: &[&str]) -> Result<HashMap<String, OrderBook>> {
+        if token_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let url = format!("{}/books", self.clob_url);
+        let body: Vec<Value> = token_ids.iter().map(|id| serde_json::json!({ "token_id": id })).collect();
+
+        let response = self
+            .send_limited(self.client.post(&url).json(&body).timeout(self.book_timeout))
+            .await
+            .context(format!("Failed to fetch orderbooks for {} tokens", token_ids.len()))?;
 
+        let status = response.status();
+        if !status.is_success() {
+            anyhow::bail!("Failed to fetch orderbooks (status: {})", status);
+        }
+
+        let books: Vec<TokenOrderBook> = response.json().await.context("Failed to parse orderbooks response")?;
+        Ok(books
+            .into_iter()
+            .map(|b| (b.asset_id, OrderBook { bids: b.bids, asks: b.asks }))
+            .collect())
+    }
+
+    /// Build and sign a FOK buy without sending it. Failures mean nothing was placed.
+    async fn sign_fok_buy(
+        &self,
+        auth: &ClobAuth,
+        token_id: &str,
+        size: &str,
+        price: &str,
+    ) -> std::result::Result<SignedOrder, ApiError> {
+        let (signer, client) = auth;
         let price_dec = rust_decimal::Decimal::from_str(price)
-            .context(format!("Failed to parse price: {}", price))?;
+            .map_err(|e| ApiError::Parse(format!("price {}: {}", price, e)))?;
         let size_dec = rust_decimal::Decimal::from_str(size)
-            .context(format!("Failed to parse size: {}", size))?;
+            .map_err(|e| ApiError::Parse(format!("size {}: {}", size, e)))?;
 
         let token_id_u256 = if token_id.starts_with("0x") {
             U256::from_str_radix(token_id.trim_start_matches("0x"), 16)
         } else {
             U256::from_str_radix(token_id, 10)
-        }.context(format!("Failed to parse token_id as U256: {}", token_id))?;
+        }.map_err(|e| ApiError::Parse(format!("token_id {}: {}", token_id, e)))?;
 
         let order_builder = client
             .limit_order()
@@ -246,78 +811,452 @@ impl PolymarketApi {
             .side(Side::Buy)
             .order_type(OrderType::FOK);
 
-        let signed_order = client.sign(signer, order_builder.build().await?)
-            .await
-            .context("Failed to sign FOK order")?;
+        // Build + sign happen before anything is sent, so a failure or timeout here is safe:
+        // the order was not placed and the sweep can continue with the next ask.
+        let prepared = tokio::time::timeout(self.order_timeout, async {
+            let order = order_builder.build().await?;
+            client.sign(signer, order).await
+        })
+        .await;
+        match prepared {
+            Ok(Ok(order)) => Ok(order),
+            Ok(Err(e)) => {
+                warn!("FOK buy not sent (build/sign failed): {}", e);
+                Err(ApiError::Rejected(format!("build/sign failed: {}", e)))
+            }
+            Err(_) => {
+                warn!("FOK buy not sent (build/sign timed out after {:?})", self.order_timeout);
+                Err(ApiError::Rejected(format!("build/sign timed out after {:?}", self.order_timeout)))
+            }
+        }
+    }
 
-        let response = match client.post_order(signed_order).await {
-            Ok(resp) => resp,
-            Err(e) => {
-                let err_str = e.to_string().to_lowercase();
-                if err_str.contains("timeout") || err_str.contains("timed out")
-                    || err_str.contains("connection") || err_str.contains("connect")
-                    || err_str.contains("broken pipe") || err_str.contains("reset")
-                {
-                    // Network error: order may have been placed — halt sweep
-                    return Err(anyhow::anyhow!("FOK buy network error (order may be placed): {}", e));
+    /// Our resting orders on the CLOB, across all pages, optionally only those in one market
+    /// (condition ID). Needs CLOB credentials: fails with a plain message in monitor mode or
+    /// before `authenticate()`.
+    pub async fn get_open_orders(&self, market: Option<&str>) -> Result<Vec<OpenOrder>> {
+        if !self.has_private_key() {
+            anyhow::bail!("Open orders need CLOB credentials; no private key is configured (monitor mode)");
+        }
+        let auth = self.get_clob_client()?;
+        let (_, client) = &*auth;
+        let market = market
+            .map(|m| B256::from_str(m.strip_prefix("0x").unwrap_or(m)).context(format!("Invalid market condition ID: {}", m)))
+            .transpose()?;
+        let request = OrdersRequest::builder().maybe_market(market).build();
+        let mut orders = Vec::new();
+        let mut cursor = None;
+        for _ in 0..MAX_ORDER_PAGES {
+            let page = client.orders(&request, cursor).await.context("Failed to fetch open orders")?;
+            orders.extend(page.data.into_iter().map(|o| OpenOrder {
+                order_id: o.id,
+                market: o.market.to_string(),
+                token_id: o.asset_id.to_string(),
+                outcome: o.outcome,
+                side: o.side.to_string(),
+                price: o.price.to_f64().unwrap_or(0.0),
+                size: o.original_size.to_f64().unwrap_or(0.0),
+                filled: o.size_matched.to_f64().unwrap_or(0.0),
+            }));
+            if page.next_cursor.is_empty() || page.next_cursor == TERMINAL_CURSOR {
+                break;
+            }
+            cursor = Some(page.next_cursor);
+        }
+        Ok(orders)
+    }
+
+    /// Cancel orders by ID in one request. Returns how many the CLOB cancelled; the rest are
+    /// logged with its reason.
+    pub async fn cancel_orders(&self, order_ids: &[&str]) -> Result<usize> {
+        if order_ids.is_empty() {
+            return Ok(0);
+        }
+        let auth = self.get_clob_client()?;
+        let (_, client) = &*auth;
+        let response = client.cancel_orders(order_ids).await.context("Failed to cancel orders")?;
+        for (id, reason) in &response.not_canceled {
+            warn!("Order {} not cancelled: {}", short(id), reason);
+        }
+        Ok(response.canceled.len())
+    }
+
+    /// `cancel_stale_orders_on_startup`: cancel our resting orders on markets that are closed or
+    /// past their end date. Markets that can't be looked up are left alone. Returns how many
+    /// orders were cancelled.
+    pub async fn cancel_stale_orders(&self) -> Result<usize> {
+        let orders = self.get_open_orders(None).await?;
+        if orders.is_empty() {
+            info!("Startup: no resting orders");
+            return Ok(0);
+        }
+        let mut markets: Vec<&str> = orders.iter().map(|o| o.market.as_str()).collect();
+        markets.sort();
+        markets.dedup();
+        let now = chrono::Utc::now().timestamp();
+        let mut stale: Vec<&str> = Vec::new();
+        for market in markets {
+            match self.get_market(market).await {
+                Ok(details) if market_has_ended(&details, now) => {
+                    stale.extend(orders.iter().filter(|o| o.market == market).map(|o| o.order_id.as_str()));
+                }
+                Ok(_) => {}
+                Err(e) => warn!("Startup: can't check market {} of a resting order, leaving it: {}", short(market), e),
+            }
+        }
+        info!("Startup: {} resting order(s), {} on ended markets", orders.len(), stale.len());
+        self.cancel_orders(&stale).await
+    }
+
+    /// Sign a FOK buy ahead of time; a later `place_fok_buy` with the same token, size and price
+    /// strings posts it without signing on the critical path.
+    pub async fn presign_fok_buy(&self, token_id: &str, size: &str, price: &str) -> Result<()> {
+        let auth = self.get_clob_client()?;
+        let order = self.sign_fok_buy(&auth, token_id, size, price).await?;
+        self.presigned
+            .lock()
+            .unwrap()
+            .insert((token_id.to_string(), size.to_string(), price.to_string()), order);
+        Ok(())
+    }
+
+    /// Drop presigned orders that weren't used (end of round).
+    pub fn clear_presigned(&self) {
+        self.presigned.lock().unwrap().clear();
+    }
+
+    /// Place a Fill-or-Kill buy order. Returns Ok(Some(response)) if filled, Ok(None) if not fillable.
+    /// Errors are typed; only `ApiError::NetworkAmbiguous` means the order may have been placed.
+    pub async fn place_fok_buy(
+        &self,
+        token_id: &str,
+        size: &str,
+        price: &str,
+    ) -> std::result::Result<Option<OrderResponse>, ApiError> {
+        let auth = self.get_clob_client().map_err(|e| ApiError::Auth(e.to_string()))?;
+        let (_, client) = &*auth;
+
+        let key = (token_id.to_string(), size.to_string(), price.to_string());
+        let presigned = self.presigned.lock().unwrap().remove(&key);
+        let signed_order = match presigned {
+            Some(order) => {
+                debug!("FOK buy {} @ {}: using presigned order", size, price);
+                order
+            }
+            None => {
+                let started = std::time::Instant::now();
+                let order = self.sign_fok_buy(&auth, token_id, size, price).await?;
+                debug!("FOK buy {} @ {}: signed in {:?}", size, price, started.elapsed());
+                order
+            }
+        };
+
+        let response = match tokio::time::timeout(self.order_timeout, client.post_order(signed_order)).await {
+            Ok(Ok(resp)) => resp,
+            Ok(Err(e)) => {
+                let err = classify_sdk_error(&e);
+                if matches!(err, ApiError::Auth(_)) && e.kind() == SdkErrorKind::Status {
+                    // This order is lost either way; re-auth so the next one can go through.
+                    self.reauthenticate().await;
                 }
-                // API rejection: order was not placed — skip and continue
-                warn!("FOK buy rejected (unfillable): {}", e);
-                return Ok(None);
+                return Err(err);
+            }
+            Err(_) => {
+                // Request was in flight when the deadline hit — ambiguous, halt sweep
+                return Err(ApiError::NetworkAmbiguous(format!("timeout after {:?}", self.order_timeout)));
             }
         };
 
         if !response.success {
-            // Order rejected — not fillable at this price/size
+            // FOK killed — not fillable at this price/size
             return Ok(None);
         }
 
+        // Buy side: the maker amount is USDC paid, the taker amount is shares received.
+        let amount = |d: rust_decimal::Decimal| (!d.is_zero()).then(|| d.to_string().parse::<f64>().ok()).flatten();
         Ok(Some(OrderResponse {
             order_id: Some(response.order_id.clone()),
             status: response.status.to_string(),
             message: Some(format!("FOK buy filled. Order ID: {}", response.order_id)),
+            filled_shares: amount(response.taking_amount),
+            filled_cost: amount(response.making_amount),
         }))
     }
 
-    pub async fn get_redeemable_positions(&self, wallet: &str) -> Result<Vec<String>> {
-        let url = "https://data-api.polymarket.com/positions";
+    /// Chainlink on-chain price in effect at `at_ts` (Unix seconds): the latest aggregator round
+    /// with `updatedAt <= at_ts`, found by walking back from `latestRoundData`.
+    /// Returns (price, round updated_at).
+    pub async fn get_chainlink_price_rpc(&self, aggregator: &str, at_ts: i64) -> Result<(rust_decimal::Decimal, i64)> {
+        let aggregator = Address::from_str(aggregator)
+            .context(format!("Invalid Chainlink aggregator address: {}", aggregator))?;
+
+        let decimals = self.view_call(aggregator, IAggregatorV3::decimalsCall {}).await
+            .context("Chainlink decimals()")?;
+        let latest = self.view_call(aggregator, IAggregatorV3::latestRoundDataCall {}).await
+            .context("Chainlink latestRoundData()")?;
+        let (mut round_id, mut answer, mut updated_at) = (latest.roundId, latest.answer, latest.updatedAt);
+
+        let target = U256::from(at_ts.max(0) as u64);
+        let mut steps = 0;
+        while updated_at > target {
+            if steps >= MAX_CHAINLINK_ROUND_STEPS {
+                anyhow::bail!("no Chainlink round at or before {} within {} rounds", at_ts, MAX_CHAINLINK_ROUND_STEPS);
+            }
+            round_id -= alloy::primitives::aliases::U80::from(1);
+            let round = self.view_call(aggregator, IAggregatorV3::getRoundDataCall { _roundId: round_id }).await
+                .context(format!("Chainlink getRoundData({})", round_id))?;
+            answer = round.answer;
+            updated_at = round.updatedAt;
+            steps += 1;
+        }
+
+        let answer = answer.to_string().parse::<i128>().context("Invalid Chainlink answer")?;
+        let price = rust_decimal::Decimal::try_from_i128_with_scale(answer, decimals as u32)
+            .context("Chainlink answer out of range")?;
+        Ok((price, updated_at.to::<u64>() as i64))
+    }
+
+    /// USDC (collateral) balance of `wallet`, in whole units.
+    pub async fn usdc_balance(&self, wallet: &str) -> Result<f64> {
+        let account = Address::from_str(wallet)
+            .context(format!("Invalid wallet address: {}", wallet))?;
+        let decimals = self.view_call(USDC_ADDRESS, IERC20::decimalsCall {}).await
+            .context("USDC decimals()")?;
+        let balance = self.view_call(USDC_ADDRESS, IERC20::balanceOfCall { account }).await
+            .context("USDC balanceOf()")?;
+        let balance = balance.to_string().parse::<f64>().context("Invalid USDC balance")?;
+        Ok(balance / 10f64.powi(decimals as i32))
+    }
+
+    /// Positions held by `wallet` from the data API, optionally only redeemable ones.
+    pub async fn get_positions(&self, wallet: &str, redeemable_only: bool) -> Result<Vec<Position>> {
+        let url = format!("{}/positions", self.data_api_url.trim_end_matches('/'));
         let user = if wallet.starts_with("0x") {
             wallet.to_string()
         } else {
             format!("0x{}", wallet)
         };
-        let response = self.client
-            .get(url)
-            .query(&[("user", user.as_str()), ("redeemable", "true"), ("limit", "500")])
-            .send()
+        let mut query = vec![("user", user.as_str()), ("limit", "500")];
+        if redeemable_only {
+            query.push(("redeemable", "true"));
+        }
+        let request = self.client.get(&url).query(&query).timeout(self.data_api_timeout);
+        let response = self.send_with_rate_limit(request)
+            .await
+            .context("Failed to fetch positions")?;
+        if !response.status().is_success() {
+            anyhow::bail!("Data API returned {} for positions", response.status());
+        }
+        Ok(response.json().await.unwrap_or_default())
+    }
+
+    /// Trades of `token_id` in market `condition_id` strictly after `after_ts` (Unix seconds),
+    /// oldest first. Uses the data API's public `/trades`: the CLOB's own trades endpoint only
+    /// returns the authenticated user's trades. Only the latest 1000 trades of the market are read.
+    pub async fn get_trades(&self, condition_id: &str, token_id: &str, after_ts: i64) -> Result<Vec<Trade>> {
+        let url = format!("{}/trades", self.data_api_url.trim_end_matches('/'));
+        let request = self
+            .client
+            .get(&url)
+            .query(&[("market", condition_id), ("limit", "1000")])
+            .timeout(self.data_api_timeout);
+        let response = self.send_limited(request)
             .await
-            .context("Failed to fetch redeemable positions")?;
+            .context(format!("Failed to fetch trades for {}", condition_id))?;
         if !response.status().is_success() {
-            anyhow::bail!("Data API returned {} for redeemable positions", response.status());
+            anyhow::bail!("Data API returned {} for trades", response.status());
         }
-        let positions: Vec<Value> = response.json().await.unwrap_or_default();
+        let trades: Vec<Trade> = response.json().await.context("Failed to parse trades response")?;
+        let mut trades: Vec<Trade> = trades
+            .into_iter()
+            .filter(|t| t.asset == token_id && t.timestamp > after_ts)
+            .collect();
+        trades.sort_by_key(|t| t.timestamp);
+        Ok(trades)
+    }
+
+    pub async fn get_redeemable_positions(&self, wallet: &str) -> Result<Vec<String>> {
+        let positions = self.get_positions(wallet, true).await?;
         let mut condition_ids: Vec<String> = positions
             .iter()
-            .filter(|p| {
-                let size = p.get("size")
-                    .and_then(|s| s.as_f64())
-                    .or_else(|| p.get("size").and_then(|s| s.as_u64().map(|u| u as f64)))
-                    .or_else(|| p.get("size").and_then(|s| s.as_str()).and_then(|s| s.parse::<f64>().ok()));
-                size.map(|s| s > 0.0).unwrap_or(false)
+            .filter(|p| p.size > 0.0 && !p.condition_id.is_empty())
+            .map(|p| {
+                let c = &p.condition_id;
+                if c.starts_with("0x") { c.clone() } else { format!("0x{}", c) }
             })
-            .filter_map(|p| p.get("conditionId").and_then(|c| c.as_str()).map(|s| {
-                if s.starts_with("0x") { s.to_string() } else { format!("0x{}", s) }
-            }))
             .collect();
         condition_ids.sort();
         condition_ids.dedup();
         Ok(condition_ids)
     }
 
+    /// Owner keys that will sign a Safe transaction: the main key plus configured cosigners,
+    /// trimmed to `threshold`. Fails before anything is sent if a key is not an owner or there
+    /// are fewer owner keys than the threshold.
+    fn safe_signers(
+        &self,
+        signer: &PrivateKeySigner,
+        owners: &[Address],
+        threshold: U256,
+    ) -> Result<Vec<PrivateKeySigner>> {
+        let mut signers = vec![signer.clone()];
+        for (i, key) in self.safe_cosigner_keys.iter().enumerate() {
+            let cosigner = LocalSigner::from_str(key)
+                .context(format!("Invalid safe_cosigner_keys[{}]", i))?;
+            if signers.iter().any(|s| s.address() == cosigner.address()) {
+                warn!("safe_cosigner_keys[{}] duplicates signer {}; ignoring", i, cosigner.address());
+                continue;
+            }
+            signers.push(cosigner);
+        }
+        for s in &signers {
+            if !owners.contains(&s.address()) {
+                anyhow::bail!(
+                    "{} is not an owner of the Safe (owners: {:?}); check private_key and safe_cosigner_keys",
+                    s.address(),
+                    owners
+                );
+            }
+        }
+        let needed = threshold.saturating_to::<usize>();
+        if signers.len() < needed {
+            anyhow::bail!(
+                "Safe requires {} signatures but only {} owner key(s) are configured; \
+                add the other owners' keys to polymarket.safe_cosigner_keys (or SAFE_COSIGNER_KEYS)",
+                needed,
+                signers.len()
+            );
+        }
+        signers.truncate(needed.max(1));
+        Ok(signers)
+    }
+
+    /// Index sets `redeem_tokens` passes to `redeemPositions` for `outcome`: both outcomes
+    /// through a Gnosis Safe proxy, otherwise only the outcome's own (Up = 1, Down = 2).
+    fn redeem_index_sets(&self, outcome: &str) -> Vec<U256> {
+        if self.proxy_wallet_address.is_some() && self.signature_type.unwrap_or(1) == 2 {
+            vec![U256::from(1), U256::from(2)]
+        } else if self.outcomes.side(outcome) == Some(OutcomeSide::Up) {
+            vec![U256::from(1)]
+        } else {
+            vec![U256::from(2)]
+        }
+    }
+
+    /// Read-only preview of `redeem_tokens` for `condition_id`: the winning outcome from
+    /// `get_market`, the index sets that would be redeemed, and the winning shares in
+    /// `positions` (None if positions couldn't be read) with their $1-per-share payout.
+    pub async fn redeem_preview(&self, condition_id: &str, positions: Option<&[Position]>) -> Result<RedeemPreview> {
+        let market = self.get_market(condition_id).await?;
+        let winner = market.tokens.iter().find(|t| t.winner);
+        let shares = match (winner, positions) {
+            (Some(token), Some(positions)) => Some(
+                positions.iter().filter(|p| p.asset == token.token_id).map(|p| p.size).sum::<f64>(),
+            ),
+            (None, Some(_)) => Some(0.0),
+            (_, None) => None,
+        };
+        let outcome = winner.map(|t| t.outcome.clone());
+        let index_sets = self
+            .redeem_index_sets(outcome.as_deref().unwrap_or("Up"))
+            .iter()
+            .map(|s| s.to::<u64>())
+            .collect();
+        Ok(RedeemPreview {
+            condition_id: condition_id.to_string(),
+            question: market.question,
+            winner: outcome,
+            index_sets,
+            shares,
+        })
+    }
+
+    /// Check that `condition_id` is closed with `outcome` as its winner before redeeming it.
+    /// With `redeem_resolution_delay_secs`, wait that long and check again.
+    pub async fn verify_resolution(&self, condition_id: &str, outcome: &str) -> Result<()> {
+        self.check_resolved(condition_id, outcome).await?;
+        if !self.redeem_resolution_delay.is_zero() {
+            info!(
+                "Redeem {}: resolved {}, re-checking in {:?}",
+                condition_id, outcome, self.redeem_resolution_delay
+            );
+            tokio::time::sleep(self.redeem_resolution_delay).await;
+            self.check_resolved(condition_id, outcome).await?;
+        }
+        info!("Redeem {}: verified closed with winner {}", condition_id, outcome);
+        Ok(())
+    }
+
+    async fn check_resolved(&self, condition_id: &str, outcome: &str) -> Result<()> {
+        let market = self.get_market(condition_id).await?;
+        if !market.closed {
+            anyhow::bail!("market {} is not closed yet", condition_id);
+        }
+        // Compare sides, not labels: the winner may be reported as "Up" for an outcome we hold as "1".
+        let expected = self.outcomes.side(outcome);
+        match market.tokens.iter().find(|t| t.winner) {
+            Some(t) if expected.is_some() && self.outcomes.side(&t.outcome) == expected => Ok(()),
+            Some(t) => anyhow::bail!("market {} resolved {}, not {}", condition_id, t.outcome, outcome),
+            None => anyhow::bail!("market {} is closed but has no winner yet", condition_id),
+        }
+    }
+
+    /// Collateral the market's outcome tokens were minted against: the configured
+    /// `collateral_token` first, then the other known USDC addresses. A CTF position ID is
+    /// `keccak256(collateral ++ collectionId)`, so the right collateral is the one whose position ID
+    /// for index set 1 is one of the market's token IDs. Redeeming with any other collateral reverts.
+    async fn redeem_collateral(
+        &self,
+        market: &MarketDetails,
+        condition: B256,
+        parent_collection_id: B256,
+    ) -> Result<Address> {
+        let condition_id = &market.condition_id;
+        let configured = Address::from_str(&self.collateral_token)
+            .context(format!("Invalid polymarket.collateral_token: {}", self.collateral_token))?;
+        let collection = self
+            .view_call(
+                CTF_ADDRESS,
+                IConditionalTokens::getCollectionIdCall {
+                    parentCollectionId: parent_collection_id,
+                    conditionId: condition,
+                    indexSet: U256::from(1),
+                },
+            )
+            .await
+            .context("Failed to call CTF.getCollectionId()")?;
+        let mut candidates = vec![configured];
+        for known in [USDC_ADDRESS, NATIVE_USDC_ADDRESS] {
+            if !candidates.contains(&known) {
+                candidates.push(known);
+            }
+        }
+        for collateral in &candidates {
+            let position_id = U256::from_be_bytes(keccak256([collateral.as_slice(), collection.as_slice()].concat()).0);
+            if market.tokens.iter().any(|t| t.token_id == position_id.to_string()) {
+                if *collateral != configured {
+                    warn!(
+                        "Redeem {}: market tokens use collateral {}, not the configured {}",
+                        condition_id, collateral, configured
+                    );
+                }
+                return Ok(*collateral);
+            }
+        }
+        anyhow::bail!(
+            "None of the collateral tokens {:?} match the outcome tokens of market {}; set polymarket.collateral_token to its collateral",
+            candidates,
+            condition_id
+        )
+    }
+
+    /// Redeem winning tokens for `condition_id`. The transaction is simulated with `eth_call`
+    /// first and not sent if the simulation fails; with `simulate_only` it is never sent.
     pub async fn redeem_tokens(
         &self,
         condition_id: &str,
         outcome: &str,
+        simulate_only: bool,
     ) -> Result<RedeemResponse> {
         let private_key = self.private_key.as_ref()
             .ok_or_else(|| anyhow::anyhow!("Private key is required for order signing. Please set private_key in config.json"))?;
@@ -334,42 +1273,42 @@ impl PolymarketApi {
             Ok(Address::from(arr))
         };
 
-        let collateral_token = parse_address_hex("0x2791Bca1f2de4661ED88A30C99A7a9449Aa84174")
-            .context("Failed to parse USDC address")?;
-
         let condition_id_clean = condition_id.strip_prefix("0x").unwrap_or(condition_id);
         let condition_id_b256 = B256::from_str(condition_id_clean)
             .context(format!("Failed to parse condition_id as B256: {}", condition_id))?;
 
-        let index_set = if outcome.to_uppercase().contains("UP") || outcome == "1" {
-            U256::from(1)
-        } else {
-            U256::from(2)
-        };
+        let index_sets = self.redeem_index_sets(outcome);
 
-        eprintln!("Redeeming winning tokens for condition {} (outcome: {}, index_set: {})",
-              condition_id, outcome, index_set);
+        eprintln!("Redeeming winning tokens for condition {} (outcome: {}, index_sets: {:?})",
+              condition_id, outcome, index_sets);
 
-        const CTF_CONTRACT: &str = "0x4d97dcd97ec945f40cf65f87097ace5ea0476045";
-        let rpc_url = self.rpc_urls.first().map(|s| s.as_str()).unwrap_or("https://polygon-rpc.com");
         const PROXY_WALLET_FACTORY: &str = "0xaB45c5A4B0c941a2F231C04C3f49182e1A254052";
 
-        let ctf_address = parse_address_hex(CTF_CONTRACT)
-            .context("Failed to parse CTF contract address")?;
-
-        let parent_collection_id = B256::ZERO;
+        let ctf_address = CTF_ADDRESS;
+
+        let parent_collection_id = self.redeem_parent_collection_id;
+        let market = self.get_market(condition_id).await?;
+        if market.neg_risk && parent_collection_id.is_zero() {
+            anyhow::bail!(
+                "market {} is a neg-risk market: its positions are redeemed through Polymarket's NegRiskAdapter, \
+                not CTF.redeemPositions with a zero parent collection (that would revert). Redeem it on polymarket.com, \
+                or pass --parent-collection-id / set polymarket.redeem_parent_collection_id if it is a nested condition",
+                condition_id
+            );
+        }
+        let collateral_token = self
+            .redeem_collateral(&market, condition_id_b256, parent_collection_id)
+            .await?;
         let use_proxy = self.proxy_wallet_address.is_some();
         let sig_type = self.signature_type.unwrap_or(1);
-        let index_sets: Vec<U256> = if use_proxy && sig_type == 2 {
-            vec![U256::from(1), U256::from(2)]
-        } else {
-            vec![index_set]
-        };
 
         eprintln!("   Prepared redemption parameters:");
         eprintln!("   - CTF Contract: {}", ctf_address);
-        eprintln!("   - Collateral token (USDC): {}", collateral_token);
+        eprintln!("   - Collateral token: {}", collateral_token);
         eprintln!("   - Condition ID: {} ({:?})", condition_id, condition_id_b256);
+        if !parent_collection_id.is_zero() {
+            eprintln!("   - Parent collection ID: {}", parent_collection_id);
+        }
         eprintln!("   - Index set(s): {:?} (outcome: {})", index_sets, outcome);
 
         let redeem_call = IConditionalTokens::redeemPositionsCall {
@@ -379,6 +1318,7 @@ impl PolymarketApi {
             indexSets: index_sets.clone(),
         };
         let redeem_calldata = redeem_call.abi_encode();
+        let inner_redeem_calldata = redeem_calldata.clone();
 
         let (tx_to, tx_data, gas_limit, used_safe_redemption) = if use_proxy && sig_type == 2 {
             let safe_address_str = self.proxy_wallet_address.as_deref()
@@ -386,217 +1326,817 @@ impl PolymarketApi {
             let safe_address = parse_address_hex(safe_address_str)
                 .context("Failed to parse proxy_wallet_address (Safe address)")?;
             eprintln!("   Using Gnosis Safe (proxy): signing and executing redemption via Safe.execTransaction");
-            let nonce_selector = keccak256("nonce()".as_bytes());
-            let nonce_calldata: Vec<u8> = nonce_selector.as_slice()[..4].to_vec();
-            let provider_read = ProviderBuilder::new()
-                .connect(rpc_url)
-                .await
-                .context("Failed to connect to RPC for Safe read calls")?;
-            let nonce_tx = TransactionRequest::default()
-                .to(safe_address)
-                .input(Bytes::from(nonce_calldata.clone()).into());
-            let nonce_result = provider_read.call(nonce_tx).await
+            let nonce = self.view_call(safe_address, IGnosisSafe::nonceCall {}).await
                 .map_err(|e| anyhow::anyhow!("Failed to call Safe.nonce() on {}: {}. \
                     If you use MagicLink/email login, your proxy is a Polymarket custom proxy, not a Gnosis Safe; \
                     redemption via Safe is only supported for MetaMask (Gnosis Safe) proxies.",
                     safe_address_str, e))?;
-            let nonce_bytes: [u8; 32] = nonce_result.as_ref().try_into()
-                .map_err(|_| anyhow::anyhow!("Safe.nonce() did not return 32 bytes"))?;
-            let nonce = U256::from_be_slice(&nonce_bytes);
-            const SAFE_TX_GAS: u64 = 300_000;
-            let get_tx_hash_sig = "getTransactionHash(address,uint256,bytes,uint8,uint256,uint256,uint256,address,address,uint256)";
-            let get_tx_hash_selector = keccak256(get_tx_hash_sig.as_bytes()).as_slice()[..4].to_vec();
-            let zero_addr = [0u8; 32];
-            let mut to_enc = [0u8; 32];
-            to_enc[12..].copy_from_slice(ctf_address.as_slice());
-            let data_offset_get_hash = U256::from(32u32 * 10u32);
-            let mut get_tx_hash_calldata = Vec::new();
-            get_tx_hash_calldata.extend_from_slice(&get_tx_hash_selector);
-            get_tx_hash_calldata.extend_from_slice(&to_enc);
-            get_tx_hash_calldata.extend_from_slice(&U256::ZERO.to_be_bytes::<32>());
-            get_tx_hash_calldata.extend_from_slice(&data_offset_get_hash.to_be_bytes::<32>());
-            get_tx_hash_calldata.push(0); get_tx_hash_calldata.extend_from_slice(&[0u8; 31]);
-            get_tx_hash_calldata.extend_from_slice(&U256::from(SAFE_TX_GAS).to_be_bytes::<32>());
-            get_tx_hash_calldata.extend_from_slice(&U256::ZERO.to_be_bytes::<32>());
-            get_tx_hash_calldata.extend_from_slice(&U256::ZERO.to_be_bytes::<32>());
-            get_tx_hash_calldata.extend_from_slice(&zero_addr);
-            get_tx_hash_calldata.extend_from_slice(&zero_addr);
-            get_tx_hash_calldata.extend_from_slice(&nonce.to_be_bytes::<32>());
-            get_tx_hash_calldata.extend_from_slice(&U256::from(redeem_calldata.len()).to_be_bytes::<32>());
-            get_tx_hash_calldata.extend_from_slice(&redeem_calldata);
-            let get_tx_hash_tx = TransactionRequest::default()
-                .to(safe_address)
-                .input(Bytes::from(get_tx_hash_calldata).into());
-            let tx_hash_result = provider_read.call(get_tx_hash_tx).await
+            let tx_hash_to_sign = self
+                .view_call(
+                    safe_address,
+                    IGnosisSafe::getTransactionHashCall {
+                        to: ctf_address,
+                        value: U256::ZERO,
+                        data: Bytes::from(redeem_calldata.clone()),
+                        operation: 0,
+                        safeTxGas: U256::from(SAFE_TX_GAS),
+                        baseGas: U256::ZERO,
+                        gasPrice: U256::ZERO,
+                        gasToken: Address::ZERO,
+                        refundReceiver: Address::ZERO,
+                        _nonce: nonce,
+                    },
+                )
+                .await
                 .context("Failed to call Safe.getTransactionHash()")?;
-            let tx_hash_to_sign: B256 = tx_hash_result.as_ref().try_into()
-                .map_err(|_| anyhow::anyhow!("getTransactionHash did not return 32 bytes"))?;
-            const EIP191_PREFIX: &[u8] = b"\x19Ethereum Signed Message:\n32";
-            let mut eip191_message = Vec::with_capacity(EIP191_PREFIX.len() + 32);
-            eip191_message.extend_from_slice(EIP191_PREFIX);
-            eip191_message.extend_from_slice(tx_hash_to_sign.as_slice());
-            let hash_to_sign = keccak256(&eip191_message);
-            let sig = signer.sign_hash(&hash_to_sign).await
-                .context("Failed to sign Safe transaction hash")?;
-            let sig_bytes = sig.as_bytes();
-            let r = &sig_bytes[0..32];
-            let s = &sig_bytes[32..64];
-            let v = sig_bytes[64];
-            let v_safe = if v == 27 || v == 28 { v + 4 } else { v };
-            let mut packed_sig: Vec<u8> = Vec::with_capacity(85);
-            packed_sig.extend_from_slice(r);
-            packed_sig.extend_from_slice(s);
-            packed_sig.extend_from_slice(&[v_safe]);
-            let get_threshold_selector = keccak256("getThreshold()".as_bytes()).as_slice()[..4].to_vec();
-            let threshold_tx = TransactionRequest::default()
-                .to(safe_address)
-                .input(Bytes::from(get_threshold_selector).into());
-            let threshold_result = provider_read.call(threshold_tx).await
+            let threshold = self.view_call(safe_address, IGnosisSafe::getThresholdCall {}).await
                 .context("Failed to call Safe.getThreshold()")?;
-            let threshold_bytes: [u8; 32] = threshold_result.as_ref().try_into()
-                .map_err(|_| anyhow::anyhow!("getThreshold did not return 32 bytes"))?;
-            let threshold = U256::from_be_slice(&threshold_bytes);
-            if threshold > U256::from(1) {
-                let owner = signer.address();
-                let mut with_owner = Vec::with_capacity(20 + packed_sig.len());
-                with_owner.extend_from_slice(owner.as_slice());
-                with_owner.extend_from_slice(&packed_sig);
-                packed_sig = with_owner;
-            }
-            let safe_sig_bytes = packed_sig;
-            let exec_sig = "execTransaction(address,uint256,bytes,uint8,uint256,uint256,uint256,address,address,bytes)";
-            let exec_selector = keccak256(exec_sig.as_bytes()).as_slice()[..4].to_vec();
-            let data_offset = 32u32 * 10u32;
-            let sigs_offset = data_offset + 32 + redeem_calldata.len() as u32;
-            let mut exec_calldata = Vec::new();
-            exec_calldata.extend_from_slice(&exec_selector);
-            exec_calldata.extend_from_slice(&to_enc);
-            exec_calldata.extend_from_slice(&U256::ZERO.to_be_bytes::<32>());
-            exec_calldata.extend_from_slice(&U256::from(data_offset).to_be_bytes::<32>());
-            exec_calldata.push(0); exec_calldata.extend_from_slice(&[0u8; 31]);
-            exec_calldata.extend_from_slice(&U256::from(SAFE_TX_GAS).to_be_bytes::<32>());
-            exec_calldata.extend_from_slice(&U256::ZERO.to_be_bytes::<32>());
-            exec_calldata.extend_from_slice(&U256::ZERO.to_be_bytes::<32>());
-            exec_calldata.extend_from_slice(&zero_addr);
-            exec_calldata.extend_from_slice(&zero_addr);
-            exec_calldata.extend_from_slice(&U256::from(sigs_offset).to_be_bytes::<32>());
-            exec_calldata.extend_from_slice(&U256::from(redeem_calldata.len()).to_be_bytes::<32>());
-            exec_calldata.extend_from_slice(&redeem_calldata);
-            exec_calldata.extend_from_slice(&U256::from(safe_sig_bytes.len()).to_be_bytes::<32>());
-            exec_calldata.extend_from_slice(&safe_sig_bytes);
+            let owners = self.view_call(safe_address, IGnosisSafe::getOwnersCall {}).await
+                .context("Failed to call Safe.getOwners()")?;
+            let signers = self.safe_signers(&signer, &owners, threshold)?;
+            let packed_sig = safe_signatures(&signers, tx_hash_to_sign).await?;
+            let exec_calldata = safe_exec_calldata(ctf_address, redeem_calldata.clone(), packed_sig);
             (safe_address, exec_calldata, 400_000u64, true)
         } else if use_proxy && sig_type == 1 {
             eprintln!("   Using proxy wallet: sending redemption via Proxy Wallet Factory");
             let factory_address = parse_address_hex(PROXY_WALLET_FACTORY)
                 .context("Failed to parse Proxy Wallet Factory address")?;
-            let selector = keccak256("proxy((uint8,address,uint256,bytes)[])".as_bytes());
-            let proxy_selector = &selector.as_slice()[..4];
-            let mut proxy_calldata = Vec::with_capacity(4 + 32 * 3 + 128 + 32 + redeem_calldata.len());
-            proxy_calldata.extend_from_slice(proxy_selector);
-            proxy_calldata.extend_from_slice(&U256::from(32u32).to_be_bytes::<32>());
-            proxy_calldata.extend_from_slice(&U256::from(1u32).to_be_bytes::<32>());
-            proxy_calldata.extend_from_slice(&U256::from(96u32).to_be_bytes::<32>());
-            let mut type_code = [0u8; 32];
-            type_code[31] = 1;
-            proxy_calldata.extend_from_slice(&type_code);
-            let mut to_bytes = [0u8; 32];
-            to_bytes[12..].copy_from_slice(ctf_address.as_slice());
-            proxy_calldata.extend_from_slice(&to_bytes);
-            proxy_calldata.extend_from_slice(&U256::ZERO.to_be_bytes::<32>());
-            proxy_calldata.extend_from_slice(&U256::from(128u32).to_be_bytes::<32>());
-            let data_len = redeem_calldata.len();
-            proxy_calldata.extend_from_slice(&U256::from(data_len).to_be_bytes::<32>());
-            proxy_calldata.extend_from_slice(&redeem_calldata);
+            let proxy_calldata = proxy_factory_calldata(ctf_address, &redeem_calldata);
             (factory_address, proxy_calldata, 400_000u64, false)
         } else {
             eprintln!("   Sending redemption from EOA to CTF contract");
             (ctf_address, redeem_calldata, 300_000, false)
         };
 
-        // Try each RPC URL for sending the redemption transaction
-        let redeem_urls: Vec<&str> = if self.rpc_urls.is_empty() {
-            vec!["https://polygon-rpc.com"]
-        } else {
-            self.rpc_urls.iter().map(|s| s.as_str()).collect()
+        eprintln!("   Simulating redemption (eth_call from {})...", signer.address());
+        let simulated = self
+            .eth_call_as(Some(signer.address()), tx_to, tx_data.clone(), None)
+            .await
+            .context("Redemption simulation failed; transaction not sent")?;
+        if used_safe_redemption {
+            // execTransaction doesn't revert when the inner call fails (safeTxGas > 0); it returns false.
+            let executed = IGnosisSafe::execTransactionCall::abi_decode_returns(&simulated)
+                .context("Failed to decode simulated Safe.execTransaction() result")?;
+            if !executed {
+                let reason = self
+                    .replay_revert_reason(tx_to, ctf_address, inner_redeem_calldata.clone(), None)
+                    .await;
+                anyhow::bail!("Redemption simulation: the Safe's inner redeem would fail: {}; transaction not sent", reason);
+            }
+        }
+        eprintln!("   Simulation passed.");
+        if simulate_only {
+            return Ok(RedeemResponse {
+                success: true,
+                message: Some("Simulation passed; transaction not sent (--simulate-only)".to_string()),
+                transaction_hash: None,
+                amount_redeemed: None,
+            });
+        }
+
+        let (tx_hash, receipt, confirmed) = self
+            .send_redemption(&signer, tx_to, tx_data.clone(), gas_limit)
+            .await?;
+
+        if !receipt.status() {
+            let reason = self
+                .replay_revert_reason(signer.address(), tx_to, tx_data.clone(), receipt.block_number)
+                .await;
+            anyhow::bail!("Redemption transaction reverted: {}. Transaction hash: {:?}", reason, tx_hash);
+        }
+
+        if used_safe_redemption {
+            let logs = receipt.logs();
+            let safe_failed = logs.iter().any(|log| {
+                log.address() == tx_to
+                    && log.topics().first() == Some(&IGnosisSafe::ExecutionFailure::SIGNATURE_HASH)
+            });
+            if safe_failed {
+                // The Safe caught the inner revert; replay the inner call as the Safe to see why.
+                let reason = self
+                    .replay_revert_reason(tx_to, ctf_address, inner_redeem_calldata.clone(), receipt.block_number)
+                    .await;
+                anyhow::bail!(
+                    "Redemption tx was mined but the Safe reported ExecutionFailure for the inner redeem: {}. Tx: {:?}",
+                    reason,
+                    tx_hash
+                );
+            }
+            let payout_redemption_topic = keccak256(
+                b"PayoutRedemption(address,address,bytes32,bytes32,uint256[],uint256)"
+            );
+            let ctf_has_payout = logs.iter().any(|log| {
+                log.address() == ctf_address && log.topics().first().map(|t| t.as_slice()) == Some(payout_redemption_topic.as_slice())
+            });
+            if !ctf_has_payout {
+                anyhow::bail!(
+                    "Redemption tx was mined but the inner redeem reverted (no PayoutRedemption from CTF). \
+                    Check that the Safe holds the winning tokens and conditionId/indexSet are correct. Tx: {:?}",
+                    tx_hash
+                );
+            }
+        }
+
+        let redeem_response = RedeemResponse {
+            success: true,
+            message: Some(format!("Successfully redeemed tokens. Transaction: {:?} ({})", tx_hash, confirmed)),
+            transaction_hash: Some(format!("{:?}", tx_hash)),
+            amount_redeemed: None,
         };
+        eprintln!("Successfully redeemed winning tokens!");
+        eprintln!("Transaction hash: {:?}", tx_hash);
+        if let Some(block_number) = receipt.block_number {
+            eprintln!("Block number: {}", block_number);
+        }
+        Ok(redeem_response)
+    }
+
+    /// Send a redemption with an explicit (pending) nonce and wait for it to mine. If it isn't
+    /// mined within `redeem_confirm_timeout`, resend the same nonce with fees bumped by
+    /// `redeem_gas_bump_pct`, up to `redeem_max_replacements` times; whichever of the original or
+    /// a replacement mines first wins. Replacement only happens once a send has returned a hash:
+    /// if the first send fails the error is returned as is. Returns (hash, receipt, which mined).
+    async fn send_redemption(
+        &self,
+        signer: &PrivateKeySigner,
+        to: Address,
+        data: Vec<u8>,
+        gas_limit: u64,
+    ) -> Result<(B256, TransactionReceipt, String)> {
+        let (nonce, mut max_fee, mut priority_fee) = self.nonce_and_fees(signer.address()).await?;
+        eprintln!(
+            "   Nonce {}, max fee {:.1} gwei, priority fee {:.1} gwei",
+            nonce,
+            max_fee as f64 / 1e9,
+            priority_fee as f64 / 1e9
+        );
+
+        // (hash, attempt) for every send that reached a node; all share `nonce`.
+        let mut sent: Vec<(B256, u32)> = Vec::new();
+        for attempt in 0..=self.redeem_max_replacements {
+            if attempt > 0 {
+                max_fee = bump_fee(max_fee, self.redeem_gas_bump_pct);
+                priority_fee = bump_fee(priority_fee, self.redeem_gas_bump_pct);
+                eprintln!(
+                    "   Not mined after {}s; replacing nonce {} with fees +{}% ({:.1} gwei max)",
+                    self.redeem_confirm_timeout.as_secs(),
+                    nonce,
+                    self.redeem_gas_bump_pct,
+                    max_fee as f64 / 1e9
+                );
+            }
+            let tx = TransactionRequest {
+                to: Some(alloy::primitives::TxKind::Call(to)),
+                input: Bytes::from(data.clone()).into(),
+                value: Some(U256::ZERO),
+                gas: Some(gas_limit),
+                nonce: Some(nonce),
+                max_fee_per_gas: Some(max_fee),
+                max_priority_fee_per_gas: Some(priority_fee),
+                chain_id: Some(POLYGON),
+                ..Default::default()
+            };
+            match self.send_signed(signer, tx).await {
+                Ok(hash) => {
+                    eprintln!("   Transaction hash: {:?}", hash);
+                    sent.push((hash, attempt));
+                }
+                Err(e) if sent.is_empty() => return Err(e),
+                // An earlier tx holds this nonce (the bump may be underpriced, or it already
+                // mined): keep waiting on what was sent.
+                Err(e) => warn!("Redemption: replacement send failed: {}", e),
+            }
+            if let Some((hash, attempt, receipt)) = self.wait_for_receipt(&sent).await {
+                let which = if attempt == 0 {
+                    "original".to_string()
+                } else {
+                    format!("replacement #{}", attempt)
+                };
+                eprintln!("   Mined: {} ({:?})", which, hash);
+                return Ok((hash, receipt, which));
+            }
+        }
+        anyhow::bail!(
+            "Redemption not mined after {} replacement(s); nonce {} may still be pending. Sent: {:?}",
+            self.redeem_max_replacements,
+            nonce,
+            sent.iter().map(|(h, _)| *h).collect::<Vec<_>>()
+        )
+    }
 
-        let mut last_redeem_err = anyhow::anyhow!("no RPC URLs configured for redemption");
+    /// Pending nonce of `from` and EIP-1559 fee estimates (max fee, priority fee) from the first
+    /// RPC URL that answers.
+    async fn nonce_and_fees(&self, from: Address) -> Result<(u64, u128, u128)> {
+        let mut last_err = anyhow::anyhow!("no RPC URLs configured for redemption");
+        for rpc_url in self.rpc_urls()? {
+            let result = async {
+                let provider = ProviderBuilder::new().connect(rpc_url).await?;
+                let nonce = provider.get_transaction_count(from).pending().await?;
+                let fees = provider.estimate_eip1559_fees().await?;
+                anyhow::Ok((nonce, fees.max_fee_per_gas, fees.max_priority_fee_per_gas))
+            }
+            .await;
+            match result {
+                Ok(v) => return Ok(v),
+                Err(e) => {
+                    warn!("Redemption: nonce/fee read via {} failed: {}", rpc_url, e);
+                    last_err = e;
+                }
+            }
+        }
+        Err(last_err).context("Failed to read nonce and gas fees for redemption")
+    }
 
-        for redeem_rpc_url in &redeem_urls {
+    /// Broadcast a fully specified tx, trying each RPC URL. Every field (including the nonce) is
+    /// fixed, so resending through another URL rebroadcasts the same tx rather than a new one.
+    async fn send_signed(&self, signer: &PrivateKeySigner, tx: TransactionRequest) -> Result<B256> {
+        let mut last_err = anyhow::anyhow!("no RPC URLs configured for redemption");
+        for rpc_url in self.rpc_urls()? {
             let provider = match ProviderBuilder::new()
                 .wallet(signer.clone())
-                .connect(*redeem_rpc_url)
+                .connect(rpc_url)
                 .await
             {
                 Ok(p) => p,
                 Err(e) => {
-                    warn!("Redemption: connect to {} failed: {}", redeem_rpc_url, e);
-                    last_redeem_err = anyhow::anyhow!("connect to {} failed: {}", redeem_rpc_url, e);
+                    warn!("Redemption: connect to {} failed: {}", rpc_url, e);
+                    last_err = anyhow::anyhow!("connect to {} failed: {}", rpc_url, e);
                     continue;
                 }
             };
-
-            let tx_request = TransactionRequest {
-                to: Some(alloy::primitives::TxKind::Call(tx_to)),
-                input: Bytes::from(tx_data.clone()).into(),
-                value: Some(U256::ZERO),
-                gas: Some(gas_limit),
-                ..Default::default()
-            };
-
-            let pending_tx = match provider.send_transaction(tx_request).await {
-                Ok(tx) => tx,
+            match provider.send_transaction(tx.clone()).await {
+                Ok(pending) => {
+                    eprintln!("   Transaction sent via {}", rpc_url);
+                    return Ok(*pending.tx_hash());
+                }
                 Err(e) => {
-                    warn!("Redemption: send via {} failed: {}", redeem_rpc_url, e);
-                    last_redeem_err = anyhow::anyhow!("send via {} failed: {}", redeem_rpc_url, e);
-                    continue;
+                    warn!("Redemption: send via {} failed: {}", rpc_url, e);
+                    last_err = anyhow::anyhow!("send via {} failed: {}", rpc_url, e);
                 }
-            };
-
-            // Transaction sent — do NOT retry from here (tx may be on chain)
-            let tx_hash = *pending_tx.tx_hash();
-            eprintln!("   Transaction sent via {}, waiting for confirmation...", redeem_rpc_url);
-            eprintln!("   Transaction hash: {:?}", tx_hash);
-
-            let receipt = pending_tx.get_receipt().await
-                .context("Failed to get transaction receipt")?;
-
-            if !receipt.status() {
-                anyhow::bail!("Redemption transaction failed. Transaction hash: {:?}", tx_hash);
             }
+        }
+        Err(last_err)
+    }
 
-            if used_safe_redemption {
-                let payout_redemption_topic = keccak256(
-                    b"PayoutRedemption(address,address,bytes32,bytes32,uint256[],uint256)"
-                );
-                let logs = receipt.logs();
-                let ctf_has_payout = logs.iter().any(|log| {
-                    log.address() == ctf_address && log.topics().first().map(|t| t.as_slice()) == Some(payout_redemption_topic.as_slice())
-                });
-                if !ctf_has_payout {
-                    anyhow::bail!(
-                        "Redemption tx was mined but the inner redeem reverted (no PayoutRedemption from CTF). \
-                        Check that the Safe holds the winning tokens and conditionId/indexSet are correct. Tx: {:?}",
-                        tx_hash
-                    );
+    /// Poll for a receipt of any of `sent` until `redeem_confirm_timeout` elapses.
+    async fn wait_for_receipt(&self, sent: &[(B256, u32)]) -> Option<(B256, u32, TransactionReceipt)> {
+        const POLL_INTERVAL: Duration = Duration::from_secs(3);
+        let deadline = tokio::time::Instant::now() + self.redeem_confirm_timeout;
+        loop {
+            for &(hash, attempt) in sent {
+                if let Some(receipt) = self.get_receipt(hash).await {
+                    return Some((hash, attempt, receipt));
                 }
             }
+            if tokio::time::Instant::now() + POLL_INTERVAL > deadline {
+                return None;
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    }
 
-            let redeem_response = RedeemResponse {
-                success: true,
-                message: Some(format!("Successfully redeemed tokens. Transaction: {:?}", tx_hash)),
-                transaction_hash: Some(format!("{:?}", tx_hash)),
-                amount_redeemed: None,
+    /// Receipt for `hash` from the first RPC URL that has it.
+    async fn get_receipt(&self, hash: B256) -> Option<TransactionReceipt> {
+        for rpc_url in self.rpc_urls().ok()? {
+            let Ok(provider) = ProviderBuilder::new().connect(rpc_url).await else {
+                continue;
             };
-            eprintln!("Successfully redeemed winning tokens!");
-            eprintln!("Transaction hash: {:?}", tx_hash);
-            if let Some(block_number) = receipt.block_number {
-                eprintln!("Block number: {}", block_number);
+            match provider.get_transaction_receipt(hash).await {
+                Ok(Some(receipt)) => return Some(receipt),
+                Ok(None) => {}
+                Err(e) => warn!("Redemption: receipt lookup via {} failed: {}", rpc_url, e),
             }
-            return Ok(redeem_response);
         }
+        None
+    }
+}
+
+/// `fee` raised by `pct` percent (at least 10%, the minimum nodes accept for a replacement).
+fn bump_fee(fee: u128, pct: u64) -> u128 {
+    fee + fee * pct.max(10) as u128 / 100
+}
 
-        Err(last_redeem_err)
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_sol_types::{Panic, PanicKind, Revert, SolError};
+
+    #[test]
+    fn describes_revert_string() {
+        let data = Revert::from("result for condition not received yet").abi_encode();
+        assert_eq!(describe_revert_data(&data), "revert: result for condition not received yet");
     }
 
+    #[test]
+    fn adds_hint_for_safe_codes() {
+        let data = Revert::from("GS026").abi_encode();
+        let reason = describe_revert_data(&data);
+        assert!(reason.starts_with("revert: GS026 ("), "{}", reason);
+        assert!(reason.contains("not a Safe owner"), "{}", reason);
+    }
+
+    #[test]
+    fn describes_panic_and_unknown_data() {
+        let data = Panic::from(PanicKind::UnderOverflow).abi_encode();
+        assert!(describe_revert_data(&data).contains("underflow"));
+        assert_eq!(describe_revert_data(&[0xde, 0xad, 0xbe, 0xef]), "undecoded revert data 0xdeadbeef");
+        assert_eq!(describe_revert_data(&[]), "reverted without a reason");
+    }
+
+    #[tokio::test]
+    async fn open_orders_without_credentials_fail_cleanly() {
+        let mut config = crate::config::Config::default().polymarket;
+        config.private_key = None;
+        let err = PolymarketApi::new(&config).get_open_orders(None).await.unwrap_err();
+        assert!(err.to_string().contains("monitor mode"), "{}", err);
+
+        config.private_key = Some(format!("0x{}", "11".repeat(32)));
+        let err = PolymarketApi::new(&config).get_open_orders(None).await.unwrap_err();
+        assert!(err.to_string().contains("authenticate()"), "{}", err);
+    }
+
+    #[test]
+    fn status_errors_are_classified_by_code() {
+        use polymarket_client_sdk::error::{Method, StatusCode};
+        let status = |code: u16| {
+            let code = StatusCode::from_u16(code).unwrap();
+            classify_sdk_error(&SdkError::status(code, Method::POST, "/order".to_string(), "x"))
+        };
+        for code in [500, 502, 503, 504] {
+            assert!(matches!(status(code), ApiError::NetworkAmbiguous(_)), "{}", code);
+        }
+        for code in [400, 404, 422] {
+            assert!(matches!(status(code), ApiError::Rejected(_)), "{}", code);
+        }
+        assert!(matches!(status(429), ApiError::RateLimited(_)));
+        assert!(matches!(status(401), ApiError::Auth(_)));
+        assert!(matches!(status(403), ApiError::Auth(_)));
+    }
+
+    #[tokio::test]
+    async fn fok_buy_answered_with_502_is_ambiguous() {
+        use axum::{http::StatusCode, routing::{get, post}, Json, Router};
+        use serde_json::json;
+        let app = Router::new()
+            .route(
+                "/auth/api-key",
+                post(|| async {
+                    Json(json!({
+                        "apiKey": "00000000-0000-0000-0000-000000000000",
+                        "secret": "c2VjcmV0",
+                        "passphrase": "pass"
+                    }))
+                }),
+            )
+            .route("/tick-size", get(|| async { Json(json!({"minimum_tick_size": "0.01"})) }))
+            .route("/neg-risk", get(|| async { Json(json!({"neg_risk": false})) }))
+            .route("/fee-rate", get(|| async { Json(json!({"base_fee": 0})) }))
+            .route("/order", post(|| async { (StatusCode::BAD_GATEWAY, "upstream timed out") }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.ok();
+        });
+        let mut config = crate::config::Config::default().polymarket;
+        config.clob_api_url = format!("http://{}", addr);
+        config.private_key = Some(format!("0x{}", "11".repeat(32)));
+        let api = PolymarketApi::new(&config);
+        api.authenticate().await.unwrap();
+
+        let err = api.place_fok_buy("111", "5", "0.99").await.unwrap_err();
+        assert!(err.is_ambiguous(), "{}", err);
+    }
+
+    #[test]
+    fn structured_price_to_beat_prefers_market_metadata() {
+        use serde_json::json;
+        let d = |v: &str| Some(rust_decimal::Decimal::from_str(v).unwrap());
+        let event = json!({"eventMetadata": {"priceToBeat": 97500.25}});
+        assert_eq!(structured_price_to_beat(&event, &json!({})), d("97500.25"));
+        assert_eq!(
+            structured_price_to_beat(&event, &json!({"eventMetadata": {"priceToBeat": "97501.5"}})),
+            d("97501.5")
+        );
+        assert_eq!(structured_price_to_beat(&json!({}), &json!({"groupItemThreshold": "3"})), None);
+        assert_eq!(
+            structured_price_to_beat(&event, &json!({"groupItemThreshold": "3"})),
+            d("97500.25")
+        );
+        assert_eq!(structured_price_to_beat(&json!({}), &json!({"eventMetadata": {"priceToBeat": 0}})), None);
+        assert_eq!(structured_price_to_beat(&json!({}), &json!({"eventMetadata": {"priceToBeat": null}})), None);
+    }
+
+    #[tokio::test]
+    async fn market_by_slug_reads_the_structured_price_to_beat() {
+        use axum::{extract::Path, routing::get, Json, Router};
+        use serde_json::json;
+        let app = Router::new().route(
+            "/events/slug/{slug}",
+            get(|Path(slug): Path<String>| async move {
+                let metadata = if slug == "with-metadata" { json!({"priceToBeat": 3250.75}) } else { Value::Null };
+                Json(json!({
+                    "eventMetadata": metadata,
+                    "markets": [{
+                        "conditionId": "0xc", "id": "1", "slug": slug, "active": true, "closed": false,
+                        "groupItemThreshold": "1",
+                        "question": "Ethereum Up or Down - June 10, 6:35PM-6:40PM ET",
+                    }]
+                }))
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.ok();
+        });
+        let mut config = crate::config::Config::default().polymarket;
+        config.gamma_api_url = format!("http://{}", addr);
+        let api = PolymarketApi::new(&config);
+
+        let market = api.get_market_by_slug("with-metadata").await.unwrap();
+        assert_eq!(market.price_to_beat, Some(rust_decimal::Decimal::from_str("3250.75").unwrap()));
+        assert_eq!(api.get_market_by_slug("plain").await.unwrap().price_to_beat, None);
+    }
+
+    #[test]
+    fn ended_markets_are_closed_or_past_their_end_date() {
+        let market = |closed: bool, end: &str| MarketDetails {
+            condition_id: "0xabc".into(),
+            question: String::new(),
+            tokens: Vec::new(),
+            active: !closed,
+            closed,
+            end_date_iso: end.into(),
+            neg_risk: false,
+        };
+        let now = chrono::DateTime::parse_from_rfc3339("2026-10-17T12:00:00Z").unwrap().timestamp();
+        assert!(market_has_ended(&market(true, "2026-10-18T00:00:00Z"), now));
+        assert!(market_has_ended(&market(false, "2026-10-17T11:55:00Z"), now));
+        assert!(!market_has_ended(&market(false, "2026-10-17T12:05:00Z"), now));
+        // An unparseable end date alone never cancels an open market's orders.
+        assert!(!market_has_ended(&market(false, ""), now));
+    }
+
+    #[test]
+    fn redeem_index_sets_follow_wallet_type() {
+        let mut config = crate::config::Config::default().polymarket;
+        let eoa = PolymarketApi::new(&config);
+        assert_eq!(eoa.redeem_index_sets("Up"), vec![U256::from(1)]);
+        assert_eq!(eoa.redeem_index_sets("Down"), vec![U256::from(2)]);
+
+        config.proxy_wallet_address = Some("0x0000000000000000000000000000000000000001".to_string());
+        config.signature_type = Some(2);
+        let safe = PolymarketApi::new(&config);
+        assert_eq!(safe.redeem_index_sets("Down"), vec![U256::from(1), U256::from(2)]);
+    }
+
+    sol! {
+        struct ProxyCall {
+            uint8 typeCode;
+            address to;
+            uint256 value;
+            bytes data;
+        }
+        function proxy(ProxyCall[] calls) external payable returns (bytes[] returnValues);
+    }
+
+    fn sample_redeem_calldata() -> Vec<u8> {
+        IConditionalTokens::redeemPositionsCall {
+            collateralToken: USDC_ADDRESS,
+            parentCollectionId: B256::ZERO,
+            conditionId: B256::repeat_byte(0xab),
+            indexSets: vec![U256::from(1), U256::from(2)],
+        }
+        .abi_encode()
+    }
+
+    #[test]
+    fn redeem_calldata_matches_reference() {
+        let calldata = hex::encode(sample_redeem_calldata());
+        let expected = [
+            "01b7037c",
+            "0000000000000000000000002791bca1f2de4661ed88a30c99a7a9449aa84174",
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            "abababababababababababababababababababababababababababababababab",
+            "0000000000000000000000000000000000000000000000000000000000000080",
+            "0000000000000000000000000000000000000000000000000000000000000002",
+            "0000000000000000000000000000000000000000000000000000000000000001",
+            "0000000000000000000000000000000000000000000000000000000000000002",
+        ]
+        .concat();
+        assert_eq!(calldata, expected);
+    }
+
+    #[test]
+    fn proxy_factory_calldata_matches_abi_encoding() {
+        let ctf = Address::repeat_byte(0x4d);
+        let redeem = sample_redeem_calldata();
+        let calldata = proxy_factory_calldata(ctf, &redeem);
+
+        assert_eq!(hex::encode(&calldata[..4]), "34ee9791");
+        let expected = proxyCall {
+            calls: vec![ProxyCall { typeCode: 1, to: ctf, value: U256::ZERO, data: Bytes::from(redeem.clone()) }],
+        };
+        assert_eq!(calldata, expected.abi_encode());
+
+        let decoded = proxyCall::abi_decode_validate(&calldata).unwrap();
+        assert_eq!(decoded.calls.len(), 1);
+        assert_eq!(decoded.calls[0].typeCode, 1);
+        assert_eq!(decoded.calls[0].to, ctf);
+        assert_eq!(decoded.calls[0].data.as_ref(), redeem.as_slice());
+    }
+
+    #[test]
+    fn safe_exec_calldata_round_trips() {
+        let ctf = Address::repeat_byte(0x4d);
+        let redeem = sample_redeem_calldata();
+        let signatures = vec![0x11; 130];
+        let calldata = safe_exec_calldata(ctf, redeem.clone(), signatures.clone());
+
+        assert_eq!(hex::encode(&calldata[..4]), "6a761202");
+        let decoded = IGnosisSafe::execTransactionCall::abi_decode_validate(&calldata).unwrap();
+        assert_eq!(decoded.to, ctf);
+        assert_eq!(decoded.value, U256::ZERO);
+        assert_eq!(decoded.data.as_ref(), redeem.as_slice());
+        assert_eq!(decoded.operation, 0);
+        assert_eq!(decoded.safeTxGas, U256::from(SAFE_TX_GAS));
+        assert_eq!(decoded.gasToken, Address::ZERO);
+        assert_eq!(decoded.refundReceiver, Address::ZERO);
+        assert_eq!(decoded.signatures.as_ref(), signatures.as_slice());
+    }
+
+    #[test]
+    fn bump_fee_enforces_minimum_replacement_increase() {
+        assert_eq!(bump_fee(100_000, 30), 130_000);
+        assert_eq!(bump_fee(100_000, 5), 110_000);
+    }
+
+    fn api_with_cosigners(keys: &[&PrivateKeySigner]) -> PolymarketApi {
+        let mut config = crate::config::Config::default().polymarket;
+        config.safe_cosigner_keys = keys.iter().map(|k| hex::encode(k.to_bytes())).collect();
+        PolymarketApi::new(&config)
+    }
+
+    #[test]
+    fn safe_signers_fails_early_below_threshold() {
+        let main = PrivateKeySigner::random();
+        let api = api_with_cosigners(&[]);
+        let owners = [main.address(), Address::repeat_byte(1)];
+        let err = api.safe_signers(&main, &owners, U256::from(2)).unwrap_err().to_string();
+        assert!(err.contains("requires 2 signatures but only 1"), "{}", err);
+    }
+
+    #[test]
+    fn safe_signers_rejects_non_owner_key() {
+        let main = PrivateKeySigner::random();
+        let stranger = PrivateKeySigner::random();
+        let api = api_with_cosigners(&[&stranger]);
+        let err = api.safe_signers(&main, &[main.address()], U256::from(1)).unwrap_err().to_string();
+        assert!(err.contains("is not an owner"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn safe_signatures_are_sorted_by_owner() {
+        let a = PrivateKeySigner::random();
+        let b = PrivateKeySigner::random();
+        let api = api_with_cosigners(&[&b]);
+        let signers = api.safe_signers(&a, &[a.address(), b.address()], U256::from(2)).unwrap();
+        let hash = B256::repeat_byte(7);
+        let blob = safe_signatures(&signers, hash).await.unwrap();
+        assert_eq!(blob.len(), 130);
+
+        let prefixed = keccak256([b"\x19Ethereum Signed Message:\n32".as_slice(), hash.as_slice()].concat());
+        let recovered: Vec<Address> = blob
+            .chunks(65)
+            .map(|c| {
+                assert!(c[64] == 31 || c[64] == 32);
+                alloy::primitives::Signature::from_raw(&[&c[..64], &[c[64] - 4]].concat())
+                    .unwrap()
+                    .recover_address_from_prehash(&prefixed)
+                    .unwrap()
+            })
+            .collect();
+        let mut expected = vec![a.address(), b.address()];
+        expected.sort();
+        assert_eq!(recovered, expected);
+    }
+
+    #[tokio::test]
+    async fn retry_after_wait_does_not_hold_the_request_limit() {
+        use axum::{http::{header, StatusCode}, routing::get, Json, Router};
+        let app = Router::new()
+            .route(
+                "/events/slug/{slug}",
+                get(|| async { (StatusCode::TOO_MANY_REQUESTS, [(header::RETRY_AFTER, "2")], "slow down") }),
+            )
+            .route(
+                "/markets/{cid}",
+                get(|| async {
+                    Json(serde_json::json!({
+                        "condition_id": "0xc", "question": "q", "active": true, "closed": false,
+                        "end_date_iso": "2026-01-01T00:00:00Z", "tokens": []
+                    }))
+                }),
+            );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.ok();
+        });
+        let mut config = crate::config::Config::default().polymarket;
+        config.gamma_api_url = format!("http://{}", addr);
+        config.clob_api_url = format!("http://{}", addr);
+        config.max_concurrent_requests = 1;
+        let api = Arc::new(PolymarketApi::new(&config));
+
+        let limited = Arc::clone(&api);
+        let slug = tokio::spawn(async move { limited.get_market_by_slug("btc-updown-5m-0").await });
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        let started = std::time::Instant::now();
+        api.get_market("0xc").await.unwrap();
+        assert!(started.elapsed() < Duration::from_secs(1), "waited {:?} behind a Retry-After", started.elapsed());
+        assert_eq!(api.rate_limited_count(), 1);
+        slug.abort();
+    }
+
+    #[tokio::test]
+    async fn orderbooks_are_fetched_in_one_request_by_token() {
+        use axum::{routing::post, Json, Router};
+        let app = Router::new().route(
+            "/books",
+            post(|Json(body): Json<Vec<Value>>| async move {
+                assert_eq!(body.len(), 2);
+                Json(serde_json::json!([
+                    {"market": "0xc", "asset_id": "111", "bids": [{"price": "0.4", "size": "5"}], "asks": [{"price": "0.6", "size": "10"}]},
+                    {"market": "0xc", "asset_id": "222", "bids": [], "asks": [{"price": "0.41", "size": "7.5"}]}
+                ]))
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.ok();
+        });
+        let mut config = crate::config::Config::default().polymarket;
+        config.clob_api_url = format!("http://{}", addr);
+        let api = PolymarketApi::new(&config);
+
+        let books = api.get_orderbooks(&["111", "222"]).await.unwrap();
+        assert_eq!(books.len(), 2);
+        assert_eq!(books["111"].asks[0].price, rust_decimal::Decimal::from_str("0.6").unwrap());
+        assert_eq!(books["222"].asks[0].size, rust_decimal::Decimal::from_str("7.5").unwrap());
+        assert!(books["222"].bids.is_empty());
+        assert!(api.get_orderbooks(&[]).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn redemption_waits_for_a_closed_market_with_the_same_winner() {
+        use axum::{extract::Path, routing::get, Json, Router};
+        use std::sync::atomic::{AtomicU32, Ordering};
+        let flip_calls = Arc::new(AtomicU32::new(0));
+        let calls = flip_calls.clone();
+        let app = Router::new().route(
+            "/markets/{cid}",
+            get(move |Path(cid): Path<String>| {
+                let calls = calls.clone();
+                async move {
+                    let (closed, winner) = match cid.as_str() {
+                        "0xopen" => (false, None),
+                        "0xpending" => (true, None),
+                        "0xdown" => (true, Some("Down")),
+                        // Reads Up first and Down on every later check.
+                        "0xflip" if calls.fetch_add(1, Ordering::SeqCst) > 0 => (true, Some("Down")),
+                        _ => (true, Some("Up")),
+                    };
+                    Json(serde_json::json!({
+                        "condition_id": cid, "question": "q", "active": !closed, "closed": closed,
+                        "end_date_iso": "2026-01-01T00:00:00Z",
+                        "tokens": [
+                            {"outcome": "Up", "token_id": "1", "winner": winner == Some("Up")},
+                            {"outcome": "Down", "token_id": "2", "winner": winner == Some("Down")}
+                        ]
+                    }))
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.ok();
+        });
+        let mut config = crate::config::Config::default().polymarket;
+        config.clob_api_url = format!("http://{}", addr);
+        let api = PolymarketApi::new(&config);
+
+        assert!(api.verify_resolution("0xup", "up").await.is_ok());
+        assert!(api.verify_resolution("0xup", "1").await.is_ok(), "same side under another label");
+        assert!(api.verify_resolution("0xup", "Maybe").await.is_err(), "unmapped labels never match");
+        assert!(api.verify_resolution("0xopen", "Up").await.is_err());
+        assert!(api.verify_resolution("0xpending", "Up").await.is_err());
+        assert!(api.verify_resolution("0xdown", "Up").await.is_err());
+        // Without a delay only one check is made.
+        assert!(api.verify_resolution("0xflip", "Up").await.is_ok());
+
+        flip_calls.store(0, Ordering::SeqCst);
+        config.redeem_resolution_delay_secs = 1;
+        let api = PolymarketApi::new(&config);
+        let err = api.verify_resolution("0xflip", "Up").await.unwrap_err();
+        assert!(err.to_string().contains("resolved Down"), "{}", err);
+        assert_eq!(flip_calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn redeem_uses_the_market_collateral_and_refuses_neg_risk_with_a_zero_parent() {
+        use axum::{extract::Path, routing::{get, post}, Json, Router};
+        let collection = B256::repeat_byte(0xcc);
+        let position = |collateral: Address| {
+            U256::from_be_bytes(keccak256([collateral.as_slice(), collection.as_slice()].concat()).0).to_string()
+        };
+        let native_up = position(NATIVE_USDC_ADDRESS);
+        let app = Router::new()
+            .route(
+                "/markets/{cid}",
+                get(move |Path(cid): Path<String>| {
+                    let up = if cid == "0xnative" { native_up.clone() } else { "1".to_string() };
+                    let neg_risk = cid.starts_with("0xee");
+                    async move {
+                        Json(serde_json::json!({
+                            "condition_id": cid, "question": "q", "active": false, "closed": true, "neg_risk": neg_risk,
+                            "end_date_iso": "2026-01-01T00:00:00Z",
+                            "tokens": [
+                                {"outcome": "Up", "token_id": up, "winner": true},
+                                {"outcome": "Down", "token_id": "2", "winner": false}
+                            ]
+                        }))
+                    }
+                }),
+            )
+            // Every eth_call (CTF.getCollectionId) answers `collection`.
+            .route(
+                "/",
+                post(move |Json(req): Json<Value>| async move {
+                    Json(serde_json::json!({"jsonrpc": "2.0", "id": req["id"], "result": collection.to_string()}))
+                }),
+            );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.ok();
+        });
+        let mut config = crate::config::Config::default().polymarket;
+        config.clob_api_url = format!("http://{}", addr);
+        config.rpc_urls = vec![format!("http://{}", addr)];
+        let api = PolymarketApi::new(&config);
+        let condition = B256::repeat_byte(0xab);
+
+        let native = api.get_market("0xnative").await.unwrap();
+        let collateral = api.redeem_collateral(&native, condition, B256::ZERO).await.unwrap();
+        assert_eq!(collateral, NATIVE_USDC_ADDRESS);
+
+        let other = api.get_market("0xother").await.unwrap();
+        let err = api.redeem_collateral(&other, condition, B256::ZERO).await.unwrap_err();
+        assert!(err.to_string().contains("set polymarket.collateral_token"), "{}", err);
+
+        config.private_key = Some(hex::encode(PrivateKeySigner::random().to_bytes()));
+        let neg_risk = format!("0x{}", "ee".repeat(32));
+        let err = PolymarketApi::new(&config).redeem_tokens(&neg_risk, "Up", true).await.unwrap_err();
+        assert!(err.to_string().contains("neg-risk"), "{}", err);
+
+        config.collateral_token = "not an address".to_string();
+        assert!(PolymarketApi::new(&config).redeem_collateral(&native, condition, B256::ZERO).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn slow_rpc_times_out_and_fails_over_to_the_next_url() {
+        use axum::{routing::post, Json, Router};
+        async fn serve(delay: Duration) -> String {
+            let app = Router::new().route(
+                "/",
+                post(move |Json(req): Json<Value>| async move {
+                    tokio::time::sleep(delay).await;
+                    // Any eth_call answers a uint8 of 6 (USDC decimals).
+                    Json(serde_json::json!({"jsonrpc": "2.0", "id": req["id"], "result": format!("0x{:064x}", 6)}))
+                }),
+            );
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let addr = listener.local_addr().unwrap();
+            tokio::spawn(async move {
+                axum::serve(listener, app).await.ok();
+            });
+            format!("http://{}", addr)
+        }
+        let mut config = crate::config::Config::default().polymarket;
+        config.rpc_urls = vec![serve(Duration::from_secs(30)).await, serve(Duration::ZERO).await];
+        config.rpc_timeout_ms = 200;
+        let api = PolymarketApi::new(&config);
+
+        let started = std::time::Instant::now();
+        let decimals = api.view_call(USDC_ADDRESS, IERC20::decimalsCall {}).await.unwrap();
+        assert_eq!(decimals, 6);
+        assert!(started.elapsed() < Duration::from_secs(5), "took {:?}", started.elapsed());
+
+        config.rpc_urls.truncate(1);
+        let err = PolymarketApi::new(&config).view_call(USDC_ADDRESS, IERC20::decimalsCall {}).await.unwrap_err();
+        assert!(err.to_string().contains("timed out"), "{}", err);
+    }
 }
@@ -0,0 +1,123 @@
+//! Abstractions over `PolymarketApi` used by the strategy, discovery and executor, so they can
+//! run against a mock (see `mock_api`) instead of the real network.
+
+use crate::api::PolymarketApi;
+use crate::config::OutcomeMapping;
+use crate::error::ApiError;
+use crate::models::{Market, MarketDetails, OrderBook, OrderResponse, Position, Trade};
+use anyhow::Result;
+use futures_util::future::BoxFuture;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+
+/// Read-only market lookups.
+pub trait MarketData: Send + Sync {
+    fn get_market_by_slug<'a>(&'a self, slug: &'a str) -> BoxFuture<'a, Result<Market>>;
+    fn get_market<'a>(&'a self, condition_id: &'a str) -> BoxFuture<'a, Result<MarketDetails>>;
+    /// Symbols with an active up/down market of `duration` (e.g. "5m") on Gamma.
+    fn discover_active_updown_markets<'a>(&'a self, duration: &'a str) -> BoxFuture<'a, Result<Vec<String>>>;
+    /// On-chain Chainlink price in effect at `at_ts`: (price, round updated_at).
+    fn get_chainlink_price_rpc<'a>(&'a self, aggregator: &'a str, at_ts: i64) -> BoxFuture<'a, Result<(Decimal, i64)>>;
+    fn outcome_mapping(&self) -> &OutcomeMapping;
+    fn get_positions<'a>(&'a self, wallet: &'a str, redeemable_only: bool) -> BoxFuture<'a, Result<Vec<Position>>>;
+    /// REST orderbook snapshots by token ID in one request, used to seed the WS mirror
+    /// (`orderbook_rest_poll_secs`).
+    fn get_orderbooks<'a>(&'a self, token_ids: &'a [&'a str]) -> BoxFuture<'a, Result<HashMap<String, OrderBook>>>;
+    /// Market trades of a token after `after_ts`, oldest first; see `PolymarketApi::get_trades`.
+    fn get_trades<'a>(&'a self, condition_id: &'a str, token_id: &'a str, after_ts: i64) -> BoxFuture<'a, Result<Vec<Trade>>>;
+}
+
+/// Order placement against the CLOB.
+pub trait OrderPlacement: Send + Sync {
+    /// False in monitor mode: with no private key nothing can be signed, whatever the config.
+    fn has_private_key(&self) -> bool;
+    fn is_authenticated(&self) -> bool;
+    /// Wallet whose positions the sweep adds to; None when there's no key or proxy configured.
+    fn wallet_address(&self) -> Option<String>;
+    /// Warm the SDK order caches for a token; returns its minimum tick size.
+    fn warm_order_cache<'a>(&'a self, token_id: &'a str) -> BoxFuture<'a, Result<Decimal>>;
+    /// Sign a FOK buy before close so a matching `place_fok_buy` skips signing.
+    fn presign_fok_buy<'a>(&'a self, token_id: &'a str, size: &'a str, price: &'a str) -> BoxFuture<'a, Result<()>>;
+    fn clear_presigned(&self);
+    /// FOK buy: Ok(Some) if filled, Ok(None) if killed; see `PolymarketApi::place_fok_buy`.
+    fn place_fok_buy<'a>(
+        &'a self,
+        token_id: &'a str,
+        size: &'a str,
+        price: &'a str,
+    ) -> BoxFuture<'a, std::result::Result<Option<OrderResponse>, ApiError>>;
+}
+
+/// Everything the strategy needs from the API.
+pub trait TradingApi: MarketData + OrderPlacement {}
+
+impl<T: MarketData + OrderPlacement> TradingApi for T {}
+
+impl MarketData for PolymarketApi {
+    fn get_market_by_slug<'a>(&'a self, slug: &'a str) -> BoxFuture<'a, Result<Market>> {
+        Box::pin(PolymarketApi::get_market_by_slug(self, slug))
+    }
+
+    fn get_market<'a>(&'a self, condition_id: &'a str) -> BoxFuture<'a, Result<MarketDetails>> {
+        Box::pin(PolymarketApi::get_market(self, condition_id))
+    }
+
+    fn discover_active_updown_markets<'a>(&'a self, duration: &'a str) -> BoxFuture<'a, Result<Vec<String>>> {
+        Box::pin(PolymarketApi::discover_active_updown_markets(self, duration))
+    }
+
+    fn get_chainlink_price_rpc<'a>(&'a self, aggregator: &'a str, at_ts: i64) -> BoxFuture<'a, Result<(Decimal, i64)>> {
+        Box::pin(PolymarketApi::get_chainlink_price_rpc(self, aggregator, at_ts))
+    }
+
+    fn outcome_mapping(&self) -> &OutcomeMapping {
+        PolymarketApi::outcome_mapping(self)
+    }
+
+    fn get_positions<'a>(&'a self, wallet: &'a str, redeemable_only: bool) -> BoxFuture<'a, Result<Vec<Position>>> {
+        Box::pin(PolymarketApi::get_positions(self, wallet, redeemable_only))
+    }
+
+    fn get_orderbooks<'a>(&'a self, token_ids: &'a [&'a str]) -> BoxFuture<'a, Result<HashMap<String, OrderBook>>> {
+        Box::pin(PolymarketApi::get_orderbooks(self, token_ids))
+    }
+
+    fn get_trades<'a>(&'a self, condition_id: &'a str, token_id: &'a str, after_ts: i64) -> BoxFuture<'a, Result<Vec<Trade>>> {
+        Box::pin(PolymarketApi::get_trades(self, condition_id, token_id, after_ts))
+    }
+}
+
+impl OrderPlacement for PolymarketApi {
+    fn has_private_key(&self) -> bool {
+        PolymarketApi::has_private_key(self)
+    }
+
+    fn is_authenticated(&self) -> bool {
+        PolymarketApi::is_authenticated(self)
+    }
+
+    fn wallet_address(&self) -> Option<String> {
+        PolymarketApi::wallet_address(self)
+    }
+
+    fn warm_order_cache<'a>(&'a self, token_id: &'a str) -> BoxFuture<'a, Result<Decimal>> {
+        Box::pin(PolymarketApi::warm_order_cache(self, token_id))
+    }
+
+    fn presign_fok_buy<'a>(&'a self, token_id: &'a str, size: &'a str, price: &'a str) -> BoxFuture<'a, Result<()>> {
+        Box::pin(PolymarketApi::presign_fok_buy(self, token_id, size, price))
+    }
+
+    fn clear_presigned(&self) {
+        PolymarketApi::clear_presigned(self)
+    }
+
+    fn place_fok_buy<'a>(
+        &'a self,
+        token_id: &'a str,
+        size: &'a str,
+        price: &'a str,
+    ) -> BoxFuture<'a, std::result::Result<Option<OrderResponse>, ApiError>> {
+        Box::pin(PolymarketApi::place_fok_buy(self, token_id, size, price))
+    }
+}
@@ -1,35 +1,28 @@
-//! Price-to-beat from Polymarket RTDS Chainlink (crypto_prices_chainlink) for multiple symbols.
+//! RTDS stream supervisor: keeps one Polymarket RTDS connection alive for all handlers
+//! (Chainlink prices for every symbol, last trade prints for the traded tokens).
 //! Per docs: https://docs.polymarket.com/developers/RTDS/RTDS-crypto-prices
-//! Single WS connection subscribes to all symbols with type: "*" and filters: "".
-//! Price-to-beat is set when we receive a message whose feed_ts is in [period_start, period_start+2).
+//! Price-to-beat is set when we receive a message whose feed_ts is in the capture window
+//! (see `rtds::CaptureWindow`).
 
-use crate::rtds::{run_rtds_chainlink_all, LatestPriceCache, PriceCacheMulti};
+use crate::metrics::SharedMetrics;
+use crate::rtds::{run_rtds, RtdsCommand, RtdsHandler, RtdsSocketConfig};
 use anyhow::Result;
 use log::{debug, warn};
 use std::sync::Arc;
+use tokio::sync::mpsc;
 use tokio::time::Duration;
 
-/// Spawn RTDS Chainlink stream for all symbols on a single connection.
+/// Spawn the RTDS stream for all handlers on a single connection, reconnecting forever.
 pub async fn run_chainlink_multi_poller(
-    rtds_ws_url: String,
-    symbols: Vec<String>,
-    price_cache_5: PriceCacheMulti,
-    latest_prices: LatestPriceCache,
+    socket: RtdsSocketConfig,
+    handlers: Vec<Arc<dyn RtdsHandler>>,
+    mut commands: mpsc::UnboundedReceiver<RtdsCommand>,
+    metrics: SharedMetrics,
 ) -> Result<()> {
-    let cache_5 = Arc::clone(&price_cache_5);
-    let latest = Arc::clone(&latest_prices);
-
     tokio::spawn(async move {
         let mut attempts: u32 = 0;
         loop {
-            if let Err(e) = run_rtds_chainlink_all(
-                &rtds_ws_url,
-                &symbols,
-                cache_5.clone(),
-                latest.clone(),
-            )
-            .await
-            {
+            if let Err(e) = run_rtds(&socket, &handlers, &mut commands, &metrics).await {
                 attempts += 1;
                 if attempts <= 2 {
                     warn!("RTDS WS stream exited: {} (reconnecting in 5s)", e);
@@ -42,6 +35,7 @@ pub async fn run_chainlink_multi_poller(
                 warn!("RTDS WS connection closed (reconnecting in 5s)");
             }
             tokio::time::sleep(Duration::from_secs(5)).await;
+            metrics.rtds.record_reconnect();
         }
     });
 
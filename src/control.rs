@@ -0,0 +1,110 @@
+//! Runtime trading controls shared between the dashboard and the strategy loop.
+
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU32, AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// `budget_override` bits meaning "no override" (a NaN, never a valid budget).
+const NO_BUDGET_OVERRIDE: u64 = u64::MAX;
+
+/// Shared, lock-free switches the dashboard can flip while the bot is running.
+pub struct TradingControl {
+    paused: AtomicBool,
+    /// Runtime `max_sweep_cost` as f64 bits, or `NO_BUDGET_OVERRIDE`.
+    budget_override: AtomicU64,
+    /// Ambiguous network errors on live orders in a row, across rounds.
+    network_errors: AtomicU32,
+    /// Unix seconds until which the network-error circuit breaker keeps the bot monitor-only;
+    /// 0 when closed.
+    breaker_until: AtomicI64,
+    /// Net P&L of live sweeps resolved on `pnl_day`, as f64 bits.
+    day_pnl: AtomicU64,
+    /// UTC day (days since the epoch) `day_pnl` is for.
+    pnl_day: AtomicI64,
+}
+
+impl Default for TradingControl {
+    fn default() -> Self {
+        Self {
+            paused: AtomicBool::new(false),
+            budget_override: AtomicU64::new(NO_BUDGET_OVERRIDE),
+            network_errors: AtomicU32::new(0),
+            breaker_until: AtomicI64::new(0),
+            day_pnl: AtomicU64::new(0f64.to_bits()),
+            pnl_day: AtomicI64::new(0),
+        }
+    }
+}
+
+pub type SharedControl = Arc<TradingControl>;
+
+impl TradingControl {
+    pub fn new() -> SharedControl {
+        Arc::new(Self::default())
+    }
+
+    /// When paused, rounds still run and paper-log, but no live orders are placed.
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+
+    /// Set the paused flag; returns the previous value.
+    pub fn set_paused(&self, paused: bool) -> bool {
+        self.paused.swap(paused, Ordering::Relaxed)
+    }
+
+    /// `max_sweep_cost` set from the dashboard; it replaces the configured value for every
+    /// symbol until cleared.
+    pub fn budget_override(&self) -> Option<f64> {
+        let bits = self.budget_override.load(Ordering::Relaxed);
+        (bits != NO_BUDGET_OVERRIDE).then(|| f64::from_bits(bits))
+    }
+
+    /// Set or clear (None) the budget override; returns the previous one.
+    pub fn set_budget_override(&self, budget: Option<f64>) -> Option<f64> {
+        let bits = budget.map_or(NO_BUDGET_OVERRIDE, f64::to_bits);
+        let previous = self.budget_override.swap(bits, Ordering::Relaxed);
+        (previous != NO_BUDGET_OVERRIDE).then(|| f64::from_bits(previous))
+    }
+
+    /// Count an ambiguous network error on a live order. The `threshold`-th in a row (0 = never)
+    /// opens the circuit breaker until `now + cooldown_secs`, resets the count and returns that
+    /// time.
+    pub fn record_network_error(&self, threshold: u32, cooldown_secs: u64, now: i64) -> Option<i64> {
+        let errors = self.network_errors.fetch_add(1, Ordering::Relaxed) + 1;
+        if threshold == 0 || errors < threshold {
+            return None;
+        }
+        self.network_errors.store(0, Ordering::Relaxed);
+        let until = now + cooldown_secs as i64;
+        self.breaker_until.store(until, Ordering::Relaxed);
+        Some(until)
+    }
+
+    /// The CLOB answered an order (filled or killed), so the network is back: reset the count.
+    pub fn record_order_answered(&self) {
+        self.network_errors.store(0, Ordering::Relaxed);
+    }
+
+    pub fn consecutive_network_errors(&self) -> u32 {
+        self.network_errors.load(Ordering::Relaxed)
+    }
+
+    /// Add a resolved live sweep's P&L to the total for UTC `day` (days since the epoch), starting
+    /// over on a new day. Returns the day's net P&L including it.
+    pub fn record_sweep_pnl(&self, pnl: f64, day: i64) -> f64 {
+        let before = if self.pnl_day.swap(day, Ordering::Relaxed) == day {
+            f64::from_bits(self.day_pnl.load(Ordering::Relaxed))
+        } else {
+            0.0
+        };
+        let total = before + pnl;
+        self.day_pnl.store(total.to_bits(), Ordering::Relaxed);
+        total
+    }
+
+    /// End of the circuit breaker's cool-down while it is open at `now`.
+    pub fn breaker_open_until(&self, now: i64) -> Option<i64> {
+        let until = self.breaker_until.load(Ordering::Relaxed);
+        (until > now).then_some(until)
+    }
+}
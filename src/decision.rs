@@ -0,0 +1,367 @@
+//! Winner/margin decision for a closed round, shared by the live sweep, the paper log and
+//! `/replay` so they can't disagree about a round.
+
+use crate::config::StrategyConfig;
+use crate::discovery::MARKET_5M_DURATION_SECS;
+use crate::models::{fraction_of, OrderBook};
+use crate::paper_trade::{PaperTradeRow, SkipReason};
+use crate::recorder;
+use log::warn;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::path::Path;
+
+/// What to do with a closed round given its price-to-beat and close price.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Decision {
+    /// Buy the `side` ("Up"/"Down") token; `diff` is close minus price-to-beat.
+    Sweep { side: &'static str, diff: Decimal },
+    /// The close price's feed timestamp is `skew_ms` from close, past `winner_price_max_skew_ms`.
+    SkipStale { skew_ms: i64 },
+    /// Close price or price-to-beat outside the symbol's plausible price range.
+    SkipSanity,
+    /// Close equals price-to-beat.
+    SkipTied,
+    /// |diff| under `sweep_min_margin_pct` of the price-to-beat.
+    SkipBelowMargin { diff: Decimal, min_margin: Decimal },
+}
+
+impl Decision {
+    pub fn skip_reason(&self) -> Option<SkipReason> {
+        match self {
+            Decision::Sweep { .. } => None,
+            Decision::SkipStale { .. } => Some(SkipReason::RtdsStale),
+            Decision::SkipSanity => Some(SkipReason::InvalidPrice),
+            Decision::SkipTied => Some(SkipReason::Tied),
+            Decision::SkipBelowMargin { .. } => Some(SkipReason::BelowMargin),
+        }
+    }
+}
+
+/// Whether `price` is inside the symbol's `min_plausible_price..=max_plausible_price`.
+pub fn plausible(price: Decimal, cfg: &StrategyConfig) -> bool {
+    let price = price.to_f64().unwrap_or(f64::NAN);
+    (cfg.min_plausible_price..=cfg.max_plausible_price).contains(&price)
+}
+
+/// Decide the round: stale feed, then sanity, tie and margin checks, then the winning side.
+/// `skew_ms` is the close price's feed timestamp minus the close time.
+pub fn decide_winner(price_to_beat: Decimal, latest_price: Decimal, skew_ms: i64, cfg: &StrategyConfig) -> Decision {
+    if cfg.winner_price_max_skew_ms > 0 && skew_ms.unsigned_abs() > cfg.winner_price_max_skew_ms {
+        return Decision::SkipStale { skew_ms };
+    }
+    if !plausible(latest_price, cfg) || !plausible(price_to_beat, cfg) {
+        return Decision::SkipSanity;
+    }
+    let diff = latest_price - price_to_beat;
+    if diff.is_zero() {
+        return Decision::SkipTied;
+    }
+    let min_margin = fraction_of(price_to_beat, cfg.sweep_min_margin_pct);
+    if diff.abs() < min_margin {
+        return Decision::SkipBelowMargin { diff, min_margin };
+    }
+    let side = if diff > Decimal::ZERO { "Up" } else { "Down" };
+    Decision::Sweep { side, diff }
+}
+
+/// Whether `latest_price` still favours `side` over `price_to_beat` by at least
+/// `min_edge_pct` of it. A tie never holds.
+pub fn edge_holds(side: &str, price_to_beat: Decimal, latest_price: Decimal, min_edge_pct: f64) -> bool {
+    let diff = latest_price - price_to_beat;
+    let edge = if side == "Up" { diff } else { -diff };
+    edge > Decimal::ZERO && edge >= fraction_of(price_to_beat, min_edge_pct)
+}
+
+/// (best bid, best ask) when the best ask is at or below the best bid. The CLOB matches such
+/// orders immediately, so a crossed or locked book in the mirror is a stale or partial update.
+pub fn crossed_book(book: &OrderBook) -> Option<(Decimal, Decimal)> {
+    let (best_bid, best_ask) = (book.best_bid()?, book.best_ask()?);
+    (best_ask <= best_bid).then_some((best_bid, best_ask))
+}
+
+/// Eligible depth on the recorded close book (the resolved winner's token).
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplayedBook {
+    pub side: String,
+    pub token_id: String,
+    pub eligible_levels: usize,
+    pub eligible_shares: f64,
+    pub eligible_notional: f64,
+}
+
+/// One round re-decided from `paper_trades.jsonl` (and its close book, if saved).
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplayedRound {
+    pub period_5: i64,
+    pub period: String,
+    pub condition_id: String,
+    pub price_to_beat: Decimal,
+    pub close_price: Option<Decimal>,
+    pub skew_ms: Option<i64>,
+    /// None when the round had no close price.
+    pub decision: Option<Decision>,
+    pub skip_reason: Option<SkipReason>,
+    /// What was logged at the time, for comparison.
+    pub recorded_status: String,
+    pub recorded_skip_reason: Option<SkipReason>,
+    pub actual: Option<String>,
+    pub book: Option<ReplayedBook>,
+}
+
+/// Re-run the decision for `symbol`'s last `n` rounds in `rows` with `cfg` (the symbol's
+/// effective config). Close books are read from `book_dir` (`book_snapshot_dir`) when set.
+pub fn replay(
+    rows: &[PaperTradeRow],
+    symbol: &str,
+    n: usize,
+    cfg: &StrategyConfig,
+    book_dir: Option<&Path>,
+) -> Vec<ReplayedRound> {
+    let mut rows: Vec<&PaperTradeRow> = rows.iter().filter(|r| r.symbol.eq_ignore_ascii_case(symbol)).collect();
+    rows.sort_by_key(|r| r.period_5);
+    let skip = rows.len().saturating_sub(n);
+    rows.into_iter()
+        .skip(skip)
+        .map(|row| {
+            let (price_to_beat, close_price) = (row.price_to_beat, row.close_price);
+            let skew_ms = row
+                .close_rtds_ts_ms
+                .map(|ts| ts - (row.period_5 + MARKET_5M_DURATION_SECS) * 1000);
+            let decision = close_price.map(|close| decide_winner(price_to_beat, close, skew_ms.unwrap_or(0), cfg));
+            let skip_reason = match decision {
+                Some(d) => d.skip_reason(),
+                None => Some(SkipReason::NoClosePrice),
+            };
+            ReplayedRound {
+                period_5: row.period_5,
+                period: row.period.clone(),
+                condition_id: row.condition_id.clone(),
+                price_to_beat,
+                close_price,
+                skew_ms,
+                decision,
+                skip_reason,
+                recorded_status: row.status.clone(),
+                recorded_skip_reason: row.skip_reason,
+                actual: row.actual.clone(),
+                book: book_dir.and_then(|dir| replay_book(dir, &row.symbol, row.period_5, cfg)),
+            }
+        })
+        .collect()
+}
+
+fn replay_book(dir: &Path, symbol: &str, period_5: i64, cfg: &StrategyConfig) -> Option<ReplayedBook> {
+    let snapshot = match recorder::read_book_snapshot(dir, symbol, period_5) {
+        Ok(snapshot) => snapshot?,
+        Err(e) => {
+            warn!("Replay: {} close book for {} unreadable: {:#}", symbol, period_5, e);
+            return None;
+        }
+    };
+    let book = snapshot.book.as_ref();
+    let asks = book.map(|b| b.eligible_asks(0.0, cfg.sweep_max_price)).unwrap_or_default();
+    Some(ReplayedBook {
+        side: snapshot.winner.clone(),
+        token_id: snapshot.token_id.clone(),
+        eligible_levels: asks.len(),
+        eligible_shares: asks.iter().map(|a| a.size.to_f64().unwrap_or(0.0)).sum(),
+        eligible_notional: book.map_or(0.0, |b| b.total_notional(0.0, cfg.sweep_max_price)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::models::OrderBookEntry;
+    use crate::recorder::BookSnapshot;
+    use std::str::FromStr;
+
+    fn row(period_5: i64, close_price: Option<&str>) -> PaperTradeRow {
+        PaperTradeRow {
+            symbol: "btc".to_string(),
+            period_5,
+            status: "predicted".to_string(),
+            price_to_beat: Decimal::ONE_HUNDRED,
+            close_price: close_price.map(d),
+            close_rtds_ts_ms: close_price.map(|_| (period_5 + MARKET_5M_DURATION_SECS) * 1000),
+            ..Default::default()
+        }
+    }
+
+    fn d(s: &str) -> Decimal {
+        Decimal::from_str(s).unwrap()
+    }
+
+    fn cfg(min_margin_pct: f64, max_skew_ms: u64) -> StrategyConfig {
+        let mut cfg = Config::default().strategy;
+        cfg.sweep_min_margin_pct = min_margin_pct;
+        cfg.winner_price_max_skew_ms = max_skew_ms;
+        cfg
+    }
+
+    #[test]
+    fn sweeps_the_side_the_close_moved_to() {
+        let cfg = cfg(0.001, 2000);
+        assert_eq!(decide_winner(d("100"), d("100.5"), 0, &cfg), Decision::Sweep { side: "Up", diff: d("0.5") });
+        assert_eq!(decide_winner(d("100"), d("99.5"), 0, &cfg), Decision::Sweep { side: "Down", diff: d("-0.5") });
+    }
+
+    #[test]
+    fn margin_boundary_is_inclusive() {
+        let cfg = cfg(0.001, 0);
+        assert_eq!(decide_winner(d("100"), d("100.1"), 0, &cfg), Decision::Sweep { side: "Up", diff: d("0.1") });
+        assert_eq!(decide_winner(d("100"), d("99.9"), 0, &cfg), Decision::Sweep { side: "Down", diff: d("-0.1") });
+        assert_eq!(
+            decide_winner(d("100"), d("100.09"), 0, &cfg),
+            Decision::SkipBelowMargin { diff: d("0.09"), min_margin: d("0.1") }
+        );
+        assert_eq!(
+            decide_winner(d("100"), d("99.91"), 0, &cfg),
+            Decision::SkipBelowMargin { diff: d("-0.09"), min_margin: d("0.1") }
+        );
+    }
+
+    #[test]
+    fn zero_margin_sweeps_any_nonzero_diff() {
+        let cfg = cfg(0.0, 0);
+        assert_eq!(decide_winner(d("100"), d("100.000001"), 0, &cfg), Decision::Sweep { side: "Up", diff: d("0.000001") });
+    }
+
+    #[test]
+    fn tie_is_skipped_even_without_a_margin() {
+        assert_eq!(decide_winner(d("100"), d("100.00"), 0, &cfg(0.0, 0)), Decision::SkipTied);
+        assert_eq!(decide_winner(d("100"), d("100"), 0, &cfg(0.01, 0)), Decision::SkipTied);
+    }
+
+    #[test]
+    fn stale_feed_is_checked_first_and_in_both_directions() {
+        let cfg = cfg(0.001, 2000);
+        assert_eq!(decide_winner(d("100"), d("100.5"), 2000, &cfg), Decision::Sweep { side: "Up", diff: d("0.5") });
+        assert_eq!(decide_winner(d("100"), d("100.5"), -2000, &cfg), Decision::Sweep { side: "Up", diff: d("0.5") });
+        assert_eq!(decide_winner(d("100"), d("100.5"), 2001, &cfg), Decision::SkipStale { skew_ms: 2001 });
+        assert_eq!(decide_winner(d("100"), d("100.5"), -2001, &cfg), Decision::SkipStale { skew_ms: -2001 });
+        // Stale wins over every other skip.
+        assert_eq!(decide_winner(d("100"), d("100"), 5000, &cfg), Decision::SkipStale { skew_ms: 5000 });
+        assert_eq!(decide_winner(d("0"), d("100"), 5000, &cfg), Decision::SkipStale { skew_ms: 5000 });
+    }
+
+    #[test]
+    fn zero_max_skew_disables_the_staleness_check() {
+        assert_eq!(
+            decide_winner(d("100"), d("100.5"), 600_000, &cfg(0.001, 0)),
+            Decision::Sweep { side: "Up", diff: d("0.5") }
+        );
+    }
+
+    #[test]
+    fn prices_outside_the_sanity_range_are_skipped() {
+        let cfg = cfg(0.001, 0);
+        for (ptb, close) in [("0", "100"), ("100", "0"), ("0.0009", "100"), ("100", "1000000.01"), ("-5", "-6")] {
+            assert_eq!(decide_winner(d(ptb), d(close), 0, &cfg), Decision::SkipSanity, "ptb={} close={}", ptb, close);
+        }
+        assert_eq!(decide_winner(d("0.001"), d("0.002"), 0, &cfg), Decision::Sweep { side: "Up", diff: d("0.001") });
+        assert!(matches!(decide_winner(d("1000000"), d("999000"), 0, &cfg), Decision::Sweep { side: "Down", .. }));
+    }
+
+    #[test]
+    fn per_symbol_bounds_catch_glitches_the_default_range_allows() {
+        let mut xrp = cfg(0.001, 0);
+        xrp.min_plausible_price = 0.01;
+        xrp.max_plausible_price = 100.0;
+        assert!(matches!(decide_winner(d("2.5"), d("250"), 0, &cfg(0.001, 0)), Decision::Sweep { .. }));
+        assert_eq!(decide_winner(d("2.5"), d("250"), 0, &xrp), Decision::SkipSanity);
+        assert_eq!(decide_winner(d("0.005"), d("2.5"), 0, &xrp), Decision::SkipSanity);
+        assert!(matches!(decide_winner(d("2.5"), d("2.6"), 0, &xrp), Decision::Sweep { side: "Up", .. }));
+    }
+
+    #[test]
+    fn every_skip_maps_to_its_skip_reason() {
+        let cases = [
+            (Decision::Sweep { side: "Up", diff: Decimal::ONE }, None),
+            (Decision::SkipStale { skew_ms: 1 }, Some(SkipReason::RtdsStale)),
+            (Decision::SkipSanity, Some(SkipReason::InvalidPrice)),
+            (Decision::SkipTied, Some(SkipReason::Tied)),
+            (Decision::SkipBelowMargin { diff: Decimal::ONE, min_margin: Decimal::TWO }, Some(SkipReason::BelowMargin)),
+        ];
+        for (decision, reason) in cases {
+            assert_eq!(decision.skip_reason(), reason, "{:?}", decision);
+        }
+    }
+
+    #[test]
+    fn edge_holds_until_the_price_flips_or_nears_the_threshold() {
+        let ptb = d("100");
+        assert!(edge_holds("Up", ptb, d("100.5"), 0.005));
+        assert!(!edge_holds("Up", ptb, d("100.4"), 0.005), "shrunk below the edge");
+        assert!(!edge_holds("Up", ptb, d("99"), 0.0), "flipped");
+        assert!(!edge_holds("Down", ptb, d("100"), 0.0), "tied");
+        assert!(edge_holds("Down", ptb, d("99.9"), 0.0));
+    }
+
+    #[test]
+    fn crossed_and_locked_books_are_detected() {
+        let book = |bids: &[&str], asks: &[&str]| OrderBook {
+            bids: bids.iter().map(|p| OrderBookEntry { price: d(p), size: d("10") }).collect(),
+            asks: asks.iter().map(|p| OrderBookEntry { price: d(p), size: d("10") }).collect(),
+        };
+        assert_eq!(crossed_book(&book(&["0.90", "0.96"], &["0.99", "0.95"])), Some((d("0.96"), d("0.95"))));
+        assert_eq!(crossed_book(&book(&["0.95"], &["0.95"])), Some((d("0.95"), d("0.95"))), "locked");
+        assert_eq!(crossed_book(&book(&["0.94"], &["0.95"])), None);
+        assert_eq!(crossed_book(&book(&[], &["0.95"])), None, "no bids: nothing to cross");
+    }
+
+    #[test]
+    fn replays_last_rounds_with_close_books() {
+        let dir = std::env::temp_dir().join(format!("replay_books_{}", std::process::id()));
+        let entry = |p: &str, s: &str| OrderBookEntry { price: Decimal::from_str(p).unwrap(), size: Decimal::from_str(s).unwrap() };
+        recorder::write_book_snapshot(&dir, &BookSnapshot {
+            symbol: "btc".to_string(),
+            period_5: 600,
+            condition_id: "cond".to_string(),
+            winner: "Down".to_string(),
+            token_id: "2".to_string(),
+            captured_ms: 900_000,
+            book: Some(OrderBook { bids: vec![], asks: vec![entry("0.95", "10"), entry("0.9995", "100")] }),
+        })
+        .unwrap();
+        let rows = vec![row(0, Some("101")), row(600, Some("99")), row(300, None)];
+        let cfg = Config::default().strategy;
+
+        let replayed = replay(&rows, "BTC", 2, &cfg, Some(&dir));
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(replayed.iter().map(|r| r.period_5).collect::<Vec<_>>(), vec![300, 600]);
+        assert_eq!(replayed[0].decision, None);
+        assert_eq!(replayed[0].skip_reason, Some(SkipReason::NoClosePrice));
+        assert_eq!(replayed[1].decision, Some(Decision::Sweep { side: "Down", diff: Decimal::from(-1) }));
+        let book = replayed[1].book.as_ref().unwrap();
+        assert_eq!((book.eligible_levels, book.eligible_shares), (1, 10.0));
+        assert!((book.eligible_notional - 9.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn replay_decides_from_the_logged_decimal_prices() {
+        let exact = PaperTradeRow {
+            price_to_beat: d("97500.123456789012"),
+            ..row(0, Some("97500.123456789013"))
+        };
+        let line = serde_json::to_string(&exact).unwrap();
+        assert!(line.contains(r#""price_to_beat":"97500.123456789012""#), "{}", line);
+        let rows: Vec<PaperTradeRow> = vec![serde_json::from_str(&line).unwrap()];
+
+        let replayed = replay(&rows, "btc", 1, &cfg(0.0, 0), None);
+        let diff = d("0.000000000001");
+        assert_eq!(replayed[0].decision, Some(Decision::Sweep { side: "Up", diff }));
+
+        // Rows written before prices were strings still load.
+        let mut old = serde_json::to_value(row(0, None)).unwrap();
+        old["price_to_beat"] = serde_json::json!(97500.25);
+        old["close_price"] = serde_json::json!(97501.5);
+        let old: PaperTradeRow = serde_json::from_value(old).unwrap();
+        assert_eq!((old.price_to_beat, old.close_price), (d("97500.25"), Some(d("97501.5"))));
+    }
+}
@@ -1,7 +1,12 @@
-use crate::api::PolymarketApi;
+use crate::api_traits::MarketData;
+use crate::config::OutcomeSide;
+use crate::models::Market;
 use anyhow::Result;
-use chrono::{TimeZone, Timelike};
+use chrono::TimeZone;
 use chrono_tz::America::New_York;
+use log::warn;
+use rust_decimal::Decimal;
+use std::str::FromStr;
 
 use std::sync::Arc;
 
@@ -9,20 +14,7 @@ pub const MARKET_5M_DURATION_SECS: i64 = 5 * 60;  // 300
 
 /// Polymarket aligns 5m markets to Eastern Time (ET). Period start = start of current window in ET, as Unix timestamp.
 fn period_start_et_unix(minutes: i64) -> i64 {
-    let utc_now = chrono::Utc::now();
-    let et = New_York;
-    let now_et = utc_now.with_timezone(&et);
-    let minute_floor = (now_et.minute() as i64 / minutes) * minutes;
-    let truncated_naive = now_et
-        .date_naive()
-        .and_hms_opt(now_et.hour(), minute_floor as u32, 0)
-        .unwrap();
-    let dt_et = et
-        .from_local_datetime(&truncated_naive)
-        .single()
-        .or_else(|| et.from_local_datetime(&truncated_naive).earliest())
-        .expect("ET period start");
-    dt_et.timestamp()
+    period_start_et_unix_for_timestamp(chrono::Utc::now().timestamp(), minutes)
 }
 
 /// 5m slug for any symbol: {symbol}-updown-5m-{timestamp} (e.g. btc, eth, sol, xrp).
@@ -30,6 +22,15 @@ pub fn build_5m_slug(symbol: &str, period_start_unix: i64) -> String {
     format!("{}-updown-5m-{}", symbol.to_lowercase(), period_start_unix)
 }
 
+/// Symbol of an up/down event slug for `duration`: "btc" for `btc-updown-5m-1767726000` and
+/// "5m". None for other slugs.
+pub fn updown_symbol(slug: &str, duration: &str) -> Option<String> {
+    let (symbol, rest) = slug.split_once("-updown-")?;
+    let ts = rest.strip_prefix(duration)?.strip_prefix('-')?;
+    let valid = !symbol.is_empty() && !ts.is_empty() && ts.bytes().all(|b| b.is_ascii_digit());
+    valid.then(|| symbol.to_lowercase())
+}
+
 /// Format a 5m period as "February 21, 6:35PM-6:40PM ET".
 pub fn format_5m_period_et(period_start_unix: i64) -> String {
     let start = chrono::Utc.timestamp_opt(period_start_unix, 0).single()
@@ -53,84 +54,146 @@ pub fn current_5m_period_start() -> i64 {
 }
 
 /// ET-aligned period start (Unix) that contains the given timestamp. Used to match RTDS price timestamp to market.
+///
+/// US Eastern offsets are whole hours (UTC-5 / UTC-4), so for a period length that divides an
+/// hour every ET boundary is also a UTC boundary and the start is `ts` floored to the period.
+/// This is exact across DST changes: the skipped spring-forward hour simply never occurs, and in
+/// the repeated fall-back hour (01:00-02:00 local) each occurrence is its own period with its own
+/// Unix start — 01:35 EDT and 01:35 EST are an hour apart and map to different markets.
 pub fn period_start_et_unix_for_timestamp(ts_sec: i64, minutes: i64) -> i64 {
-    let utc_dt = match chrono::Utc.timestamp_opt(ts_sec, 0).single() {
-        Some(dt) => dt,
-        None => return ts_sec,
-    };
-    let et = New_York;
-    let et_dt = utc_dt.with_timezone(&et);
-    let minute_floor = (et_dt.minute() as i64 / minutes) * minutes;
-    let truncated_naive = et_dt
-        .date_naive()
-        .and_hms_opt(et_dt.hour(), minute_floor as u32, 0)
-        .unwrap();
-    let dt_et = et
-        .from_local_datetime(&truncated_naive)
-        .single()
-        .or_else(|| et.from_local_datetime(&truncated_naive).earliest())
-        .expect("ET period for timestamp");
-    dt_et.timestamp()
+    debug_assert!(minutes > 0 && 60 % minutes == 0, "period must divide an hour: {}", minutes);
+    let len = minutes * 60;
+    ts_sec - ts_sec.rem_euclid(len)
+}
+
+/// Comparison implied by a market question: "above"/"over" vs "below"/"under".
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThresholdDirection {
+    Above,
+    Below,
 }
 
-/// Parse price-to-beat from market question (e.g. "Will Bitcoin be above $97,500 at ...").
-pub fn parse_price_to_beat_from_question(question: &str) -> Option<f64> {
+const ABOVE_KEYWORDS: [&str; 5] = ["above ", "over ", "higher than ", "greater than ", "at least "];
+const BELOW_KEYWORDS: [&str; 4] = ["below ", "under ", "lower than ", "less than "];
+
+/// Parse price-to-beat and comparison direction from a market question, e.g.
+/// "Will Bitcoin be above $97,500 at ...", "Will XRP be under $0.52?", "... below 3,250.75 USD".
+/// Accepts `$` / `US$` / `USD` prefixes, a trailing `USD`, comma grouping and decimals.
+/// Direction defaults to `Above` when the question has no comparison keyword.
+pub fn parse_price_to_beat_from_question(question: &str) -> Option<(Decimal, ThresholdDirection)> {
     let q = question.to_lowercase();
-    let idx = q.find("above ").or_else(|| q.find('$'))?;
-    let after = &question[idx..];
-    let mut num_start_byte = 0;
-    for (i, c) in after.char_indices() {
-        if c == '$' || c.is_ascii_digit() {
-            num_start_byte = if c == '$' {
-                i + c.len_utf8()
-            } else {
-                i
-            };
-            break;
+    let keyword = ABOVE_KEYWORDS.iter().map(|k| (k, ThresholdDirection::Above))
+        .chain(BELOW_KEYWORDS.iter().map(|k| (k, ThresholdDirection::Below)))
+        .filter_map(|(k, dir)| q.find(k).map(|idx| (idx + k.len(), dir)))
+        .min_by_key(|(idx, _)| *idx);
+    let direction = keyword.map(|(_, d)| d).unwrap_or(ThresholdDirection::Above);
+    let search_from = keyword.map(|(idx, _)| idx).unwrap_or(0);
+    let rest = &q[search_from..];
+
+    // Prefer an explicit currency marker; after a keyword, a bare number is accepted too.
+    let num_start = if let Some(idx) = rest.find('$') {
+        Some(idx + 1)
+    } else if let Some(idx) = rest.find("usd") {
+        let after = idx + 3;
+        if rest[after..].trim_start().starts_with(|c: char| c.is_ascii_digit()) {
+            Some(after + (rest[after..].len() - rest[after..].trim_start().len()))
+        } else {
+            // "97,500 USD": number right before the suffix
+            let before = rest[..idx].trim_end();
+            let start = before
+                .rfind(|c: char| !(c.is_ascii_digit() || c == '.' || c == ','))
+                .map(|i| i + 1)
+                .unwrap_or(0);
+            (start < before.len()).then_some(start)
         }
-    }
-    let num_str: String = after[num_start_byte..]
+    } else if keyword.is_some() {
+        rest.find(|c: char| c.is_ascii_digit())
+    } else {
+        None
+    }?;
+    parse_grouped_number(rest[num_start..].trim_start())
+        .map(|p| (p, direction))
+}
+
+/// Parse a leading number like "97,500", "1,234.50" or "0.5234", ignoring comma grouping and
+/// trailing sentence punctuation.
+fn parse_grouped_number(s: &str) -> Option<Decimal> {
+    let raw: String = s
         .chars()
         .take_while(|c| c.is_ascii_digit() || *c == '.' || *c == ',')
+        .collect();
+    let num_str: String = raw
+        .trim_end_matches(['.', ','])
+        .chars()
         .filter(|c| *c != ',')
         .collect();
     if num_str.is_empty() {
         return None;
     }
-    num_str.parse::<f64>().ok()
+    Decimal::from_str(&num_str).ok()
+}
+
+/// Unix time of a market's `end_date_iso` (RFC 3339, e.g. "2025-02-21T23:40:00Z").
+pub fn parse_end_date_iso(end_date_iso: &str) -> Option<i64> {
+    chrono::DateTime::parse_from_rfc3339(end_date_iso.trim()).ok().map(|dt| dt.timestamp())
+}
+
+/// Up/Down tokens of a binary market and its close time from the API.
+pub struct BinaryMarket {
+    pub up_token: String,
+    pub down_token: String,
+    /// Parsed `end_date_iso`; None if missing or unparsable.
+    pub end_time: Option<i64>,
 }
 
 pub struct MarketDiscovery {
-    api: Arc<PolymarketApi>,
+    api: Arc<dyn MarketData>,
 }
 
 impl MarketDiscovery {
-    pub fn new(api: Arc<PolymarketApi>) -> Self {
+    pub fn new(api: Arc<dyn MarketData>) -> Self {
         Self { api }
     }
 
-    pub async fn get_market_tokens(&self, condition_id: &str) -> Result<(String, String)> {
+    /// Up/Down token ids for a binary market. Returns None (with a warning) for markets that are
+    /// not a plain Up/Down pair — neg-risk / multi-outcome or unrecognised outcome labels —
+    /// so the symbol is skipped for the period instead of erroring.
+    pub async fn get_market_tokens(&self, condition_id: &str) -> Result<Option<BinaryMarket>> {
         let details = self.api.get_market(condition_id).await?;
+        let outcomes: Vec<&str> = details.tokens.iter().map(|t| t.outcome.as_str()).collect();
+        if details.neg_risk || details.tokens.len() != 2 {
+            warn!(
+                "Skipping non-binary market {} (neg_risk={}, outcomes={:?})",
+                condition_id, details.neg_risk, outcomes
+            );
+            return Ok(None);
+        }
+
         let mut up_token = None;
         let mut down_token = None;
-
-        for token in details.tokens {
-            let outcome = token.outcome.to_uppercase();
-            if outcome.contains("UP") || outcome == "1" {
-                up_token = Some(token.token_id);
-            } else if outcome.contains("DOWN") || outcome == "0" {
-                down_token = Some(token.token_id);
+        for token in &details.tokens {
+            match self.api.outcome_mapping().side(&token.outcome) {
+                Some(OutcomeSide::Up) => up_token = Some(token.token_id.clone()),
+                Some(OutcomeSide::Down) => down_token = Some(token.token_id.clone()),
+                None => {}
             }
         }
 
-        let up = up_token.ok_or_else(|| anyhow::anyhow!("Up token not found"))?;
-        let down = down_token.ok_or_else(|| anyhow::anyhow!("Down token not found"))?;
-
-        Ok((up, down))
+        match (up_token, down_token) {
+            (Some(up_token), Some(down_token)) => Ok(Some(BinaryMarket {
+                up_token,
+                down_token,
+                end_time: parse_end_date_iso(&details.end_date_iso),
+            })),
+            _ => {
+                warn!("Skipping market {}: no Up/Down outcome pair in {:?}", condition_id, outcomes);
+                Ok(None)
+            }
+        }
     }
 
-    /// Fetch 5m market by symbol and period start; returns (condition_id, question).
-    pub async fn get_5m_market(&self, symbol: &str, period_start: i64) -> Result<Option<(String, String)>> {
+    /// Fetch the open 5m market for a symbol and period start.
+    pub async fn get_5m_market(&self, symbol: &str, period_start: i64) -> Result<Option<Market>> {
         let slug = build_5m_slug(symbol, period_start);
         let market = match self.api.get_market_by_slug(&slug).await {
             Ok(m) => m,
@@ -139,6 +202,140 @@ impl MarketDiscovery {
         if !market.active || market.closed {
             return Ok(None);
         }
-        Ok(Some((market.condition_id, market.question)))
+        Ok(Some(market))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(y: i32, mo: u32, d: u32, h: u32, mi: u32, s: u32) -> i64 {
+        chrono::Utc.with_ymd_and_hms(y, mo, d, h, mi, s).unwrap().timestamp()
+    }
+
+    #[test]
+    fn updown_symbol_matches_only_the_duration() {
+        assert_eq!(updown_symbol("btc-updown-5m-1767726000", "5m"), Some("btc".to_string()));
+        assert_eq!(updown_symbol(&build_5m_slug("DOGE", 1767726000), "5m"), Some("doge".to_string()));
+        assert_eq!(updown_symbol("btc-updown-15m-1767726000", "5m"), None);
+        assert_eq!(updown_symbol("btc-updown-5m-", "5m"), None);
+        assert_eq!(updown_symbol("btc-updown-5m-next", "5m"), None);
+        assert_eq!(updown_symbol("bitcoin-up-or-down-june-10", "5m"), None);
+    }
+
+    #[test]
+    fn parses_end_date_iso() {
+        assert_eq!(parse_end_date_iso("2025-06-10T18:40:00Z"), Some(utc(2025, 6, 10, 18, 40, 0)));
+        assert_eq!(parse_end_date_iso("2025-06-10T14:40:00-04:00"), Some(utc(2025, 6, 10, 18, 40, 0)));
+        assert_eq!(parse_end_date_iso("2025-06-10"), None);
+        assert_eq!(parse_end_date_iso(""), None);
+    }
+
+    #[test]
+    fn period_start_is_et_aligned_on_normal_day() {
+        // 2025-06-10 14:37:42 EDT
+        let ts = utc(2025, 6, 10, 18, 37, 42);
+        let start = period_start_et_unix_for_timestamp(ts, 5);
+        assert_eq!(start, utc(2025, 6, 10, 18, 35, 0));
+        assert_eq!(format_5m_period_et(start), "June 10, 2:35PM-2:40PM ET");
+    }
+
+    #[test]
+    fn period_start_across_spring_forward() {
+        // 2025-03-09: 01:59:59 EST is followed by 03:00:00 EDT (07:00 UTC)
+        assert_eq!(period_start_et_unix_for_timestamp(utc(2025, 3, 9, 6, 59, 59), 5), utc(2025, 3, 9, 6, 55, 0));
+        assert_eq!(period_start_et_unix_for_timestamp(utc(2025, 3, 9, 7, 0, 0), 5), utc(2025, 3, 9, 7, 0, 0));
+        assert_eq!(format_5m_period_et(utc(2025, 3, 9, 6, 55, 0)), "March 9, 1:55AM-3:00AM ET");
+        assert_eq!(format_5m_period_et(utc(2025, 3, 9, 7, 0, 0)), "March 9, 3:00AM-3:05AM ET");
+    }
+
+    #[test]
+    fn period_start_in_repeated_fall_back_hour() {
+        // 2025-11-02: 01:00-02:00 local happens twice (EDT at 05:xx UTC, then EST at 06:xx UTC)
+        let first = period_start_et_unix_for_timestamp(utc(2025, 11, 2, 5, 37, 0), 5);
+        let second = period_start_et_unix_for_timestamp(utc(2025, 11, 2, 6, 37, 0), 5);
+        assert_eq!(first, utc(2025, 11, 2, 5, 35, 0));
+        assert_eq!(second, utc(2025, 11, 2, 6, 35, 0));
+        assert_eq!(second - first, 3600);
+        assert_eq!(format_5m_period_et(first), "November 2, 1:35AM-1:40AM ET");
+        assert_eq!(format_5m_period_et(second), "November 2, 1:35AM-1:40AM ET");
+        assert_ne!(build_5m_slug("btc", first), build_5m_slug("btc", second));
+    }
+
+    #[test]
+    fn period_start_at_exact_boundary() {
+        let ts = utc(2025, 11, 2, 6, 0, 0);
+        assert_eq!(period_start_et_unix_for_timestamp(ts, 5), ts);
+        assert_eq!(period_start_et_unix_for_timestamp(ts - 1, 5), ts - 300);
+        assert_eq!(period_start_et_unix_for_timestamp(ts + 899, 15), ts);
+    }
+
+    fn d(s: &str) -> Decimal {
+        Decimal::from_str(s).unwrap()
+    }
+
+    #[test]
+    fn parses_btc_above_with_grouping() {
+        assert_eq!(
+            parse_price_to_beat_from_question("Will Bitcoin be above $97,500 on February 21?"),
+            Some((d("97500"), ThresholdDirection::Above))
+        );
+    }
+
+    #[test]
+    fn parses_eth_below_with_decimals() {
+        assert_eq!(
+            parse_price_to_beat_from_question("Will Ethereum be below $3,250.75 at 6PM ET?"),
+            Some((d("3250.75"), ThresholdDirection::Below))
+        );
+    }
+
+    #[test]
+    fn parses_sol_under_with_trailing_period() {
+        assert_eq!(
+            parse_price_to_beat_from_question("Will Solana close under $142.5."),
+            Some((d("142.5"), ThresholdDirection::Below))
+        );
+    }
+
+    #[test]
+    fn parses_xrp_sub_dollar() {
+        assert_eq!(
+            parse_price_to_beat_from_question("Will XRP be above $0.5234 on March 3?"),
+            Some((d("0.5234"), ThresholdDirection::Above))
+        );
+    }
+
+    #[test]
+    fn parses_usd_prefix_and_suffix() {
+        assert_eq!(
+            parse_price_to_beat_from_question("Will BTC be over USD 1,234,567.89 at close?"),
+            Some((d("1234567.89"), ThresholdDirection::Above))
+        );
+        assert_eq!(
+            parse_price_to_beat_from_question("Will ETH be less than 2,999 USD on May 1?"),
+            Some((d("2999"), ThresholdDirection::Below))
+        );
+        assert_eq!(
+            parse_price_to_beat_from_question("Will SOL be under US$150 tomorrow?"),
+            Some((d("150"), ThresholdDirection::Below))
+        );
+    }
+
+    #[test]
+    fn defaults_to_above_without_keyword() {
+        assert_eq!(
+            parse_price_to_beat_from_question("Bitcoin price target $100,000"),
+            Some((d("100000"), ThresholdDirection::Above))
+        );
+    }
+
+    #[test]
+    fn updown_question_has_no_threshold() {
+        assert_eq!(
+            parse_price_to_beat_from_question("Bitcoin Up or Down - February 21, 6:35PM-6:40PM ET"),
+            None
+        );
     }
 }
@@ -0,0 +1,90 @@
+//! Price and ID formatting for log lines, the paper trade markdown and dashboard messages, so
+//! raw feed precision (`$97234.56000001`) and 77-digit token IDs never reach them.
+
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+
+/// Significant digits kept for underlying prices.
+const PRICE_SIG_DIGITS: i32 = 6;
+/// Decimals for outcome token prices (sub-cent ticks are 0.001, fills can average finer).
+pub const TOKEN_PRICE_DECIMALS: usize = 4;
+/// Bytes of a token or condition ID kept by `short`.
+const SHORT_ID_LEN: usize = 16;
+
+/// Decimals to show for an underlying price of this magnitude: at least 2 (cents), more for
+/// small prices so sub-dollar symbols keep their significant digits (XRP $2.51234, not $2.51).
+pub fn price_decimals(reference: Decimal) -> usize {
+    let magnitude = reference.abs().to_f64().unwrap_or(0.0);
+    if magnitude < 1e-9 {
+        return PRICE_SIG_DIGITS as usize;
+    }
+    let int_digits = magnitude.log10().floor() as i32 + 1;
+    (PRICE_SIG_DIGITS - int_digits.max(0)).clamp(2, PRICE_SIG_DIGITS) as usize
+}
+
+/// Underlying price as `$97234.56` / `$2.51234`.
+pub fn usd(price: Decimal) -> String {
+    let dp = price_decimals(price);
+    format!("${:.*}", dp, price.round_dp(dp as u32))
+}
+
+/// Signed price difference at the precision of `reference` (usually the price-to-beat), so the
+/// diff lines up with the prices it was computed from: `+$12.34`, `-$0.00123`.
+pub fn usd_diff(diff: Decimal, reference: Decimal) -> String {
+    let sign = if diff.is_sign_negative() { "-" } else { "+" };
+    let dp = price_decimals(reference);
+    format!("{}${:.*}", sign, dp, diff.abs().round_dp(dp as u32))
+}
+
+/// Dollar amount (cost, budget, P&L) to the cent.
+pub fn dollars(amount: f64) -> String {
+    format!("${:.2}", amount)
+}
+
+/// Outcome token price, e.g. `0.9500`.
+pub fn token_price(price: f64) -> String {
+    format!("{:.*}", TOKEN_PRICE_DECIMALS, price)
+}
+
+/// Token or condition ID cut to its first `SHORT_ID_LEN` bytes for logs. Backs off to a char
+/// boundary, so unexpected non-ASCII API data can't panic the caller.
+pub fn short(id: &str) -> &str {
+    let mut end = id.len().min(SHORT_ID_LEN);
+    while !id.is_char_boundary(end) {
+        end -= 1;
+    }
+    &id[..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn d(s: &str) -> Decimal {
+        Decimal::from_str(s).unwrap()
+    }
+
+    #[test]
+    fn precision_follows_magnitude() {
+        assert_eq!(usd(d("97234.56000001")), "$97234.56");
+        assert_eq!(usd(d("3456.7")), "$3456.70");
+        assert_eq!(usd(d("182.123456")), "$182.123");
+        assert_eq!(usd(d("2.5123449")), "$2.51234");
+        assert_eq!(usd(d("0.123456789")), "$0.123457");
+        assert_eq!(usd_diff(d("-0.004"), d("97234.5")), "-$0.00");
+        assert_eq!(usd_diff(d("0.00123"), d("2.5")), "+$0.00123");
+        assert_eq!(dollars(14.349999), "$14.35");
+        assert_eq!(token_price(0.95), "0.9500");
+    }
+
+    #[test]
+    fn short_ids_never_split_a_char() {
+        assert_eq!(short(""), "");
+        assert_eq!(short("111"), "111");
+        assert_eq!(short("0x1234567890abcdef1234"), "0x1234567890abcd");
+        // 15 ASCII bytes then a 2-byte char straddling the cut.
+        assert_eq!(short("123456789012345é67"), "123456789012345");
+        assert_eq!(short("ééééééééééé"), "éééééééé");
+    }
+}
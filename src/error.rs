@@ -0,0 +1,41 @@
+//! Typed API errors for order placement, so callers decide halt vs continue by variant
+//! instead of matching on error text.
+
+use std::fmt;
+
+#[derive(Debug)]
+pub enum ApiError {
+    /// The request may have reached the exchange (in-flight timeout, transport error or a
+    /// 5xx answer): the order state is unknown and sweeping must stop.
+    NetworkAmbiguous(String),
+    /// The order was definitely not placed: a 4xx rejection, SDK validation or a failure
+    /// before anything was sent.
+    Rejected(String),
+    /// HTTP 429 from the exchange; the order was not placed.
+    RateLimited(String),
+    /// Not authenticated, bad credentials or geoblocked.
+    Auth(String),
+    /// Invalid input (price, size, token id) or an unparsable response.
+    Parse(String),
+}
+
+impl ApiError {
+    /// True when the order may have been placed despite the error.
+    pub fn is_ambiguous(&self) -> bool {
+        matches!(self, ApiError::NetworkAmbiguous(_))
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ApiError::NetworkAmbiguous(m) => write!(f, "network error (order may be placed): {}", m),
+            ApiError::Rejected(m) => write!(f, "rejected: {}", m),
+            ApiError::RateLimited(m) => write!(f, "rate limited: {}", m),
+            ApiError::Auth(m) => write!(f, "auth error: {}", m),
+            ApiError::Parse(m) => write!(f, "parse error: {}", m),
+        }
+    }
+}
+
+impl std::error::Error for ApiError {}
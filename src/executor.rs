@@ -10,8 +10,9 @@
 //! - Network error handling (halt on ambiguous failures)
 //! - Execution logging
 
-use crate::api::PolymarketApi;
-use anyhow::Result;
+use crate::api_traits::OrderPlacement;
+use crate::display::short;
+use crate::strategy::{floor_to_lot, LOT_SIZE_DECIMALS};
 use log::{error, info, warn};
 use std::sync::Arc;
 use tokio::time::{sleep, Duration};
@@ -39,6 +40,7 @@ pub enum Side {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[allow(clippy::upper_case_acronyms)]
 pub enum IntentOrderType {
     /// Fill-or-Kill: fill entire size immediately or cancel.
     FOK,
@@ -83,6 +85,8 @@ pub struct ExecutorConfig {
     pub inter_order_delay: Duration,
     /// Max consecutive not-fillable results before stopping the batch.
     pub max_consecutive_misses: u32,
+    /// Max orders sent per batch (None = unlimited).
+    pub max_orders: Option<u32>,
     /// Whether to actually send orders (false = paper/dry-run mode).
     pub live: bool,
 }
@@ -95,6 +99,7 @@ impl Default for ExecutorConfig {
             min_size: 0.01,
             inter_order_delay: Duration::from_millis(50),
             max_consecutive_misses: 3,
+            max_orders: None,
             live: false,
         }
     }
@@ -103,12 +108,12 @@ impl Default for ExecutorConfig {
 // ── Executor ───────────────────────────────────────────────────────────
 
 pub struct OrderExecutor {
-    api: Arc<PolymarketApi>,
+    api: Arc<dyn OrderPlacement>,
     config: ExecutorConfig,
 }
 
 impl OrderExecutor {
-    pub fn new(api: Arc<PolymarketApi>, config: ExecutorConfig) -> Self {
+    pub fn new(api: Arc<dyn OrderPlacement>, config: ExecutorConfig) -> Self {
         Self { api, config }
     }
 
@@ -135,8 +140,16 @@ impl OrderExecutor {
         let mut results = Vec::with_capacity(intents.len());
         let mut total_cost: f64 = 0.0;
         let mut consecutive_misses: u32 = 0;
+        let mut orders_sent: u32 = 0;
 
         for intent in intents {
+            if let Some(max_orders) = self.config.max_orders {
+                if orders_sent >= max_orders {
+                    info!("Executor: order-count cap hit ({} orders), stopping batch", max_orders);
+                    break;
+                }
+            }
+
             // ── Pre-flight checks ──
             if let Some(rejection) = self.validate(&intent, total_cost) {
                 info!("Executor: REJECTED {} — {}", self.intent_summary(&intent), rejection);
@@ -158,7 +171,7 @@ impl OrderExecutor {
                 0.0
             };
             let actual_size = intent.size.min(affordable_size);
-            let actual_size = (actual_size * 100.0).floor() / 100.0; // round down to 2dp
+            let actual_size = floor_to_lot(actual_size, LOT_SIZE_DECIMALS);
 
             if actual_size < self.config.min_size {
                 info!("Executor: SKIP {} — capped size {:.2} below min", self.intent_summary(&intent), actual_size);
@@ -173,6 +186,7 @@ impl OrderExecutor {
             }
 
             // ── Execute ──
+            orders_sent += 1;
             let result = if self.config.live {
                 self.execute_live(&intent, actual_size).await
             } else {
@@ -292,13 +306,10 @@ impl OrderExecutor {
                 order_id: None,
             },
             Err(e) => {
-                let err_str = e.to_string().to_lowercase();
-                let is_network = err_str.contains("network")
-                    || err_str.contains("timeout")
-                    || err_str.contains("connection");
+                warn!("Executor: order failed: {}", e);
                 ExecutionResult {
                     intent: intent.clone(),
-                    status: if is_network { FillStatus::NetworkError } else { FillStatus::Rejected },
+                    status: if e.is_ambiguous() { FillStatus::NetworkError } else { FillStatus::Rejected },
                     filled_size: 0.0,
                     filled_price: 0.0,
                     order_id: None,
@@ -314,7 +325,7 @@ impl OrderExecutor {
             if intent.side == Side::Buy { "BUY" } else { "SELL" },
             actual_size,
             intent.price,
-            intent.token_id[..intent.token_id.len().min(16)].to_string(),
+            short(&intent.token_id),
             intent.reason,
         );
         ExecutionResult {
@@ -332,7 +343,7 @@ impl OrderExecutor {
             if intent.side == Side::Buy { "BUY" } else { "SELL" },
             intent.size,
             intent.price,
-            &intent.token_id[..intent.token_id.len().min(12)],
+            short(&intent.token_id),
             intent.reason,
         )
     }
@@ -1,10 +1,52 @@
 //! Shared in-memory log buffer with broadcast channel for SSE subscribers.
 
 use std::collections::VecDeque;
-use std::sync::Arc;
-use tokio::sync::{broadcast, RwLock};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
+use tokio::sync::{broadcast, mpsc, RwLock};
 
 const MAX_ENTRIES: usize = 500;
+/// `log::` records queued for the buffer before further ones are dropped.
+const MIRROR_QUEUE: usize = 1024;
+/// Broadcast capacity when none is configured (`dashboard.sse_channel_capacity`).
+pub const DEFAULT_CHANNEL_CAPACITY: usize = 256;
+/// `log::` target for records about the dashboard stream itself (a subscriber lagging). They go
+/// to stderr only: mirrored, each lag warning would add an entry and feed the lag it reports.
+pub const SSE_LOG_TARGET: &str = "dashboard_sse";
+
+/// Entry levels, least to most severe. `debug` is per-order / per-step detail; round summaries
+/// are `info`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    #[default]
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl LogLevel {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LogLevel::Debug => "debug",
+            LogLevel::Info => "info",
+            LogLevel::Warn => "warn",
+            LogLevel::Error => "error",
+        }
+    }
+
+    /// Dashboard level for a `log::` record; trace has none and is never mirrored.
+    fn from_log(level: log::Level) -> Option<Self> {
+        match level {
+            log::Level::Error => Some(LogLevel::Error),
+            log::Level::Warn => Some(LogLevel::Warn),
+            log::Level::Info => Some(LogLevel::Info),
+            log::Level::Debug => Some(LogLevel::Debug),
+            log::Level::Trace => None,
+        }
+    }
+}
 
 #[derive(Clone, serde::Serialize)]
 pub struct LogEntry {
@@ -14,19 +56,42 @@ pub struct LogEntry {
     pub message: String,
 }
 
+impl LogEntry {
+    /// Whether the entry is at `min` or above. Unrecognized levels are always shown.
+    pub fn at_least(&self, min: LogLevel) -> bool {
+        let level = match self.level.as_str() {
+            "debug" => LogLevel::Debug,
+            "info" => LogLevel::Info,
+            "warn" => LogLevel::Warn,
+            "error" => LogLevel::Error,
+            _ => return true,
+        };
+        level >= min
+    }
+}
+
 /// Thread-safe log buffer that stores recent entries and broadcasts new ones to SSE subscribers.
 #[derive(Clone)]
 pub struct LogBuffer {
     entries: Arc<RwLock<VecDeque<LogEntry>>>,
     tx: broadcast::Sender<LogEntry>,
+    /// Entries SSE subscribers missed because they fell more than the channel capacity behind.
+    lagged: Arc<AtomicU64>,
 }
 
 impl LogBuffer {
     pub fn new() -> Self {
-        let (tx, _) = broadcast::channel(256);
+        Self::with_capacity(DEFAULT_CHANNEL_CAPACITY)
+    }
+
+    /// Buffer whose broadcast channel holds `channel_capacity` entries per subscriber before it
+    /// starts dropping the oldest. Must be at least 1.
+    pub fn with_capacity(channel_capacity: usize) -> Self {
+        let (tx, _) = broadcast::channel(channel_capacity);
         Self {
             entries: Arc::new(RwLock::new(VecDeque::with_capacity(MAX_ENTRIES))),
             tx,
+            lagged: Arc::new(AtomicU64::new(0)),
         }
     }
 
@@ -58,4 +123,118 @@ impl LogBuffer {
     pub fn subscribe(&self) -> broadcast::Receiver<LogEntry> {
         self.tx.subscribe()
     }
+
+    /// Count entries a subscriber skipped after lagging.
+    pub fn record_lagged(&self, skipped: u64) {
+        self.lagged.fetch_add(skipped, Ordering::Relaxed);
+    }
+
+    /// Total entries skipped by lagging subscribers since startup, summed over subscribers.
+    pub fn lagged_total(&self) -> u64 {
+        self.lagged.load(Ordering::Relaxed)
+    }
+}
+
+type MirrorQueue = (LogLevel, mpsc::Sender<(LogLevel, String)>);
+
+/// Global `log::Log` that writes through to env_logger and, once `LogMirror::attach` is called,
+/// also copies records into a `LogBuffer` as `SYS` entries (`dashboard.mirror_logs`).
+pub struct TeeLogger {
+    inner: env_logger::Logger,
+    mirror: Arc<OnceLock<MirrorQueue>>,
+}
+
+/// Handle for attaching the dashboard buffer to the installed `TeeLogger`.
+pub struct LogMirror(Arc<OnceLock<MirrorQueue>>);
+
+impl TeeLogger {
+    fn new(inner: env_logger::Logger) -> (Self, LogMirror) {
+        let mirror = Arc::new(OnceLock::new());
+        (Self { inner, mirror: Arc::clone(&mirror) }, LogMirror(mirror))
+    }
+
+    /// Install as the global logger. Only records env_logger's filter (`RUST_LOG`) passes are
+    /// mirrored.
+    pub fn init(inner: env_logger::Logger) -> LogMirror {
+        let max_level = inner.filter();
+        let (logger, mirror) = Self::new(inner);
+        log::set_boxed_logger(Box::new(logger)).expect("logger already installed");
+        log::set_max_level(max_level);
+        mirror
+    }
+}
+
+impl log::Log for TeeLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &log::Record) {
+        if !self.inner.matches(record) {
+            return;
+        }
+        self.inner.log(record);
+        let Some((min, tx)) = self.mirror.get() else { return };
+        if record.target() == SSE_LOG_TARGET {
+            return;
+        }
+        if let Some(level) = LogLevel::from_log(record.level()).filter(|l| l >= min) {
+            // Never block the caller: when the buffer can't keep up, drop the record.
+            let _ = tx.try_send((level, record.args().to_string()));
+        }
+    }
+
+    fn flush(&self) {
+        self.inner.flush()
+    }
+}
+
+impl LogMirror {
+    /// Start copying records at `min` or above into `buffer`. Later calls are ignored.
+    pub fn attach(&self, buffer: LogBuffer, min: LogLevel) {
+        let (tx, mut rx) = mpsc::channel(MIRROR_QUEUE);
+        if self.0.set((min, tx)).is_err() {
+            return;
+        }
+        tokio::spawn(async move {
+            while let Some((level, message)) = rx.recv().await {
+                buffer.push("SYS", level.as_str(), message).await;
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use log::Log;
+
+    #[tokio::test]
+    async fn mirror_respects_the_min_level_and_skips_sse_lag() {
+        let inner = env_logger::Builder::new().filter_level(log::LevelFilter::Debug).is_test(true).build();
+        let (logger, mirror) = TeeLogger::new(inner);
+        let buffer = LogBuffer::new();
+        let record = |level, message| {
+            logger.log(&log::Record::builder().level(level).args(format_args!("{}", message)).build());
+        };
+        record(log::Level::Info, "before attach");
+        mirror.attach(buffer.clone(), LogLevel::Info);
+        record(log::Level::Debug, "per-ask detail");
+        logger.log(
+            &log::Record::builder()
+                .level(log::Level::Warn)
+                .target(SSE_LOG_TARGET)
+                .args(format_args!("Dashboard SSE subscriber fell behind, skipped 3 events"))
+                .build(),
+        );
+        record(log::Level::Warn, "RTDS reconnecting");
+
+        while buffer.snapshot().await.is_empty() {
+            tokio::task::yield_now().await;
+        }
+        let entries = buffer.snapshot().await;
+        assert_eq!(entries.len(), 1);
+        assert_eq!((entries[0].symbol.as_str(), entries[0].level.as_str()), ("SYS", "warn"));
+        assert_eq!(entries[0].message, "RTDS reconnecting");
+    }
 }
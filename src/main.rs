@@ -1,13 +1,24 @@
 mod api;
+mod api_traits;
 mod chainlink;
 mod config;
+mod control;
+mod decision;
 mod discovery;
+mod display;
+mod error;
 #[allow(dead_code)]
 mod executor;
 mod log_buffer;
+mod metrics;
+#[cfg(test)]
+mod mock_api;
 mod models;
+mod notify;
 mod orderbook_ws;
 mod paper_trade;
+mod recorder;
+mod replay;
 mod rtds;
 mod strategy;
 mod web;
@@ -16,8 +27,12 @@ mod web;
 use anyhow::Result;
 use clap::Parser;
 use config::{Args, Config};
+use control::TradingControl;
 use log_buffer::LogBuffer;
-use std::io::Write;
+use metrics::Metrics;
+use notify::Notifier;
+use orderbook_ws::OrderbookMirror;
+use std::io::{IsTerminal, Write};
 use std::sync::Arc;
 use api::PolymarketApi;
 use strategy::ArbStrategy;
@@ -28,54 +43,127 @@ async fn main() -> Result<()> {
         .install_default()
         .expect("Failed to install rustls crypto provider");
 
-    env_logger::Builder::from_default_env()
-        .filter_level(log::LevelFilter::Info)
-        .format(|buf, record| writeln!(buf, "{}", record.args()))
-        .init();
+    let log_mirror = log_buffer::TeeLogger::init(
+        env_logger::Builder::from_default_env()
+            .filter_level(log::LevelFilter::Info)
+            .format(|buf, record| writeln!(buf, "{}", record.args()))
+            .build(),
+    );
 
     let args = Args::parse();
-    let config = Config::load(&args.config)?;
+    if let Some(out) = &args.export_csv {
+        let rows = paper_trade::export_csv(std::path::Path::new(paper_trade::PAPER_TRADES_JSONL), out)?;
+        eprintln!("Exported {} rounds from {} to {}", rows, paper_trade::PAPER_TRADES_JSONL, out.display());
+        return Ok(());
+    }
+    let mut config = Config::load(&args.config)?;
+    if let Some(parent) = args.parent_collection_id {
+        config.polymarket.redeem_parent_collection_id = Some(parent);
+    }
+    if args.print_config {
+        println!("{}", config.resolved_json()?);
+        return Ok(());
+    }
 
     eprintln!("----------------------------------------------------");
-    eprintln!("5m post-close sweep bot (BTC, ETH, SOL, XRP)");
-    eprintln!("   Price-to-beat: RTDS Chainlink per symbol for 5m period");
+    let period_mins = discovery::MARKET_5M_DURATION_SECS / 60;
+    let symbols: Vec<String> = config.strategy.symbols.iter().map(|s| s.to_uppercase()).collect();
+    eprintln!("{}m post-close sweep bot ({})", period_mins, symbols.join(", "));
+    eprintln!("   Build: {}", config::BUILD_INFO);
+    eprintln!("   Price-to-beat: RTDS Chainlink per symbol for {}m period", period_mins);
     eprintln!("----------------------------------------------------");
 
-    let api = Arc::new(PolymarketApi::new(
-        config.polymarket.gamma_api_url.clone(),
-        config.polymarket.clob_api_url.clone(),
-        config.polymarket.private_key.clone(),
-        config.polymarket.proxy_wallet_address.clone(),
-        config.polymarket.signature_type,
-        config.polymarket.rpc_urls.clone(),
-    ));
+    let api = Arc::new(PolymarketApi::new(&config.polymarket));
 
     if args.redeem {
-        run_redeem_only(api.as_ref(), &config, args.condition_id.as_deref()).await?;
+        run_redeem_only(api.as_ref(), &config, args.condition_id.as_deref(), args.simulate_only, args.yes).await?;
         return Ok(());
     }
 
+    if let Some(dir) = &args.backtest {
+        config.strategy.simulation_mode = true;
+        config.strategy.record_dir = None;
+        // Replayed books never update and the wall clock is far past the recorded close, so
+        // don't wait between passes and don't apply the post-close deadline.
+        config.strategy.sweep_empty_pass_wait_ms = 0;
+        config.strategy.sweep_hard_deadline_ms = None;
+        let events = recorder::load_events(dir)?;
+        eprintln!("Backtest mode: {} events from {}", events.len(), dir.display());
+        let notifier = Notifier::new(None, std::time::Duration::from_millis(config.notifications.webhook_timeout_ms));
+        let mirror = Arc::new(OrderbookMirror::new(&config.polymarket.ws_url));
+        let replay = Arc::new(replay::ReplayApi::from_events(&events));
+        let strategy = ArbStrategy::new(replay, config, LogBuffer::new(), notifier, TradingControl::new(), Metrics::new(), mirror);
+        return strategy.run_backtest(events).await;
+    }
+
+    let notifier = Notifier::new(
+        config.notifications.webhook_url.clone(),
+        std::time::Duration::from_millis(config.notifications.webhook_timeout_ms),
+    );
+
     // Start web dashboard
-    let log_buffer = LogBuffer::new();
-    web::spawn_dashboard(log_buffer.clone()).await;
+    let log_buffer = LogBuffer::with_capacity(config.dashboard.sse_channel_capacity);
+    if let Some(level) = config.dashboard.mirror_logs {
+        log_mirror.attach(log_buffer.clone(), level);
+    }
+    let control = TradingControl::new();
+    let metrics = Metrics::new();
+    let orderbook_mirror = Arc::new(OrderbookMirror::new(&config.polymarket.ws_url));
+    let strategy = ArbStrategy::new(
+        api.clone(),
+        config.clone(),
+        log_buffer.clone(),
+        notifier.clone(),
+        Arc::clone(&control),
+        Arc::clone(&metrics),
+        Arc::clone(&orderbook_mirror),
+    );
+    if let Err(e) = web::spawn_dashboard(
+        log_buffer,
+        Arc::clone(&api),
+        control,
+        metrics,
+        orderbook_mirror,
+        config.dashboard.clone(),
+        strategy.shared_settings(),
+    )
+    .await
+    {
+        if config.dashboard.required {
+            anyhow::bail!("Dashboard unavailable and dashboard.required is set: {}", e);
+        }
+        log::error!("Dashboard unavailable, continuing without it: {}", e);
+    }
 
     if config.polymarket.private_key.is_some() {
         if let Err(e) = api.authenticate().await {
             log::error!("Authentication failed: {}", e);
+            notifier.notify(notify::EVENT_AUTH_FAILURE, "SYS", format!("Authentication failed: {}", e), serde_json::Value::Null);
+            // Give the fire-and-forget webhook a moment before the process exits.
+            tokio::time::sleep(std::time::Duration::from_millis(config.notifications.webhook_timeout_ms)).await;
             anyhow::bail!("Authentication failed. Please check your credentials.");
         }
+        if config.polymarket.cancel_stale_orders_on_startup {
+            match api.cancel_stale_orders().await {
+                Ok(0) => {}
+                Ok(n) => log::warn!("Startup: cancelled {} resting order(s) on ended markets", n),
+                Err(e) => log::error!("Startup: stale order cleanup failed: {}", e),
+            }
+        }
     } else {
-        log::warn!("⚠️ No private key provided. Bot can only monitor (no orders).");
+        log::warn!("⚠️ No private key provided: monitor mode. Sweeps are simulated against live books and no orders are sent.");
     }
 
-    let strategy = ArbStrategy::new(api, config, log_buffer);
-    strategy.run().await
+    strategy.spawn_config_watch(args.config.clone());
+    strategy.run(args.once).await
 }
 
 async fn run_redeem_only(
     api: &PolymarketApi,
     config: &Config,
     condition_id: Option<&str>,
+    simulate_only: bool,
+    yes: bool,
 ) -> Result<()> {
     let proxy = config
         .polymarket
@@ -83,7 +171,12 @@ async fn run_redeem_only(
         .as_deref()
         .ok_or_else(|| anyhow::anyhow!("--redeem requires proxy_wallet_address in config.json"))?;
 
-    eprintln!("Redeem-only mode (proxy: {})", proxy);
+    eprintln!(
+        "Redeem-only mode (proxy: {}){}",
+        proxy,
+        if simulate_only { ", simulate only" } else { "" }
+    );
+    let balance_before = report_usdc_balance(api, proxy).await;
     let cids: Vec<String> = if let Some(cid) = condition_id {
         let cid = if cid.starts_with("0x") {
             cid.to_string()
@@ -103,11 +196,58 @@ async fn run_redeem_only(
         list
     };
 
+    let positions = match api.get_positions(proxy, false).await {
+        Ok(positions) => Some(positions),
+        Err(e) => {
+            eprintln!("Could not read positions, share counts unknown: {}", e);
+            None
+        }
+    };
+    eprintln!("\nRedemption preview:");
+    let mut previews = Vec::with_capacity(cids.len());
+    for cid in &cids {
+        match api.redeem_preview(cid, positions.as_deref()).await {
+            Ok(preview) => {
+                print_redeem_preview(&preview);
+                previews.push(Some(preview));
+            }
+            Err(e) => {
+                eprintln!("  {}: preview failed: {}", display::short(cid), e);
+                previews.push(None);
+            }
+        }
+    }
+    let payout: f64 = previews.iter().flatten().filter_map(|p| p.shares).sum();
+    eprintln!("Expected payout: {} across {} condition(s)", display::dollars(payout), cids.len());
+    if !simulate_only && !yes {
+        if !std::io::stdin().is_terminal() {
+            anyhow::bail!("stdin is not a terminal, so redemptions can't be confirmed; pass --yes to send them non-interactively");
+        }
+        if !confirm(format!("Send {} redemption(s)?", cids.len())).await? {
+            eprintln!("Aborted; nothing sent.");
+            return Ok(());
+        }
+    }
+
     let mut ok_count = 0u32;
     let mut fail_count = 0u32;
-    for cid in &cids {
-        eprintln!("\n--- Redeeming condition {} ---", &cid[..cid.len().min(18)]);
-        match api.redeem_tokens(cid, "Up").await {
+    for (cid, preview) in cids.iter().zip(&previews) {
+        eprintln!("\n--- Redeeming condition {} ---", display::short(cid));
+        let Some(outcome) = preview.as_ref().and_then(|p| p.winner.as_deref()) else {
+            eprintln!("Not redeeming {}: winner unknown, preview failed or market unresolved (skipping)", cid);
+            fail_count += 1;
+            continue;
+        };
+        if let Err(e) = api.verify_resolution(cid, outcome).await {
+            eprintln!("Not redeeming {}: {} (skipping)", cid, e);
+            fail_count += 1;
+            continue;
+        }
+        match api.redeem_tokens(cid, outcome, simulate_only).await {
+            Ok(_) if simulate_only => {
+                eprintln!("Simulation passed: {}", cid);
+                ok_count += 1;
+            }
             Ok(_) => {
                 eprintln!("Success: {}", cid);
                 ok_count += 1;
@@ -122,5 +262,48 @@ async fn run_redeem_only(
         "\nRedeem complete. Succeeded: {}, Failed: {}",
         ok_count, fail_count
     );
+    if let (Some(before), Some(after)) = (balance_before, report_usdc_balance(api, proxy).await) {
+        eprintln!("USDC change: {:+.2}", after - before);
+    }
     Ok(())
 }
+
+fn print_redeem_preview(preview: &models::RedeemPreview) {
+    let cid = &preview.condition_id;
+    eprintln!("  {} {}", display::short(cid), preview.question);
+    let winner = preview.winner.as_deref().unwrap_or("unresolved (redemption will fail)");
+    let (shares, payout) = match preview.shares {
+        Some(shares) => (format!("{:.2}", shares), display::dollars(shares)),
+        None => ("?".to_string(), "?".to_string()),
+    };
+    eprintln!(
+        "    winner: {} | index sets: {:?} | winning shares: {} | payout: {}",
+        winner, preview.index_sets, shares, payout
+    );
+}
+
+/// Ask `prompt` and read y/N from stdin; anything but y/yes (including EOF) declines.
+async fn confirm(prompt: String) -> Result<bool> {
+    tokio::task::spawn_blocking(move || {
+        eprint!("{} [y/N] ", prompt);
+        std::io::stderr().flush()?;
+        let mut answer = String::new();
+        std::io::stdin().read_line(&mut answer)?;
+        Ok(matches!(answer.trim().to_ascii_lowercase().as_str(), "y" | "yes"))
+    })
+    .await?
+}
+
+/// Print the USDC balance of `wallet`; None if the RPC read fails.
+async fn report_usdc_balance(api: &PolymarketApi, wallet: &str) -> Option<f64> {
+    match api.usdc_balance(wallet).await {
+        Ok(balance) => {
+            eprintln!("USDC balance: {:.2}", balance);
+            Some(balance)
+        }
+        Err(e) => {
+            eprintln!("Could not read USDC balance: {}", e);
+            None
+        }
+    }
+}
@@ -0,0 +1,271 @@
+//! Shared runtime metrics exposed by the dashboard.
+
+use chrono::Utc;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Ask price buckets for fill-rate tracking: (label, exclusive upper bound).
+const FILL_BUCKETS: [(&str, f64); 4] = [
+    ("<0.90", 0.90),
+    ("0.90-0.95", 0.95),
+    ("0.95-0.99", 0.99),
+    ("0.99+", f64::INFINITY),
+];
+
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct BucketCounts {
+    pub attempted: u64,
+    pub filled: u64,
+}
+
+/// Attempted vs filled FOK orders per ask price bucket.
+#[derive(Debug, Clone, Default)]
+pub struct FillHistogram {
+    buckets: [BucketCounts; FILL_BUCKETS.len()],
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FillBucketReport {
+    pub bucket: &'static str,
+    pub attempted: u64,
+    pub filled: u64,
+    pub fill_rate: f64,
+}
+
+impl FillHistogram {
+    pub fn record(&mut self, price: f64, filled: bool) {
+        let idx = FILL_BUCKETS
+            .iter()
+            .position(|(_, upper)| price < *upper)
+            .unwrap_or(FILL_BUCKETS.len() - 1);
+        self.buckets[idx].attempted += 1;
+        if filled {
+            self.buckets[idx].filled += 1;
+        }
+    }
+
+    pub fn merge(&mut self, other: &FillHistogram) {
+        for (a, b) in self.buckets.iter_mut().zip(other.buckets.iter()) {
+            a.attempted += b.attempted;
+            a.filled += b.filled;
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.buckets.iter().all(|b| b.attempted == 0)
+    }
+
+    pub fn report(&self) -> Vec<FillBucketReport> {
+        FILL_BUCKETS
+            .iter()
+            .zip(self.buckets.iter())
+            .map(|((label, _), c)| FillBucketReport {
+                bucket: label,
+                attempted: c.attempted,
+                filled: c.filled,
+                fill_rate: if c.attempted > 0 { c.filled as f64 / c.attempted as f64 } else { 0.0 },
+            })
+            .collect()
+    }
+
+    /// Compact one-line summary, e.g. "0.95-0.99 2/5, 0.99+ 1/1".
+    pub fn summary(&self) -> String {
+        self.report()
+            .iter()
+            .filter(|r| r.attempted > 0)
+            .map(|r| format!("{} {}/{}", r.bucket, r.filled, r.attempted))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// RTDS socket activity, to tell "socket open but silent" apart from "reconnecting".
+#[derive(Default)]
+pub struct RtdsStats {
+    messages_total: AtomicU64,
+    reconnects: AtomicU64,
+    /// Local receive time of the last message (Unix ms), 0 = none yet.
+    last_message_ms: AtomicI64,
+    per_symbol: Mutex<HashMap<String, u64>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RtdsReport {
+    pub messages_total: u64,
+    pub reconnects: u64,
+    pub last_message_ms: Option<i64>,
+    pub last_message_age_ms: Option<i64>,
+    pub per_symbol: BTreeMap<String, u64>,
+}
+
+impl RtdsStats {
+    /// Count one received text message (any topic).
+    pub fn record_message(&self) {
+        self.messages_total.fetch_add(1, Ordering::Relaxed);
+        self.last_message_ms.store(Utc::now().timestamp_millis(), Ordering::Relaxed);
+    }
+
+    /// Count one chainlink price for a tracked symbol.
+    pub fn record_symbol(&self, symbol: &str) {
+        *self.per_symbol.lock().unwrap().entry(symbol.to_string()).or_insert(0) += 1;
+    }
+
+    pub fn record_reconnect(&self) {
+        self.reconnects.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn report(&self) -> RtdsReport {
+        let last = self.last_message_ms.load(Ordering::Relaxed);
+        let last_message_ms = (last > 0).then_some(last);
+        RtdsReport {
+            messages_total: self.messages_total.load(Ordering::Relaxed),
+            reconnects: self.reconnects.load(Ordering::Relaxed),
+            last_message_ms,
+            last_message_age_ms: last_message_ms.map(|t| Utc::now().timestamp_millis() - t),
+            per_symbol: self.per_symbol.lock().unwrap().iter().map(|(k, v)| (k.clone(), *v)).collect(),
+        }
+    }
+}
+
+/// Recent rounds per symbol behind the RTDS vs Chainlink RPC agreement stats.
+const PRICE_AGREEMENT_WINDOW: usize = 100;
+
+/// Per-symbol close-price differences between RTDS and the Chainlink RPC over the last
+/// `PRICE_AGREEMENT_WINDOW` rounds. Persistent disagreement points at a wrong feed mapping or a
+/// broken feed.
+#[derive(Default)]
+pub struct PriceAgreement {
+    /// (absolute difference %, beyond tolerance), oldest first.
+    per_symbol: Mutex<BTreeMap<String, VecDeque<(f64, bool)>>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PriceAgreementReport {
+    pub rounds: usize,
+    pub mean_abs_diff_pct: f64,
+    pub max_abs_diff_pct: f64,
+    /// Rounds in the window beyond `price_agreement_tolerance_pct`.
+    pub disagreements: usize,
+    pub last_abs_diff_pct: f64,
+}
+
+impl PriceAgreement {
+    pub fn record(&self, symbol: &str, abs_diff_pct: f64, disagrees: bool) {
+        let mut per_symbol = self.per_symbol.lock().unwrap();
+        let window = per_symbol.entry(symbol.to_string()).or_default();
+        if window.len() >= PRICE_AGREEMENT_WINDOW {
+            window.pop_front();
+        }
+        window.push_back((abs_diff_pct, disagrees));
+    }
+
+    pub fn report(&self) -> BTreeMap<String, PriceAgreementReport> {
+        self.per_symbol
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, w)| !w.is_empty())
+            .map(|(symbol, window)| {
+                let diffs = window.iter().map(|(d, _)| *d);
+                let report = PriceAgreementReport {
+                    rounds: window.len(),
+                    mean_abs_diff_pct: diffs.clone().sum::<f64>() / window.len() as f64,
+                    max_abs_diff_pct: diffs.fold(0.0, f64::max),
+                    disagreements: window.iter().filter(|(_, bad)| *bad).count(),
+                    last_abs_diff_pct: window.back().map_or(0.0, |(d, _)| *d),
+                };
+                (symbol.clone(), report)
+            })
+            .collect()
+    }
+}
+
+/// Trade prints kept for the dashboard.
+const MAX_TRADE_PRINTS: usize = 200;
+
+/// One CLOB last-trade print from RTDS. `ts_ms` is the local receive time.
+#[derive(Debug, Clone, Serialize)]
+pub struct TradePrint {
+    pub ts_ms: i64,
+    pub asset_id: String,
+    pub market: String,
+    pub price: f64,
+    pub size: f64,
+    pub side: String,
+}
+
+/// One symbol's current round, for the dashboard schedule grid.
+#[derive(Debug, Clone, Serialize)]
+pub struct SymbolSchedule {
+    pub symbol: String,
+    pub period_start: i64,
+    /// Close time (Unix seconds).
+    pub close_time: i64,
+    pub has_price_to_beat: bool,
+    /// Orderbook WS subscribed for this round's tokens.
+    pub subscribed: bool,
+    /// This round's outcome tokens, once the market is discovered.
+    pub up_token: Option<String>,
+    pub down_token: Option<String>,
+}
+
+/// Process-wide metrics shared between the strategy and the dashboard.
+#[derive(Default)]
+pub struct Metrics {
+    /// Live FOK fill histogram aggregated across all rounds.
+    pub fills: Mutex<FillHistogram>,
+    /// RTDS socket counters.
+    pub rtds: RtdsStats,
+    /// RTDS vs Chainlink RPC close-price agreement per symbol.
+    pub price_agreement: PriceAgreement,
+    /// Recent last-trade prints for the traded tokens, oldest first.
+    trades: Mutex<VecDeque<TradePrint>>,
+    /// Current round per symbol, replaced by the strategy as the round progresses.
+    schedule: Mutex<BTreeMap<String, SymbolSchedule>>,
+    /// Latest chainlink price per symbol.
+    prices: Mutex<HashMap<String, f64>>,
+}
+
+pub type SharedMetrics = Arc<Metrics>;
+
+impl Metrics {
+    pub fn new() -> SharedMetrics {
+        Arc::new(Self::default())
+    }
+
+    pub fn record_round_fills(&self, round: &FillHistogram) {
+        self.fills.lock().unwrap().merge(round);
+    }
+
+    pub fn record_trade(&self, print: TradePrint) {
+        let mut trades = self.trades.lock().unwrap();
+        if trades.len() >= MAX_TRADE_PRINTS {
+            trades.pop_front();
+        }
+        trades.push_back(print);
+    }
+
+    /// Recent trade prints, newest first.
+    pub fn recent_trades(&self) -> Vec<TradePrint> {
+        self.trades.lock().unwrap().iter().rev().cloned().collect()
+    }
+
+    pub fn set_schedule(&self, entries: Vec<SymbolSchedule>) {
+        *self.schedule.lock().unwrap() = entries.into_iter().map(|e| (e.symbol.clone(), e)).collect();
+    }
+
+    /// Current round per symbol, sorted by symbol.
+    pub fn schedule(&self) -> Vec<SymbolSchedule> {
+        self.schedule.lock().unwrap().values().cloned().collect()
+    }
+
+    pub fn set_price(&self, symbol: &str, price: f64) {
+        self.prices.lock().unwrap().insert(symbol.to_string(), price);
+    }
+
+    pub fn price(&self, symbol: &str) -> Option<f64> {
+        self.prices.lock().unwrap().get(symbol).copied()
+    }
+}
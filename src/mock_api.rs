@@ -0,0 +1,194 @@
+//! In-memory `TradingApi` for tests: canned markets and scripted FOK results, recording every
+//! order it receives.
+
+use crate::api_traits::{MarketData, OrderPlacement};
+use crate::config::OutcomeMapping;
+use crate::error::ApiError;
+use crate::models::{Market, MarketDetails, OrderBook, OrderResponse, Position, Trade};
+use anyhow::{anyhow, Result};
+use futures_util::future::BoxFuture;
+use rust_decimal::Decimal;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+/// One FOK order received by the mock: (token_id, size, price).
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlacedOrder {
+    pub token_id: String,
+    pub size: f64,
+    pub price: f64,
+}
+
+#[derive(Default)]
+pub struct MockApi {
+    pub markets_by_slug: HashMap<String, Market>,
+    pub markets: HashMap<String, MarketDetails>,
+    /// Symbols returned by `discover_active_updown_markets`, for any duration.
+    pub updown_symbols: Vec<String>,
+    pub outcomes: OutcomeMapping,
+    /// Positions returned for any wallet.
+    pub positions: Vec<Position>,
+    /// Simulates lost credentials: `is_authenticated()` returns false.
+    pub unauthenticated: bool,
+    /// Simulates a config without `private_key` (monitor mode); implies unauthenticated.
+    pub no_private_key: bool,
+    /// Tick size returned by `warm_order_cache` per token; 0.01 when absent.
+    pub tick_sizes: HashMap<String, Decimal>,
+    /// Books returned by `get_orderbooks`; tokens not listed are left out of the result.
+    pub books: HashMap<String, OrderBook>,
+    /// Trades returned by `get_trades`, filtered by token and time like the real API.
+    pub trades: Vec<Trade>,
+    /// Price returned by `get_chainlink_price_rpc` for any aggregator (updated at the requested
+    /// time); an RPC error when unset.
+    pub chainlink_price: Option<Decimal>,
+    /// Results returned by successive `place_fok_buy` calls; once empty every order fills.
+    order_results: Mutex<VecDeque<std::result::Result<Option<OrderResponse>, ApiError>>>,
+    placed: Mutex<Vec<PlacedOrder>>,
+    presigned: Mutex<Vec<PlacedOrder>>,
+}
+
+impl MockApi {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue results for the next `place_fok_buy` calls, in order.
+    pub fn with_order_results(
+        self,
+        results: Vec<std::result::Result<Option<OrderResponse>, ApiError>>,
+    ) -> Self {
+        *self.order_results.lock().unwrap() = results.into();
+        self
+    }
+
+    pub fn placed_orders(&self) -> Vec<PlacedOrder> {
+        self.placed.lock().unwrap().clone()
+    }
+
+    /// Orders presigned and not yet cleared.
+    pub fn presigned_orders(&self) -> Vec<PlacedOrder> {
+        self.presigned.lock().unwrap().clone()
+    }
+
+    pub fn filled(order_id: &str) -> std::result::Result<Option<OrderResponse>, ApiError> {
+        Ok(Some(OrderResponse {
+            order_id: Some(order_id.to_string()),
+            status: "MATCHED".to_string(),
+            message: None,
+            filled_shares: None,
+            filled_cost: None,
+        }))
+    }
+
+    /// A fill reporting the shares received and USDC actually paid.
+    pub fn filled_at(order_id: &str, shares: f64, cost: f64) -> std::result::Result<Option<OrderResponse>, ApiError> {
+        Ok(Some(OrderResponse {
+            filled_shares: Some(shares),
+            filled_cost: Some(cost),
+            ..Self::filled(order_id)?.unwrap()
+        }))
+    }
+}
+
+impl MarketData for MockApi {
+    fn get_market_by_slug<'a>(&'a self, slug: &'a str) -> BoxFuture<'a, Result<Market>> {
+        let market = self.markets_by_slug.get(slug).cloned().ok_or_else(|| anyhow!("no market {}", slug));
+        Box::pin(async move { market })
+    }
+
+    fn discover_active_updown_markets<'a>(&'a self, _duration: &'a str) -> BoxFuture<'a, Result<Vec<String>>> {
+        let symbols = self.updown_symbols.clone();
+        Box::pin(async move { Ok(symbols) })
+    }
+
+    fn get_market<'a>(&'a self, condition_id: &'a str) -> BoxFuture<'a, Result<MarketDetails>> {
+        let market = self.markets.get(condition_id).cloned().ok_or_else(|| anyhow!("no market {}", condition_id));
+        Box::pin(async move { market })
+    }
+
+    fn get_chainlink_price_rpc<'a>(&'a self, _aggregator: &'a str, at_ts: i64) -> BoxFuture<'a, Result<(Decimal, i64)>> {
+        let price = self.chainlink_price.map(|p| (p, at_ts)).ok_or_else(|| anyhow!("no Chainlink RPC in mock"));
+        Box::pin(async move { price })
+    }
+
+    fn outcome_mapping(&self) -> &OutcomeMapping {
+        &self.outcomes
+    }
+
+    fn get_positions<'a>(&'a self, _wallet: &'a str, redeemable_only: bool) -> BoxFuture<'a, Result<Vec<Position>>> {
+        let positions = self.positions.iter().filter(|p| !redeemable_only || p.redeemable).cloned().collect();
+        Box::pin(async move { Ok(positions) })
+    }
+
+    fn get_orderbooks<'a>(&'a self, token_ids: &'a [&'a str]) -> BoxFuture<'a, Result<HashMap<String, OrderBook>>> {
+        let books = token_ids
+            .iter()
+            .filter_map(|id| Some((id.to_string(), self.books.get(*id)?.clone())))
+            .collect();
+        Box::pin(async move { Ok(books) })
+    }
+
+    fn get_trades<'a>(&'a self, _condition_id: &'a str, token_id: &'a str, after_ts: i64) -> BoxFuture<'a, Result<Vec<Trade>>> {
+        let trades = self
+            .trades
+            .iter()
+            .filter(|t| t.asset == token_id && t.timestamp > after_ts)
+            .cloned()
+            .collect();
+        Box::pin(async move { Ok(trades) })
+    }
+}
+
+impl OrderPlacement for MockApi {
+    fn has_private_key(&self) -> bool {
+        !self.no_private_key
+    }
+
+    fn is_authenticated(&self) -> bool {
+        !self.unauthenticated && !self.no_private_key
+    }
+
+    fn wallet_address(&self) -> Option<String> {
+        Some("0xmock".to_string())
+    }
+
+    fn warm_order_cache<'a>(&'a self, token_id: &'a str) -> BoxFuture<'a, Result<Decimal>> {
+        let tick = self.tick_sizes.get(token_id).copied().unwrap_or(Decimal::new(1, 2));
+        Box::pin(async move { Ok(tick) })
+    }
+
+    fn presign_fok_buy<'a>(&'a self, token_id: &'a str, size: &'a str, price: &'a str) -> BoxFuture<'a, Result<()>> {
+        self.presigned.lock().unwrap().push(PlacedOrder {
+            token_id: token_id.to_string(),
+            size: size.parse().unwrap_or(0.0),
+            price: price.parse().unwrap_or(0.0),
+        });
+        Box::pin(async { Ok(()) })
+    }
+
+    fn clear_presigned(&self) {
+        self.presigned.lock().unwrap().clear();
+    }
+
+    fn place_fok_buy<'a>(
+        &'a self,
+        token_id: &'a str,
+        size: &'a str,
+        price: &'a str,
+    ) -> BoxFuture<'a, std::result::Result<Option<OrderResponse>, ApiError>> {
+        let mut placed = self.placed.lock().unwrap();
+        placed.push(PlacedOrder {
+            token_id: token_id.to_string(),
+            size: size.parse().unwrap_or(0.0),
+            price: price.parse().unwrap_or(0.0),
+        });
+        let n = placed.len();
+        let result = self
+            .order_results
+            .lock()
+            .unwrap()
+            .pop_front()
+            .unwrap_or_else(|| Self::filled(&format!("mock-{}", n)));
+        Box::pin(async move { result })
+    }
+}
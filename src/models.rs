@@ -13,6 +13,10 @@ pub struct Market {
     pub end_date_iso: Option<String>,
     pub active: bool,
     pub closed: bool,
+    /// Threshold from the market's structured metadata, when Gamma provides one; filled in by
+    /// `get_market_by_slug`.
+    #[serde(skip)]
+    pub price_to_beat: Option<rust_decimal::Decimal>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,6 +29,9 @@ pub struct MarketDetails {
     pub closed: bool,
     #[serde(rename = "end_date_iso")]
     pub end_date_iso: String,
+    /// Multi-outcome (negative risk) market; not a simple Up/Down pair.
+    #[serde(default)]
+    pub neg_risk: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -41,6 +48,62 @@ pub struct OrderBook {
     pub asks: Vec<OrderBookEntry>,
 }
 
+impl OrderBook {
+    /// Highest bid price. Levels may arrive in any order.
+    pub fn best_bid(&self) -> Option<Decimal> {
+        self.bids.iter().map(|b| b.price).max()
+    }
+
+    /// Lowest ask price.
+    pub fn best_ask(&self) -> Option<Decimal> {
+        self.asks.iter().map(|a| a.price).min()
+    }
+
+    /// Midpoint of the best bid and ask; None unless both sides have a level.
+    pub fn mid(&self) -> Option<Decimal> {
+        Some((self.best_bid()? + self.best_ask()?) / Decimal::TWO)
+    }
+
+    /// Best ask minus best bid (negative on a crossed book).
+    pub fn spread(&self) -> Option<Decimal> {
+        Some(self.best_ask()? - self.best_bid()?)
+    }
+
+    /// Asks, highest price first: the order the sweep takes them.
+    pub fn sorted_asks_desc(&self) -> Vec<&OrderBookEntry> {
+        let mut asks: Vec<_> = self.asks.iter().collect();
+        asks.sort_by_key(|a| std::cmp::Reverse(a.price));
+        asks
+    }
+
+    /// Asks priced within `[min_price, max_price]`, highest first. A price with no f64
+    /// equivalent is never eligible.
+    pub fn eligible_asks(&self, min_price: f64, max_price: f64) -> Vec<&OrderBookEntry> {
+        use rust_decimal::prelude::ToPrimitive;
+        self.sorted_asks_desc()
+            .into_iter()
+            .filter(|a| a.price.to_f64().is_some_and(|p| (min_price..=max_price).contains(&p)))
+            .collect()
+    }
+
+    /// Price × size (USD) over the asks within `[min_price, max_price]`.
+    pub fn total_notional(&self, min_price: f64, max_price: f64) -> f64 {
+        use rust_decimal::prelude::ToPrimitive;
+        let total: Decimal = self.eligible_asks(min_price, max_price).iter().map(|a| a.price * a.size).sum();
+        total.to_f64().unwrap_or(0.0)
+    }
+}
+
+/// One book in the CLOB `POST /books` response.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TokenOrderBook {
+    pub asset_id: String,
+    #[serde(default)]
+    pub bids: Vec<OrderBookEntry>,
+    #[serde(default)]
+    pub asks: Vec<OrderBookEntry>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OrderBookEntry {
     pub price: Decimal,
@@ -52,6 +115,21 @@ pub struct OrderResponse {
     pub order_id: Option<String>,
     pub status: String,
     pub message: Option<String>,
+    /// Shares received and USDC paid on a matched buy, when the CLOB reports them.
+    #[serde(default)]
+    pub filled_shares: Option<f64>,
+    #[serde(default)]
+    pub filled_cost: Option<f64>,
+}
+
+impl OrderResponse {
+    /// Average price actually paid (cost / shares), if the fill amounts were reported.
+    pub fn effective_price(&self) -> Option<f64> {
+        match (self.filled_shares, self.filled_cost) {
+            (Some(shares), Some(cost)) if shares > 0.0 => Some(cost / shares),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -62,3 +140,148 @@ pub struct RedeemResponse {
     pub amount_redeemed: Option<String>,
 }
 
+/// What `redeem_tokens` would do for one condition, from read-only lookups.
+#[derive(Debug, Clone)]
+pub struct RedeemPreview {
+    pub condition_id: String,
+    pub question: String,
+    /// Winning outcome per the CLOB; None until the market resolves.
+    pub winner: Option<String>,
+    /// Index sets passed to `redeemPositions`.
+    pub index_sets: Vec<u64>,
+    /// Winning shares held, each paying $1 USDC; None when positions couldn't be read.
+    pub shares: Option<f64>,
+}
+
+
+/// One wallet position from the data API (`/positions`). Numeric fields may arrive as numbers
+/// or strings.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Position {
+    #[serde(default)]
+    pub asset: String,
+    #[serde(default)]
+    pub condition_id: String,
+    #[serde(default, deserialize_with = "deser_lenient_f64")]
+    pub size: f64,
+    #[serde(default, deserialize_with = "deser_lenient_f64")]
+    pub avg_price: f64,
+    #[serde(default, deserialize_with = "deser_lenient_f64")]
+    pub cur_price: f64,
+    #[serde(default, deserialize_with = "deser_lenient_f64")]
+    pub current_value: f64,
+    #[serde(default)]
+    pub redeemable: bool,
+    #[serde(default)]
+    pub outcome: String,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub slug: String,
+}
+
+/// One market trade from the data API (`/trades`). `side` is the taker's, `BUY` or `SELL`;
+/// `timestamp` is in Unix seconds.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Trade {
+    #[serde(default)]
+    pub asset: String,
+    #[serde(default)]
+    pub side: String,
+    #[serde(default, deserialize_with = "deser_lenient_f64")]
+    pub price: f64,
+    #[serde(default, deserialize_with = "deser_lenient_f64")]
+    pub size: f64,
+    #[serde(default)]
+    pub timestamp: i64,
+}
+
+/// One of our orders resting on the CLOB. `side` is `BUY` or `SELL`; `size` is the original
+/// order size and `filled` the part already matched, both in shares.
+#[derive(Debug, Clone, Serialize)]
+pub struct OpenOrder {
+    pub order_id: String,
+    /// Market condition ID.
+    pub market: String,
+    pub token_id: String,
+    pub outcome: String,
+    pub side: String,
+    pub price: f64,
+    pub size: f64,
+    pub filled: f64,
+}
+
+fn deser_lenient_f64<'de, D>(d: D) -> Result<f64, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Ok(match serde_json::Value::deserialize(d)? {
+        serde_json::Value::Number(n) => n.as_f64().unwrap_or(0.0),
+        serde_json::Value::String(s) => s.parse().unwrap_or(0.0),
+        _ => 0.0,
+    })
+}
+
+/// `fraction` of `amount`. The config fraction is converted without its excess binary digits,
+/// so a margin of 0.001 is exactly 0.001.
+pub fn fraction_of(amount: Decimal, fraction: f64) -> Decimal {
+    use rust_decimal::prelude::FromPrimitive;
+    amount * Decimal::from_f64(fraction).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn book(bids: &[&str], asks: &[(&str, &str)]) -> OrderBook {
+        OrderBook {
+            bids: bids.iter().map(|p| OrderBookEntry { price: p.parse().unwrap(), size: Decimal::TEN }).collect(),
+            asks: asks
+                .iter()
+                .map(|(p, s)| OrderBookEntry { price: p.parse().unwrap(), size: s.parse().unwrap() })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn quotes_ignore_level_order() {
+        let b = book(&["0.40", "0.47"], &[("0.55", "1"), ("0.51", "1")]);
+        assert_eq!(b.best_bid(), Some("0.47".parse().unwrap()));
+        assert_eq!(b.best_ask(), Some("0.51".parse().unwrap()));
+        assert_eq!(b.mid(), Some("0.49".parse().unwrap()));
+        assert_eq!(b.spread(), Some("0.04".parse().unwrap()));
+        assert_eq!(book(&[], &[("0.5", "1")]).mid(), None);
+    }
+
+    #[test]
+    fn eligible_asks_are_bounded_and_highest_first() {
+        let b = book(&[], &[("0.90", "10"), ("0.999", "5"), ("0.95", "20"), ("0.10", "100")]);
+        let prices: Vec<String> = b.eligible_asks(0.5, 0.99).iter().map(|a| a.price.to_string()).collect();
+        assert_eq!(prices, vec!["0.95", "0.90"]);
+        assert_eq!(b.sorted_asks_desc().len(), 4);
+        assert_eq!(b.total_notional(0.5, 0.99), 28.0);
+        assert_eq!(b.total_notional(0.0, 0.95), 38.0, "bounds are inclusive");
+    }
+
+    #[test]
+    fn extreme_prices_fail_the_range_check() {
+        // `Decimal::to_f64` converts every one of these, so it's the range check that drops them.
+        let b = OrderBook {
+            bids: vec![],
+            asks: [
+                Decimal::MAX,
+                Decimal::MIN,
+                Decimal::new(-95, 2),
+                // 1.0000000000000000000000000001: max scale, just above a whole share.
+                Decimal::from_i128_with_scale(10_000_000_000_000_000_000_000_000_001, 28),
+            ]
+            .into_iter()
+                .map(|price| OrderBookEntry { price, size: Decimal::ONE })
+                .collect(),
+        };
+        assert!(b.eligible_asks(0.0, 0.99).is_empty());
+        assert_eq!(b.total_notional(0.0, 0.99), 0.0);
+    }
+}
@@ -0,0 +1,59 @@
+//! Fire-and-forget webhook notifications (Discord/Telegram relays, etc.).
+//! Every payload carries an `event_type` so receivers can route on it.
+
+use chrono::Utc;
+use log::debug;
+use reqwest::Client;
+use serde_json::Value;
+use std::time::Duration;
+
+pub const EVENT_SWEEP_EXECUTED: &str = "sweep_executed";
+pub const EVENT_WRONG_WINNER: &str = "wrong_winner";
+pub const EVENT_RTDS_STALE: &str = "rtds_stale";
+pub const EVENT_AUTH_FAILURE: &str = "auth_failure";
+pub const EVENT_CIRCUIT_BREAKER: &str = "circuit_breaker";
+pub const EVENT_DAILY_LOSS_LIMIT: &str = "daily_loss_limit";
+
+/// Cheap-to-clone webhook sender. A no-op when no `webhook_url` is configured.
+#[derive(Clone)]
+pub struct Notifier {
+    client: Client,
+    url: Option<String>,
+}
+
+impl Notifier {
+    pub fn new(url: Option<String>, timeout: Duration) -> Self {
+        let client = Client::builder()
+            .timeout(timeout)
+            .build()
+            .expect("Failed to create webhook HTTP client");
+        Self { client, url }
+    }
+
+    /// POST `{event_type, symbol, message, timestamp, details}` in a background task.
+    /// Never blocks the caller; failures are only logged at debug level.
+    pub fn notify(&self, event_type: &str, symbol: &str, message: String, details: Value) {
+        let url = match &self.url {
+            Some(u) => u.clone(),
+            None => return,
+        };
+        let body = serde_json::json!({
+            "event_type": event_type,
+            "symbol": symbol,
+            "message": message,
+            "timestamp": Utc::now().to_rfc3339(),
+            "details": details,
+        });
+        let client = self.client.clone();
+        let event_type = event_type.to_string();
+        tokio::spawn(async move {
+            match client.post(&url).json(&body).send().await {
+                Ok(resp) if !resp.status().is_success() => {
+                    debug!("Webhook {} returned {}", event_type, resp.status());
+                }
+                Ok(_) => {}
+                Err(e) => debug!("Webhook {} failed: {}", event_type, e),
+            }
+        });
+    }
+}
@@ -1,6 +1,7 @@
+use crate::display::short;
 use crate::models::{OrderBook, OrderBookEntry};
 use anyhow::{Context, Result};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use std::sync::atomic::{AtomicU64, Ordering};
 use tokio::sync::{Notify, RwLock};
@@ -10,9 +11,14 @@ use futures_util::StreamExt;
 use log::{debug, warn};
 use alloy::primitives::U256;
 use polymarket_client_sdk::clob::ws::Client as WsClient;
+use polymarket_client_sdk::ws::config::Config as WsConfig;
 
 pub struct OrderbookMirror {
+    /// CLOB WS base URL (`polymarket.ws_url`); the SDK appends `/ws/market`.
+    ws_url: String,
     books: Arc<RwLock<HashMap<String, OrderBook>>>,
+    /// Tokens the WS has delivered a book for since `subscribe`; `seed_orderbook` leaves them alone.
+    ws_tokens: Arc<std::sync::Mutex<HashSet<String>>>,
     notify: Arc<Notify>,
     active_tasks: std::sync::Mutex<Vec<JoinHandle<()>>>,
     update_count: Arc<AtomicU64>,
@@ -20,9 +26,11 @@ pub struct OrderbookMirror {
 }
 
 impl OrderbookMirror {
-    pub fn new() -> Self {
+    pub fn new(ws_url: &str) -> Self {
         Self {
+            ws_url: ws_url.to_string(),
             books: Arc::new(RwLock::new(HashMap::new())),
+            ws_tokens: Arc::new(std::sync::Mutex::new(HashSet::new())),
             notify: Arc::new(Notify::new()),
             active_tasks: std::sync::Mutex::new(Vec::new()),
             update_count: Arc::new(AtomicU64::new(0)),
@@ -57,7 +65,9 @@ impl OrderbookMirror {
         // The SDK's subscribe_orderbook() captures &self lifetime in the returned Stream
         // due to Rust 2024 impl Trait capture rules, even though the stream is internally
         // self-contained. Leak the WsClient (small config struct) to get a 'static ref.
-        let ws_client: &'static _ = Box::leak(Box::new(WsClient::default()));
+        let ws_client: &'static _ = Box::leak(Box::new(
+            WsClient::new(&self.ws_url, WsConfig::default()).context("Invalid orderbook WS URL")?,
+        ));
         let stream = ws_client
             .subscribe_orderbook(asset_ids)
             .context("Failed to subscribe to orderbook WS")?;
@@ -67,6 +77,7 @@ impl OrderbookMirror {
         *self.subscribe_time.write().await = Some(std::time::Instant::now());
 
         let books = Arc::clone(&self.books);
+        let ws_tokens = Arc::clone(&self.ws_tokens);
         let notify = Arc::clone(&self.notify);
         let update_count = Arc::clone(&self.update_count);
 
@@ -108,12 +119,13 @@ impl OrderbookMirror {
 
                         {
                             let mut books = books.write().await;
+                            ws_tokens.lock().unwrap().insert(token_id.clone());
                             books.insert(token_id.clone(), orderbook);
                         }
 
                         debug!(
                             "WS orderbook update: {} ({} bids, {} asks)",
-                            &token_id[..token_id.len().min(20)],
+                            short(&token_id),
                             bid_count,
                             ask_count
                         );
@@ -138,6 +150,51 @@ impl OrderbookMirror {
         books.get(token_id).cloned()
     }
 
+    /// Insert a book directly into the mirror (used by backtest replay).
+    pub async fn set_orderbook(&self, token_id: &str, orderbook: OrderBook) {
+        self.books.write().await.insert(token_id.to_string(), orderbook);
+        self.notify.notify_waiters();
+    }
+
+    /// Insert a REST snapshot unless the WS has already delivered a book for this token, so a
+    /// poll never overwrites a fresher stream update. Returns whether the book was inserted.
+    pub async fn seed_orderbook(&self, token_id: &str, orderbook: OrderBook) -> bool {
+        {
+            let mut books = self.books.write().await;
+            if self.has_ws_book(token_id) {
+                return false;
+            }
+            books.insert(token_id.to_string(), orderbook);
+        }
+        self.notify.notify_waiters();
+        true
+    }
+
+    /// Whether the WS has delivered a book for this token since `subscribe`.
+    pub fn has_ws_book(&self, token_id: &str) -> bool {
+        self.ws_tokens.lock().unwrap().contains(token_id)
+    }
+
+    /// Keep a background task (e.g. a REST poller) alive until `unsubscribe_all`.
+    pub fn track_task(&self, handle: JoinHandle<()>) {
+        self.active_tasks.lock().unwrap().push(handle);
+    }
+
+    /// Copy of every book currently in the mirror (for recording snapshots).
+    pub async fn snapshot_all(&self) -> HashMap<String, OrderBook> {
+        self.books.read().await.clone()
+    }
+
+    /// Number of WS book updates received since the last `subscribe`.
+    pub fn update_count(&self) -> u64 {
+        self.update_count.load(Ordering::Relaxed)
+    }
+
+    /// Time elapsed since the last `subscribe`, if any.
+    pub async fn since_subscribe(&self) -> Option<Duration> {
+        self.subscribe_time.read().await.map(|t| t.elapsed())
+    }
+
     /// Block until the next WS book update arrives or timeout expires.
     /// Returns true if an update was received, false on timeout.
     pub async fn wait_for_update(&self, timeout: Duration) -> bool {
@@ -157,5 +214,6 @@ impl OrderbookMirror {
         }
         let mut books = self.books.write().await;
         books.clear();
+        self.ws_tokens.lock().unwrap().clear();
     }
 }
@@ -1,74 +1,438 @@
 //! Paper trade logger: prediction accuracy tracker for 5m rounds.
 //! Logs compact prediction records and resolution results.
 
+use crate::config::{PaperLogRotation, StrategyConfig};
+use crate::decision::{decide_winner, Decision};
 use crate::discovery::{format_5m_period_et, parse_price_to_beat_from_question};
+use crate::display::{usd, usd_diff};
 use crate::log_buffer::LogBuffer;
-use crate::rtds::LatestPriceCache;
+use crate::models::Trade;
+use crate::rtds::LatestPrice;
+use anyhow::{Context, Result};
 use chrono::Utc;
 use log::{info, warn};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
 use std::fmt::Write as FmtWrite;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use tokio::fs::OpenOptions;
 use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
 
 const PAPER_TRADE_FILE: &str = "paper_trade.md";
 const PREDICTIONS_CSV: &str = "predictions.csv";
+const PREDICTIONS_CSV_HEADER: &str = "date,period,symbol,condition_id,ptb,close_price,prediction,actual,correct,close_rtds_ts,system_read_ts,age_s,diff,diff_pct\n";
+/// Structured history: one JSON line per round, including rounds with no prediction.
+pub const PAPER_TRADES_JSONL: &str = "paper_trades.jsonl";
+
+/// Round status in the structured history: `predicted`, or the `SkipReason` code of the
+/// decision that would have skipped the sweep.
+pub const STATUS_PREDICTED: &str = "predicted";
+/// No RTDS close price at close time (stale feed).
+pub const STATUS_NO_CLOSE_PRICE: &str = "no_close_price";
+
+/// Why a round wasn't traded, as a stable code for `paper_trades.jsonl` (the log lines carry
+/// the details).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SkipReason {
+    /// No RTDS price for the symbol at close.
+    NoClosePrice,
+    /// The latest RTDS price was too far from close (`winner_price_max_skew_ms`).
+    RtdsStale,
+    /// Close price or price-to-beat failed the sanity check.
+    InvalidPrice,
+    /// Close equals price-to-beat.
+    Tied,
+    /// |close - ptb| under `sweep_min_margin_pct`.
+    BelowMargin,
+    /// Trading paused from the dashboard.
+    Paused,
+    /// Market is in `skip_condition_ids` / `skip_token_ids`.
+    Denylisted,
+    /// No CLOB credentials (monitor-only).
+    Unauthenticated,
+    /// Existing holdings already use the round's budget.
+    NoBudget,
+    /// Eligible depth under `min_sweepable_notional`.
+    ThinBook,
+    /// The latest price lost the winner's edge (`min_edge_at_fill_pct`) before the first FOK.
+    EdgeLost,
+    /// The winning token ID is not a decimal CTF position ID (unexpected API data).
+    InvalidToken,
+    /// The Chainlink RPC price disagreed with RTDS on the winner, or was unavailable
+    /// (`require_source_agreement_for_live`).
+    SourcesDisagree,
+    /// Too many network errors in a row on live orders; cooling down monitor-only
+    /// (`network_error_breaker_threshold`).
+    CircuitOpen,
+}
+
+impl SkipReason {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SkipReason::NoClosePrice => "no_close_price",
+            SkipReason::RtdsStale => "rtds_stale",
+            SkipReason::InvalidPrice => "invalid_price",
+            SkipReason::Tied => "tied",
+            SkipReason::BelowMargin => "below_margin",
+            SkipReason::Paused => "paused",
+            SkipReason::Denylisted => "denylisted",
+            SkipReason::Unauthenticated => "unauthenticated",
+            SkipReason::NoBudget => "no_budget",
+            SkipReason::ThinBook => "thin_book",
+            SkipReason::EdgeLost => "edge_lost",
+            SkipReason::InvalidToken => "invalid_token",
+            SkipReason::SourcesDisagree => "sources_disagree",
+            SkipReason::CircuitOpen => "circuit_open",
+        }
+    }
+}
+
+/// What the sweep did in one round (live or simulated).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SweepOutcome {
+    pub orders: u32,
+    pub shares: f64,
+    pub cost: f64,
+    /// Set when the sweep bailed out before sending orders.
+    pub skipped: Option<SkipReason>,
+    /// Orders were really sent (not simulated, monitor mode or warmup).
+    pub live: bool,
+}
+
+impl SweepOutcome {
+    pub fn skipped(reason: SkipReason) -> Self {
+        Self { skipped: Some(reason), ..Default::default() }
+    }
+
+    /// (orders, shares, cost)
+    pub fn totals(&self) -> (u32, f64, f64) {
+        (self.orders, self.shares, self.cost)
+    }
+}
+
+/// Market trades on the predicted side's token after close, to check whether stale asks were
+/// still there for the taking.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PostCloseTrades {
+    pub trades: u32,
+    pub shares: f64,
+    /// Size-weighted; 0 when nothing traded.
+    pub avg_price: f64,
+}
+
+impl PostCloseTrades {
+    pub fn from_trades(trades: &[Trade]) -> Self {
+        let shares: f64 = trades.iter().map(|t| t.size).sum();
+        let notional: f64 = trades.iter().map(|t| t.price * t.size).sum();
+        Self {
+            trades: trades.len() as u32,
+            shares,
+            avg_price: if shares > 0.0 { notional / shares } else { 0.0 },
+        }
+    }
+}
+
+/// One round in `paper_trades.jsonl`. Written at resolution (or at close if there was no close
+/// price); fields that don't apply to the status are None. Prices are decimal strings so
+/// `/replay` re-decides from the exact values; rows written as numbers still load.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PaperTradeRow {
+    pub symbol: String,
+    pub period_5: i64,
+    pub period: String,
+    pub condition_id: String,
+    pub status: String,
+    pub price_to_beat: Decimal,
+    pub close_price: Option<Decimal>,
+    pub prediction: Option<String>,
+    /// Resolved winner; None on resolution timeout.
+    pub actual: Option<String>,
+    pub correct: Option<bool>,
+    pub close_rtds_ts_ms: Option<i64>,
+    pub system_read_ts_ms: i64,
+    pub age_s: Option<i64>,
+    pub diff: Option<f64>,
+    pub diff_pct: Option<f64>,
+    pub resolved_at: Option<String>,
+    /// Sweep totals for the round (live or simulated); None when the sweep didn't run.
+    pub sweep_orders: Option<u32>,
+    pub sweep_shares: Option<f64>,
+    pub sweep_cost: Option<f64>,
+    /// Why the round wasn't traded; None when the sweep ran (even if nothing filled).
+    #[serde(default)]
+    pub skip_reason: Option<SkipReason>,
+    /// Market trades on the predicted side after close; None when they weren't fetched.
+    #[serde(default)]
+    pub post_close_trades: Option<u32>,
+    #[serde(default)]
+    pub post_close_shares: Option<f64>,
+    #[serde(default)]
+    pub post_close_avg_price: Option<f64>,
+}
+
+impl PaperTradeRow {
+    /// P&L of the round's sweep once resolved: the swept (predicted) side pays $1 per share if it
+    /// won. None when nothing was bought or the round didn't resolve.
+    pub fn sweep_pnl(&self) -> Option<f64> {
+        let (shares, cost) = (self.sweep_shares?, self.sweep_cost?);
+        if shares <= 0.0 {
+            return None;
+        }
+        let payout = if self.correct? { shares } else { 0.0 };
+        Some(payout - cost)
+    }
+}
+
+/// Sweep P&L over a set of rounds. Only swept, resolved rounds count.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PnlSummary {
+    pub rounds: u32,
+    pub wins: u32,
+    pub win_rate: f64,
+    pub cost: f64,
+    pub pnl: f64,
+    pub avg_cost: f64,
+}
+
+pub fn pnl_summary<'a>(rows: impl IntoIterator<Item = &'a PaperTradeRow>) -> PnlSummary {
+    let mut s = PnlSummary::default();
+    for row in rows {
+        let Some(pnl) = row.sweep_pnl() else {
+            continue;
+        };
+        s.rounds += 1;
+        s.wins += u32::from(row.correct == Some(true));
+        s.cost += row.sweep_cost.unwrap_or(0.0);
+        s.pnl += pnl;
+    }
+    if s.rounds > 0 {
+        s.win_rate = s.wins as f64 / s.rounds as f64;
+        s.avg_cost = s.cost / s.rounds as f64;
+    }
+    s
+}
+
+const EXPORT_CSV_HEADER: &str = "symbol,period_5,period,condition_id,status,price_to_beat,close_price,prediction,actual,correct,close_rtds_ts_ms,system_read_ts_ms,age_s,diff,diff_pct,resolved_at,sweep_orders,sweep_shares,sweep_cost,skip_reason";
+
+/// Read every round from `paper_trades.jsonl` (at `input`), preceded by its rotated archives
+/// (`paper_trades-{date}.jsonl`, oldest first). Unparsable lines are skipped with a warning.
+pub fn load_history(input: &Path) -> Result<Vec<PaperTradeRow>> {
+    let mut rows = Vec::new();
+    for path in rotated_archives(input).into_iter().chain([input.to_path_buf()]) {
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        for (i, line) in content.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<PaperTradeRow>(line) {
+                Ok(row) => rows.push(row),
+                Err(e) => warn!("{}:{}: skipping unparsable line: {}", path.display(), i + 1, e),
+            }
+        }
+    }
+    Ok(rows)
+}
+
+/// `{stem}-{date}[-n].{ext}` next to `path`, by modification time (oldest first).
+fn rotated_archives(path: &Path) -> Vec<PathBuf> {
+    let (Some(dir), Some(name)) = (path.parent(), path.file_name().and_then(|n| n.to_str())) else {
+        return Vec::new();
+    };
+    let (stem, ext) = split_name(name);
+    let prefix = format!("{}-", stem);
+    let dir = if dir.as_os_str().is_empty() { Path::new(".") } else { dir };
+    let Ok(entries) = std::fs::read_dir(dir) else { return Vec::new() };
+    let mut archives: Vec<(std::time::SystemTime, PathBuf)> = entries
+        .filter_map(|e| e.ok())
+        .filter(|e| {
+            let name = e.file_name();
+            let name = name.to_string_lossy();
+            name.starts_with(&prefix) && split_name(&name).1 == ext
+        })
+        .filter_map(|e| Some((e.metadata().ok()?.modified().ok()?, e.path())))
+        .collect();
+    archives.sort();
+    archives.into_iter().map(|(_, p)| p).collect()
+}
+
+/// ("paper_trades", ".jsonl") for "paper_trades.jsonl"; the extension keeps its dot.
+fn split_name(name: &str) -> (&str, &str) {
+    match name.rfind('.') {
+        Some(i) if i > 0 => name.split_at(i),
+        _ => (name, ""),
+    }
+}
+
+/// Where a log file due for rotation is moved: dated by its last write (UTC), with a counter
+/// when several size rollovers happen on one day.
+async fn archive_path(path: &Path, last_write: chrono::DateTime<Utc>) -> PathBuf {
+    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+    let (stem, ext) = split_name(name);
+    let date = last_write.format("%Y-%m-%d");
+    let mut candidate = path.with_file_name(format!("{}-{}{}", stem, date, ext));
+    let mut n = 1;
+    while tokio::fs::try_exists(&candidate).await.unwrap_or(false) {
+        candidate = path.with_file_name(format!("{}-{}-{}{}", stem, date, n, ext));
+        n += 1;
+    }
+    candidate
+}
+
+/// Flatten `paper_trades.jsonl` (at `input`) into a CSV with one row per round, skipped rounds
+/// included with their status. Unparsable lines are skipped with a warning. Returns rows written.
+pub fn export_csv(input: &Path, out: &Path) -> Result<usize> {
+    let rows = load_history(input)?;
+    let mut csv = String::new();
+    let _ = writeln!(csv, "{}", EXPORT_CSV_HEADER);
+    for row in &rows {
+        let _ = writeln!(csv, "{}", csv_line(row));
+    }
+    std::fs::write(out, csv).with_context(|| format!("Failed to write {}", out.display()))?;
+    Ok(rows.len())
+}
+
+fn csv_line(row: &PaperTradeRow) -> String {
+    fn opt<T: ToString>(v: &Option<T>) -> String {
+        v.as_ref().map(|v| v.to_string()).unwrap_or_default()
+    }
+    [
+        csv_field(&row.symbol),
+        row.period_5.to_string(),
+        csv_field(&row.period),
+        csv_field(&row.condition_id),
+        csv_field(&row.status),
+        row.price_to_beat.to_string(),
+        opt(&row.close_price),
+        csv_field(&opt(&row.prediction)),
+        csv_field(&opt(&row.actual)),
+        opt(&row.correct),
+        opt(&row.close_rtds_ts_ms),
+        row.system_read_ts_ms.to_string(),
+        opt(&row.age_s),
+        opt(&row.diff),
+        row.diff_pct.map(|d| format!("{:.3}", d)).unwrap_or_default(),
+        csv_field(&opt(&row.resolved_at)),
+        opt(&row.sweep_orders),
+        opt(&row.sweep_shares),
+        opt(&row.sweep_cost),
+        row.skip_reason.map(SkipReason::as_str).unwrap_or_default().to_string(),
+    ]
+    .join(",")
+}
+
+/// History rows and CSVs keep plain JSON numbers for prices.
+fn to_f64(d: Decimal) -> f64 {
+    d.to_f64().unwrap_or_default()
+}
+
+/// Quote a CSV field if it contains a separator, quote or newline.
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
 
 /// A single prediction for one symbol in one 5m period.
 pub struct PredictionRecord {
     pub symbol: String,
     pub period_5: i64,
     pub period_str: String,
-    pub price_to_beat: f64,
-    pub close_price: f64,
+    pub price_to_beat: Decimal,
+    pub close_price: Decimal,
     pub prediction: String,
     pub condition_id: String,
     pub close_rtds_ts_ms: i64,
     pub system_read_ts_ms: i64,
     pub age_s: i64,
-    pub diff: f64,
+    pub diff: Decimal,
     pub diff_pct: f64,
     pub raw_rtds_json: String,
+    /// `STATUS_PREDICTED`, or the skip reason's code when the sweep's decision skips the round.
+    pub status: &'static str,
+    /// From the same `decide_winner` call as the sweep's; None when it would sweep.
+    pub skip_reason: Option<SkipReason>,
 }
 
 /// Shared handle for paper trade logging across concurrent symbol loops.
 #[derive(Clone)]
 pub struct PaperTradeLogger {
-    latest_prices: LatestPriceCache,
     log_buffer: LogBuffer,
+    /// Directory the log files are written to (the working directory by default).
+    dir: PathBuf,
+    rotation: PaperLogRotation,
+    /// Roll a file over before an append would take it past this size. 0 = no limit.
+    max_bytes: u64,
+    /// Serializes appends with rollovers so no write lands in a file being renamed.
+    file_mutex: Arc<Mutex<()>>,
 }
 
 impl PaperTradeLogger {
-    pub fn new(latest_prices: LatestPriceCache, log_buffer: LogBuffer) -> Self {
+    pub fn new(log_buffer: LogBuffer) -> Self {
         Self {
-            latest_prices,
             log_buffer,
+            dir: PathBuf::from("."),
+            rotation: PaperLogRotation::default(),
+            max_bytes: 0,
+            file_mutex: Arc::new(Mutex::new(())),
         }
     }
 
-    /// Log a prediction after a 5m round closes.
+    /// Roll the log files over to dated archives per `paper_log_rotation` / `paper_log_max_bytes`.
+    pub fn with_rotation(mut self, rotation: PaperLogRotation, max_bytes: u64) -> Self {
+        self.rotation = rotation;
+        self.max_bytes = max_bytes;
+        self
+    }
+
+    #[cfg(test)]
+    pub fn in_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.dir = dir.into();
+        self
+    }
+
+    /// Log a prediction after a 5m round closes at `close_time`. `close_tick` is the symbol's
+    /// RTDS price at close, if any. `cfg` is the symbol's effective config; rounds the sweep
+    /// would skip are marked with the same `decide_winner` decision.
     /// Returns the prediction record if a close price was available.
+    #[allow(clippy::too_many_arguments)]
     pub async fn log(
         &self,
         symbol: &str,
         period_5: i64,
-        price_to_beat: f64,
+        price_to_beat: Decimal,
         condition_id: &str,
+        close_time: i64,
+        close_tick: Option<&LatestPrice>,
+        cfg: &StrategyConfig,
     ) -> Option<PredictionRecord> {
-        info!("Paper trade: {} period={} ptb=${}", symbol, period_5, price_to_beat);
+        info!("Paper trade: {} period={} ptb={}", symbol, period_5, usd(price_to_beat));
         let system_read_ts_ms = Utc::now().timestamp_millis();
         let period_str = format_5m_period_et(period_5);
 
-        // Get close price from RTDS WS
-        let rtds_result = {
-            let cache = self.latest_prices.read().await;
-            cache.get(symbol).cloned()
-        };
-
-        let (close_price, close_rtds_ts_ms, raw_json) = match rtds_result {
+        let (close_price, close_rtds_ts_ms, raw_json) = match close_tick.cloned() {
             Some((p, ts, raw)) => (p, ts, raw),
             None => {
-                let md = format!("## {} | {}\n\n- PTB: ${} | Close: unavailable\n---\n\n", symbol.to_uppercase(), period_str, price_to_beat);
+                let md = format!("## {} | {}\n\n- PTB: {} | Close: unavailable\n---\n\n", symbol.to_uppercase(), period_str, usd(price_to_beat));
                 self.append_file(PAPER_TRADE_FILE, &md).await;
+                self.append_history(&PaperTradeRow {
+                    symbol: symbol.to_string(),
+                    period_5,
+                    period: period_str.clone(),
+                    condition_id: condition_id.to_string(),
+                    status: STATUS_NO_CLOSE_PRICE.to_string(),
+                    price_to_beat,
+                    system_read_ts_ms,
+                    skip_reason: Some(SkipReason::NoClosePrice),
+                    ..Default::default()
+                })
+                .await;
                 self.log_buffer.push(symbol, "warn", format!("{} | no close price", period_str)).await;
                 return None;
             }
@@ -76,8 +440,16 @@ impl PaperTradeLogger {
 
         let age_s = (system_read_ts_ms - close_rtds_ts_ms) / 1000;
         let diff = close_price - price_to_beat;
-        let diff_pct = if price_to_beat > 0.0 { (diff / price_to_beat).abs() * 100.0 } else { 0.0 };
-        let prediction = if diff > 0.0 { "Up" } else { "Down" };
+        let diff_pct = if price_to_beat > Decimal::ZERO { to_f64((diff / price_to_beat).abs()) * 100.0 } else { 0.0 };
+        let decision = decide_winner(price_to_beat, close_price, close_rtds_ts_ms - close_time * 1000, cfg);
+        // Skipped rounds still get a prediction from the diff's sign, for accuracy tracking.
+        let prediction = match decision {
+            Decision::Sweep { side, .. } => side,
+            _ if diff > Decimal::ZERO => "Up",
+            _ => "Down",
+        };
+        let skip_reason = decision.skip_reason();
+        let status = skip_reason.map_or(STATUS_PREDICTED, SkipReason::as_str);
 
         let record = PredictionRecord {
             symbol: symbol.to_string(),
@@ -92,39 +464,55 @@ impl PaperTradeLogger {
             age_s,
             diff,
             diff_pct,
-            raw_rtds_json: raw_json.clone(),
+            raw_rtds_json: raw_json,
+            status,
+            skip_reason,
         };
 
         // Write compact markdown
         let mut md = String::new();
         let _ = writeln!(md, "## {} | {}\n", symbol.to_uppercase(), period_str);
-        let _ = writeln!(md, "- PTB: ${}", price_to_beat);
-        let _ = writeln!(md, "- Close: ${}", close_price);
+        let _ = writeln!(md, "- PTB: {}", usd(price_to_beat));
+        let _ = writeln!(md, "- Close: {}", usd(close_price));
         let _ = writeln!(md, "- Prediction: {}", prediction);
-        let _ = writeln!(md, "- Diff: {}${} ({}%)", if diff >= 0.0 { "+" } else { "-" }, diff.abs(), format!("{:.3}", diff_pct));
+        let _ = writeln!(md, "- Diff: {} ({:.3}%)", usd_diff(diff, price_to_beat), diff_pct);
         let _ = writeln!(md, "- Close RTDS ts: {}", close_rtds_ts_ms);
         let _ = writeln!(md, "- System read: {}", system_read_ts_ms);
         let _ = writeln!(md, "- Age: {}s", age_s);
-        let _ = writeln!(md, "- Raw RTDS: {}", raw_json);
+        let _ = writeln!(md, "- Raw RTDS: {}", record.raw_rtds_json);
 
         self.append_file(PAPER_TRADE_FILE, &md).await;
 
         let summary = format!(
-            "{} | {} ptb=${} close=${} diff={}${} ({}%)",
-            period_str, prediction, price_to_beat, close_price,
-            if diff >= 0.0 { "+" } else { "-" }, diff.abs(),
-            format!("{:.3}", diff_pct),
+            "{} | {} ptb={} close={} diff={} ({:.3}%)",
+            period_str, prediction, usd(price_to_beat), usd(close_price),
+            usd_diff(diff, price_to_beat), diff_pct,
         );
         self.log_buffer.push(symbol, "info", summary).await;
 
         Some(record)
     }
 
-    /// Log resolution result after polling completes.
-    pub async fn log_resolution(&self, record: &PredictionRecord, actual: Option<&str>, api_question: Option<&str>) {
+    /// Log resolution result after polling completes. `sweep` is the round's sweep outcome if
+    /// the sweep was attempted; its skip reason wins over the prediction's. `post_close` is the
+    /// market's trading on the predicted side after close, if it was fetched.
+    pub async fn log_resolution(
+        &self,
+        record: &PredictionRecord,
+        actual: Option<&str>,
+        api_question: Option<&str>,
+        sweep: Option<&SweepOutcome>,
+        post_close: Option<&PostCloseTrades>,
+    ) {
         let api_ptb_str = api_question
             .and_then(parse_price_to_beat_from_question)
-            .map(|p| format!(" | API PTB: ${}", p))
+            .map(|(p, _)| format!(" | API PTB: {}", usd(p)))
+            .unwrap_or_default();
+        let post_close_str = post_close
+            .map(|t| format!(
+                " | After close: {} trades, {:.2} shares @ avg {:.4}",
+                t.trades, t.shares, t.avg_price
+            ))
             .unwrap_or_default();
 
         let md = match actual {
@@ -133,25 +521,25 @@ impl PaperTradeLogger {
                 let resolved_at = Utc::now().to_rfc3339();
                 if correct {
                     format!(
-                        "- **{}** Resolution: {} \u{2705} | PTB: ${} | Close: ${} |{} Resolved at: {}\n---\n\n",
+                        "- **{}** Resolution: {} \u{2705} | PTB: {} | Close: {} |{} Resolved at: {}{}\n---\n\n",
                         record.symbol.to_uppercase(), winner,
-                        record.price_to_beat, record.close_price,
-                        api_ptb_str, resolved_at
+                        usd(record.price_to_beat), usd(record.close_price),
+                        api_ptb_str, resolved_at, post_close_str
                     )
                 } else {
                     format!(
-                        "- **{}** Resolution: {} \u{274C} (predicted {}) | PTB: ${} | Close: ${} |{} Resolved at: {}\n---\n\n",
+                        "- **{}** Resolution: {} \u{274C} (predicted {}) | PTB: {} | Close: {} |{} Resolved at: {}{}\n---\n\n",
                         record.symbol.to_uppercase(), winner, record.prediction,
-                        record.price_to_beat, record.close_price,
-                        api_ptb_str, resolved_at
+                        usd(record.price_to_beat), usd(record.close_price),
+                        api_ptb_str, resolved_at, post_close_str
                     )
                 }
             }
             None => {
                 format!(
-                    "- **{}** Resolution: TIMEOUT (predicted {}) | PTB: ${} | Close: ${}\n---\n\n",
+                    "- **{}** Resolution: TIMEOUT (predicted {}) | PTB: {} | Close: {}{}\n---\n\n",
                     record.symbol.to_uppercase(), record.prediction,
-                    record.price_to_beat, record.close_price
+                    usd(record.price_to_beat), usd(record.close_price), post_close_str
                 )
             }
         };
@@ -163,6 +551,33 @@ impl PaperTradeLogger {
         let actual_str = actual.unwrap_or("TIMEOUT");
         self.write_csv_row(record, actual_str, correct).await;
 
+        self.append_history(&PaperTradeRow {
+            symbol: record.symbol.clone(),
+            period_5: record.period_5,
+            period: record.period_str.clone(),
+            condition_id: record.condition_id.clone(),
+            status: record.status.to_string(),
+            price_to_beat: record.price_to_beat,
+            close_price: Some(record.close_price),
+            prediction: Some(record.prediction.clone()),
+            actual: actual.map(str::to_string),
+            correct: actual.map(|a| a == record.prediction),
+            close_rtds_ts_ms: Some(record.close_rtds_ts_ms),
+            system_read_ts_ms: record.system_read_ts_ms,
+            age_s: Some(record.age_s),
+            diff: Some(to_f64(record.diff)),
+            diff_pct: Some(record.diff_pct),
+            resolved_at: actual.map(|_| Utc::now().to_rfc3339()),
+            sweep_orders: sweep.map(|s| s.orders),
+            sweep_shares: sweep.map(|s| s.shares),
+            sweep_cost: sweep.map(|s| s.cost),
+            skip_reason: sweep.and_then(|s| s.skipped).or(record.skip_reason),
+            post_close_trades: post_close.map(|t| t.trades),
+            post_close_shares: post_close.map(|t| t.shares),
+            post_close_avg_price: post_close.map(|t| t.avg_price),
+        })
+        .await;
+
         let log_msg = match actual {
             Some(w) if w == record.prediction => format!("{} | {} CORRECT", record.period_str, record.symbol),
             Some(w) => format!("{} | {} WRONG (predicted {} actual {})", record.period_str, record.symbol, record.prediction, w),
@@ -173,42 +588,184 @@ impl PaperTradeLogger {
 
     /// Append a row to predictions.csv (creating with header if needed).
     async fn write_csv_row(&self, record: &PredictionRecord, actual: &str, correct: bool) {
-        let file_exists = tokio::fs::metadata(PREDICTIONS_CSV).await.is_ok();
-
         let mut content = String::new();
-        if !file_exists {
-            let _ = writeln!(content, "date,period,symbol,condition_id,ptb,close_price,prediction,actual,correct,close_rtds_ts,system_read_ts,age_s,diff,diff_pct");
-        }
         let date = Utc::now().format("%Y-%m-%d");
         let _ = writeln!(
             content,
-            "{},{},{},{},{},{},{},{},{},{},{},{},{},{}",
+            "{},{},{},{},{},{},{},{},{},{},{},{},{},{:.3}",
             date, record.period_5, record.symbol, record.condition_id,
             record.price_to_beat, record.close_price,
             record.prediction, actual, correct,
             record.close_rtds_ts_ms, record.system_read_ts_ms,
-            record.age_s, record.diff, format!("{:.3}", record.diff_pct),
+            record.age_s, record.diff, record.diff_pct,
         );
 
-        self.append_file(PREDICTIONS_CSV, &content).await;
+        self.append(PREDICTIONS_CSV, Some(PREDICTIONS_CSV_HEADER), &content).await;
+    }
+
+    /// The file's last write time when appending `incoming` bytes should first roll it over: it
+    /// was last written on an earlier UTC day (`daily`) or would pass `max_bytes`.
+    fn rotation_due(&self, meta: &std::fs::Metadata, incoming: usize) -> Option<chrono::DateTime<Utc>> {
+        let last_write: chrono::DateTime<Utc> = meta.modified().ok()?.into();
+        let new_day = self.rotation == PaperLogRotation::Daily && last_write.date_naive() != Utc::now().date_naive();
+        let too_big = self.max_bytes > 0 && meta.len() > 0 && meta.len() + incoming as u64 > self.max_bytes;
+        (new_day || too_big).then_some(last_write)
     }
 
-    /// Append content to a file.
-    async fn append_file(&self, path: &str, content: &str) {
+    /// Append one round to the structured history.
+    async fn append_history(&self, row: &PaperTradeRow) {
+        match serde_json::to_string(row) {
+            Ok(line) => self.append_file(PAPER_TRADES_JSONL, &format!("{}\n", line)).await,
+            Err(e) => warn!("Failed to serialize paper trade row: {}", e),
+        }
+    }
+
+    /// Append content to a file in the log directory.
+    async fn append_file(&self, name: &str, content: &str) {
+        self.append(name, None, content).await
+    }
+
+    /// Append content, first rolling the file over if it is due and writing `header` when the
+    /// file is new.
+    async fn append(&self, name: &str, header: Option<&str>, content: &str) {
+        let path = self.dir.join(name);
+        let _guard = self.file_mutex.lock().await;
+        let existing = tokio::fs::metadata(&path).await.ok();
+        let mut is_new = existing.is_none();
+        if let Some(meta) = existing {
+            if let Some(last_write) = self.rotation_due(&meta, content.len()) {
+                let archive = archive_path(&path, last_write).await;
+                match tokio::fs::rename(&path, &archive).await {
+                    Ok(()) => {
+                        info!("Rotated {} to {}", path.display(), archive.display());
+                        is_new = true;
+                    }
+                    Err(e) => warn!("Failed to rotate {}: {}", path.display(), e),
+                }
+            }
+        }
+        let content = match header {
+            Some(header) if is_new => format!("{}{}", header, content),
+            _ => content.to_string(),
+        };
         match OpenOptions::new()
             .create(true)
             .append(true)
-            .open(path)
+            .open(&path)
             .await
         {
             Ok(mut file) => {
-                if let Err(e) = file.write_all(content.as_bytes()).await {
-                    warn!("Failed to write {}: {}", path, e);
+                // tokio's File finishes writes in the background; flush so the line is on disk
+                // before the next reader (e.g. `/pnl`) looks.
+                if let Err(e) = file.write_all(content.as_bytes()).await.and(file.flush().await) {
+                    warn!("Failed to write {}: {}", path.display(), e);
                 }
             }
             Err(e) => {
-                warn!("Failed to open {}: {}", path, e);
+                warn!("Failed to open {}: {}", path.display(), e);
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pnl_counts_only_swept_resolved_rounds() {
+        let swept = |correct: Option<bool>, shares: f64, cost: f64| PaperTradeRow {
+            correct,
+            sweep_orders: Some(1),
+            sweep_shares: Some(shares),
+            sweep_cost: Some(cost),
+            ..Default::default()
+        };
+        let rows = [
+            swept(Some(true), 10.0, 9.5),
+            swept(Some(false), 5.0, 4.8),
+            swept(None, 5.0, 4.8),
+            swept(Some(true), 0.0, 0.0),
+            PaperTradeRow { correct: Some(true), ..Default::default() },
+        ];
+
+        let s = pnl_summary(&rows);
+        assert_eq!((s.rounds, s.wins), (2, 1));
+        assert!((s.pnl - (0.5 - 4.8)).abs() < 1e-9);
+        assert!((s.cost - 14.3).abs() < 1e-9);
+        assert!((s.avg_cost - 7.15).abs() < 1e-9);
+        assert_eq!(s.win_rate, 0.5);
+    }
+
+    #[test]
+    fn export_writes_one_row_per_round_including_skipped() {
+        let dir = std::env::temp_dir().join(format!("paper_export_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let input = dir.join("in.jsonl");
+        let out = dir.join("out.csv");
+        let predicted = PaperTradeRow {
+            symbol: "btc".into(),
+            period_5: 1_771_820_400,
+            period: "February 22, 11:20PM-11:25PM ET".into(),
+            status: STATUS_PREDICTED.into(),
+            price_to_beat: Decimal::ONE_HUNDRED,
+            close_price: Some(Decimal::from(101)),
+            prediction: Some("Up".into()),
+            actual: Some("Up".into()),
+            correct: Some(true),
+            diff_pct: Some(1.0),
+            ..Default::default()
+        };
+        let stale = PaperTradeRow {
+            symbol: "xrp".into(),
+            status: STATUS_NO_CLOSE_PRICE.into(),
+            price_to_beat: Decimal::new(13, 1),
+            skip_reason: Some(SkipReason::NoClosePrice),
+            ..Default::default()
+        };
+        let lines = [&predicted, &stale].map(|r| serde_json::to_string(r).unwrap()).join("\n");
+        std::fs::write(&input, format!("{}\nnot json\n", lines)).unwrap();
+
+        assert_eq!(export_csv(&input, &out).unwrap(), 2);
+        let csv = std::fs::read_to_string(&out).unwrap();
+        let rows: Vec<&str> = csv.lines().collect();
+        assert_eq!(rows[0], EXPORT_CSV_HEADER);
+        assert!(rows[1].starts_with("btc,1771820400,\"February 22, 11:20PM-11:25PM ET\",,predicted,100,101,Up,Up,true,"));
+        assert!(rows[2].starts_with("xrp,0,,,no_close_price,1.3,,,,,"));
+        assert!(rows[1].ends_with(",,,,"));
+        assert!(rows[2].ends_with(",no_close_price"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn rotation_archives_old_files_and_history_reads_them_back() {
+        let dir = std::env::temp_dir().join(format!("paper_rotate_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let row = |period_5| PaperTradeRow { symbol: "btc".into(), period_5, status: STATUS_PREDICTED.into(), ..Default::default() };
+        let line_len = serde_json::to_string(&row(1)).unwrap().len() as u64 + 1;
+        let logger = PaperTradeLogger::new(LogBuffer::new())
+            .in_dir(&dir)
+            .with_rotation(PaperLogRotation::Daily, line_len * 2);
+
+        for period_5 in 1..=3 {
+            logger.append_history(&row(period_5)).await;
+        }
+        let today = Utc::now().format("%Y-%m-%d");
+        let archive = dir.join(format!("paper_trades-{}.jsonl", today));
+        assert_eq!(std::fs::read_to_string(&archive).unwrap().lines().count(), 2, "rolled over at max_bytes");
+        let periods: Vec<i64> = load_history(&dir.join(PAPER_TRADES_JSONL)).unwrap().iter().map(|r| r.period_5).collect();
+        assert_eq!(periods, vec![1, 2, 3]);
+
+        // A file last written yesterday rolls over on today's first write, header and all.
+        logger.append(PREDICTIONS_CSV, Some(PREDICTIONS_CSV_HEADER), "old\n").await;
+        let yesterday = std::time::SystemTime::now() - std::time::Duration::from_secs(86_400);
+        std::fs::File::options().append(true).open(dir.join(PREDICTIONS_CSV)).unwrap().set_modified(yesterday).unwrap();
+        logger.append(PREDICTIONS_CSV, Some(PREDICTIONS_CSV_HEADER), "new\n").await;
+        let dated: chrono::DateTime<Utc> = yesterday.into();
+        let old = std::fs::read_to_string(dir.join(format!("predictions-{}.csv", dated.format("%Y-%m-%d")))).unwrap();
+        assert_eq!(old, format!("{}old\n", PREDICTIONS_CSV_HEADER));
+        let new = std::fs::read_to_string(dir.join(PREDICTIONS_CSV)).unwrap();
+        assert_eq!(new, format!("{}new\n", PREDICTIONS_CSV_HEADER));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
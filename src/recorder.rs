@@ -0,0 +1,171 @@
+//! Market data recorder for backtesting.
+//! Writes every RTDS chainlink message, periodic orderbook snapshots, round metadata and
+//! resolutions as JSON lines to `{record_dir}/{YYYY-MM-DD}.jsonl`. The backtest replays the
+//! same `RecordedEvent` format, so recorder and replayer can never drift apart.
+
+use crate::models::OrderBook;
+use anyhow::{Context, Result};
+use chrono::Utc;
+use log::warn;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::fs::OpenOptions;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+
+/// One recorded event. `ts_ms` is the local receive/record time in Unix milliseconds.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RecordedEvent {
+    /// Raw RTDS text message (replayed through `rtds::apply_chainlink_message`).
+    Rtds { ts_ms: i64, raw: String },
+    /// Orderbook snapshot for one token from the WS mirror.
+    Orderbook { ts_ms: i64, token_id: String, book: OrderBook },
+    /// Market discovered for a period, recorded at period close just before the sweep.
+    Round {
+        ts_ms: i64,
+        symbol: String,
+        period_5: i64,
+        condition_id: String,
+        up_token: String,
+        down_token: String,
+        price_to_beat: Decimal,
+    },
+    /// Resolution result for a round ("Up"/"Down"), None on timeout.
+    Resolution {
+        ts_ms: i64,
+        symbol: String,
+        period_5: i64,
+        winner: Option<String>,
+        question: Option<String>,
+    },
+}
+
+impl RecordedEvent {
+    pub fn rtds(raw: String) -> Self {
+        RecordedEvent::Rtds { ts_ms: Utc::now().timestamp_millis(), raw }
+    }
+
+    pub fn orderbook(token_id: &str, book: OrderBook) -> Self {
+        RecordedEvent::Orderbook {
+            ts_ms: Utc::now().timestamp_millis(),
+            token_id: token_id.to_string(),
+            book,
+        }
+    }
+}
+
+/// Shared handle that appends `RecordedEvent`s to daily JSONL files.
+#[derive(Clone)]
+pub struct Recorder {
+    dir: PathBuf,
+    file_mutex: Arc<Mutex<()>>,
+}
+
+impl Recorder {
+    pub fn new(dir: impl Into<PathBuf>) -> Result<Self> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir)
+            .context(format!("Failed to create record_dir {}", dir.display()))?;
+        Ok(Self {
+            dir,
+            file_mutex: Arc::new(Mutex::new(())),
+        })
+    }
+
+    /// Append one event. Errors are logged, never propagated (recording must not affect trading).
+    pub async fn record(&self, event: &RecordedEvent) {
+        let line = match serde_json::to_string(event) {
+            Ok(l) => l,
+            Err(e) => {
+                warn!("Recorder: failed to serialize event: {}", e);
+                return;
+            }
+        };
+        let path = self.dir.join(format!("{}.jsonl", Utc::now().format("%Y-%m-%d")));
+        let _guard = self.file_mutex.lock().await;
+        match OpenOptions::new().create(true).append(true).open(&path).await {
+            Ok(mut file) => {
+                if let Err(e) = file.write_all(format!("{}\n", line).as_bytes()).await {
+                    warn!("Recorder: failed to write {}: {}", path.display(), e);
+                }
+            }
+            Err(e) => warn!("Recorder: failed to open {}: {}", path.display(), e),
+        }
+    }
+}
+
+/// Winning token's orderbook at period close, written once the round resolves.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookSnapshot {
+    pub symbol: String,
+    pub period_5: i64,
+    pub condition_id: String,
+    pub winner: String,
+    pub token_id: String,
+    /// When the book was captured from the WS mirror (Unix ms).
+    pub captured_ms: i64,
+    /// None when the mirror had no book for the token at close.
+    pub book: Option<OrderBook>,
+}
+
+/// `{dir}/{YYYY-MM-DD}/{symbol}-{period_5}.json` (date of the period, UTC).
+fn book_snapshot_path(dir: &Path, symbol: &str, period_5: i64) -> PathBuf {
+    let date = chrono::DateTime::from_timestamp(period_5, 0)
+        .unwrap_or_default()
+        .format("%Y-%m-%d")
+        .to_string();
+    dir.join(date).join(format!("{}-{}.json", symbol, period_5))
+}
+
+/// Write `snapshot` to its `book_snapshot_path`.
+pub fn write_book_snapshot(dir: &Path, snapshot: &BookSnapshot) -> Result<PathBuf> {
+    let path = book_snapshot_path(dir, &snapshot.symbol, snapshot.period_5);
+    if let Some(day_dir) = path.parent() {
+        std::fs::create_dir_all(day_dir).context(format!("Failed to create {}", day_dir.display()))?;
+    }
+    let json = serde_json::to_string_pretty(snapshot)?;
+    std::fs::write(&path, json).context(format!("Failed to write {}", path.display()))?;
+    Ok(path)
+}
+
+/// Read the close book written for `symbol`'s round at `period_5`; None if none was saved.
+pub fn read_book_snapshot(dir: &Path, symbol: &str, period_5: i64) -> Result<Option<BookSnapshot>> {
+    let path = book_snapshot_path(dir, symbol, period_5);
+    let json = match std::fs::read_to_string(&path) {
+        Ok(json) => json,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e).context(format!("Failed to read {}", path.display())),
+    };
+    let snapshot = serde_json::from_str(&json).context(format!("Failed to parse {}", path.display()))?;
+    Ok(Some(snapshot))
+}
+
+/// Load all recorded events from every `*.jsonl` file in `dir`, in file-name (date) order.
+/// Unparsable lines are skipped with a warning.
+pub fn load_events(dir: &Path) -> Result<Vec<RecordedEvent>> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir(dir)
+        .context(format!("Failed to read backtest dir {}", dir.display()))?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.extension().map(|x| x == "jsonl").unwrap_or(false))
+        .collect();
+    files.sort();
+
+    let mut events = Vec::new();
+    for path in &files {
+        let content = std::fs::read_to_string(path)
+            .context(format!("Failed to read {}", path.display()))?;
+        for (i, line) in content.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<RecordedEvent>(line) {
+                Ok(ev) => events.push(ev),
+                Err(e) => warn!("Backtest: skipping {}:{}: {}", path.display(), i + 1, e),
+            }
+        }
+    }
+    Ok(events)
+}
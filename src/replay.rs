@@ -0,0 +1,193 @@
+//! `TradingApi` over recorded rounds for `--backtest`: each `RecordedEvent::Round` is served as
+//! the Gamma market and CLOB details that live discovery looked up, so replay goes through the
+//! same `discover_symbol` path. Nothing is ever signed or sent.
+
+use crate::api_traits::{MarketData, OrderPlacement};
+use crate::config::{OutcomeMapping, OutcomeSide};
+use crate::discovery::{build_5m_slug, MARKET_5M_DURATION_SECS};
+use crate::error::ApiError;
+use crate::models::{Market, MarketDetails, MarketToken, OrderBook, OrderResponse, Position, Trade};
+use crate::recorder::RecordedEvent;
+use anyhow::{anyhow, Result};
+use futures_util::future::BoxFuture;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+
+pub struct ReplayApi {
+    /// Gamma market per 5m slug.
+    markets_by_slug: HashMap<String, Market>,
+    /// CLOB market details per condition ID.
+    markets: HashMap<String, MarketDetails>,
+    /// Replayed outcomes are labelled "Up"/"Down", so the default mapping always applies.
+    outcomes: OutcomeMapping,
+}
+
+impl ReplayApi {
+    /// Markets of every recorded round. The recorded price-to-beat is served as the market's
+    /// structured one, and a resolution's question (when recorded) as its question.
+    pub fn from_events(events: &[RecordedEvent]) -> Self {
+        let questions: HashMap<(&str, i64), &str> = events
+            .iter()
+            .filter_map(|e| match e {
+                RecordedEvent::Resolution { symbol, period_5, question: Some(q), .. } => {
+                    Some(((symbol.as_str(), *period_5), q.as_str()))
+                }
+                _ => None,
+            })
+            .collect();
+        let mut markets_by_slug = HashMap::new();
+        let mut markets = HashMap::new();
+        for event in events {
+            let RecordedEvent::Round { symbol, period_5, condition_id, up_token, down_token, price_to_beat, .. } = event
+            else {
+                continue;
+            };
+            let question = questions.get(&(symbol.as_str(), *period_5)).copied().unwrap_or_default().to_string();
+            let end_date_iso = chrono::DateTime::from_timestamp(period_5 + MARKET_5M_DURATION_SECS, 0)
+                .unwrap_or_default()
+                .to_rfc3339();
+            let slug = build_5m_slug(symbol, *period_5);
+            markets_by_slug.insert(
+                slug.clone(),
+                Market {
+                    condition_id: condition_id.clone(),
+                    market_id: None,
+                    question: question.clone(),
+                    slug,
+                    end_date_iso: Some(end_date_iso.clone()),
+                    active: true,
+                    closed: false,
+                    price_to_beat: Some(*price_to_beat),
+                },
+            );
+            let token = |side: OutcomeSide, token_id: &str| MarketToken {
+                outcome: side.label().to_string(),
+                token_id: token_id.to_string(),
+                winner: false,
+            };
+            markets.insert(
+                condition_id.clone(),
+                MarketDetails {
+                    condition_id: condition_id.clone(),
+                    question,
+                    tokens: vec![token(OutcomeSide::Up, up_token), token(OutcomeSide::Down, down_token)],
+                    active: true,
+                    closed: false,
+                    end_date_iso,
+                    neg_risk: false,
+                },
+            );
+        }
+        Self { markets_by_slug, markets, outcomes: OutcomeMapping::default() }
+    }
+}
+
+impl MarketData for ReplayApi {
+    fn get_market_by_slug<'a>(&'a self, slug: &'a str) -> BoxFuture<'a, Result<Market>> {
+        let market = self.markets_by_slug.get(slug).cloned().ok_or_else(|| anyhow!("no recorded market {}", slug));
+        Box::pin(async move { market })
+    }
+
+    fn get_market<'a>(&'a self, condition_id: &'a str) -> BoxFuture<'a, Result<MarketDetails>> {
+        let market = self.markets.get(condition_id).cloned().ok_or_else(|| anyhow!("no recorded market {}", condition_id));
+        Box::pin(async move { market })
+    }
+
+    fn discover_active_updown_markets<'a>(&'a self, _duration: &'a str) -> BoxFuture<'a, Result<Vec<String>>> {
+        Box::pin(async { Err(anyhow!("symbol discovery is not recorded")) })
+    }
+
+    fn get_chainlink_price_rpc<'a>(&'a self, _aggregator: &'a str, _at_ts: i64) -> BoxFuture<'a, Result<(Decimal, i64)>> {
+        Box::pin(async { Err(anyhow!("no Chainlink RPC in backtest")) })
+    }
+
+    fn outcome_mapping(&self) -> &OutcomeMapping {
+        &self.outcomes
+    }
+
+    fn get_positions<'a>(&'a self, _wallet: &'a str, _redeemable_only: bool) -> BoxFuture<'a, Result<Vec<Position>>> {
+        Box::pin(async { Ok(Vec::new()) })
+    }
+
+    fn get_orderbooks<'a>(&'a self, _token_ids: &'a [&'a str]) -> BoxFuture<'a, Result<HashMap<String, OrderBook>>> {
+        Box::pin(async { Ok(HashMap::new()) })
+    }
+
+    fn get_trades<'a>(&'a self, _condition_id: &'a str, _token_id: &'a str, _after_ts: i64) -> BoxFuture<'a, Result<Vec<Trade>>> {
+        Box::pin(async { Ok(Vec::new()) })
+    }
+}
+
+impl OrderPlacement for ReplayApi {
+    fn has_private_key(&self) -> bool {
+        false
+    }
+
+    fn is_authenticated(&self) -> bool {
+        false
+    }
+
+    fn wallet_address(&self) -> Option<String> {
+        None
+    }
+
+    fn warm_order_cache<'a>(&'a self, _token_id: &'a str) -> BoxFuture<'a, Result<Decimal>> {
+        // Tick sizes aren't recorded; 0.01 is the common one.
+        Box::pin(async { Ok(Decimal::new(1, 2)) })
+    }
+
+    fn presign_fok_buy<'a>(&'a self, _token_id: &'a str, _size: &'a str, _price: &'a str) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async { Err(anyhow!("backtest never signs orders")) })
+    }
+
+    fn clear_presigned(&self) {}
+
+    fn place_fok_buy<'a>(
+        &'a self,
+        _token_id: &'a str,
+        _size: &'a str,
+        _price: &'a str,
+    ) -> BoxFuture<'a, std::result::Result<Option<OrderResponse>, ApiError>> {
+        Box::pin(async { Err(ApiError::Rejected("backtest never places orders".to_string())) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::discovery::MarketDiscovery;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn recorded_rounds_are_discoverable() {
+        let period_5 = 1_767_726_000;
+        let events = vec![
+            RecordedEvent::Round {
+                ts_ms: 0,
+                symbol: "btc".to_string(),
+                period_5,
+                condition_id: "0xc".to_string(),
+                up_token: "111".to_string(),
+                down_token: "222".to_string(),
+                price_to_beat: Decimal::new(9750025, 2),
+            },
+            RecordedEvent::Resolution {
+                ts_ms: 0,
+                symbol: "btc".to_string(),
+                period_5,
+                winner: Some("Up".to_string()),
+                question: Some("Bitcoin Up or Down".to_string()),
+            },
+        ];
+        let discovery = MarketDiscovery::new(Arc::new(ReplayApi::from_events(&events)));
+
+        let market = discovery.get_5m_market("btc", period_5).await.unwrap().expect("recorded market");
+        assert_eq!(market.condition_id, "0xc");
+        assert_eq!(market.price_to_beat, Some(Decimal::new(9750025, 2)));
+        assert_eq!(market.question, "Bitcoin Up or Down");
+        let tokens = discovery.get_market_tokens("0xc").await.unwrap().expect("binary market");
+        assert_eq!((tokens.up_token.as_str(), tokens.down_token.as_str()), ("111", "222"));
+        assert_eq!(tokens.end_time, Some(period_5 + MARKET_5M_DURATION_SECS));
+        assert!(discovery.get_5m_market("btc", period_5 + 300).await.unwrap().is_none());
+    }
+}
@@ -1,30 +1,93 @@
-//! Polymarket RTDS (Real-Time Data Socket) — Chainlink crypto/USD prices (btc, eth, sol, xrp).
+//! Polymarket RTDS (Real-Time Data Socket) — Chainlink crypto/USD prices (btc, eth, sol, xrp)
+//! and CLOB last trade prints for the traded tokens.
 //! Per docs: https://docs.polymarket.com/developers/RTDS/RTDS-crypto-prices
 //! Topic: crypto_prices_chainlink, subscribe with type: "*" and filters: "" for all symbols.
-//! Price-to-beat: use the message whose feed_ts is at (or within 2s of) the period start.
+//! Topic: clob_market, type: "last_trade_price", filters: JSON array of token ids.
+//! Price-to-beat: use the message whose feed_ts is in the capture window after the period start
+//! (2s by default, configurable per symbol), else the nearest message within a wider tolerance.
 
+use crate::config::{PolymarketConfig, StrategyConfig};
 use crate::discovery::period_start_et_unix_for_timestamp;
+use crate::display::usd;
+use crate::metrics::{SharedMetrics, TradePrint};
+use crate::recorder::{RecordedEvent, Recorder};
 use anyhow::{Context, Result};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use std::str::FromStr;
+use futures_util::future::BoxFuture;
 use futures_util::{SinkExt, StreamExt};
 use log::{debug, info, warn};
-use serde::Deserialize;
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
-use tokio::sync::RwLock;
-use tokio::time::{interval, Duration};
+use tokio::sync::{mpsc, RwLock};
+use tokio::time::{interval, Duration, Instant};
 use tokio_tungstenite::{connect_async, tungstenite::Message};
 
-const PING_INTERVAL_SECS: u64 = 5;
-/// Only use a price as price-to-beat when feed_ts is in [period_start, period_start + 2).
-const FEED_TS_CAPTURE_WINDOW_SECS: i64 = 2;
+pub(crate) const PING_INTERVAL_SECS: u64 = 5;
+const CHAINLINK_TOPIC: &str = "crypto_prices_chainlink";
+const CLOB_MARKET_TOPIC: &str = "clob_market";
+const LAST_TRADE_PRICE_TYPE: &str = "last_trade_price";
+
+/// RTDS socket settings.
+#[derive(Debug, Clone)]
+pub struct RtdsSocketConfig {
+    pub ws_url: String,
+    /// Max time without any inbound frame (message, ping or pong) before forcing a reconnect.
+    pub idle_timeout: Duration,
+}
+
+impl RtdsSocketConfig {
+    pub fn from_config(cfg: &PolymarketConfig) -> Self {
+        Self {
+            ws_url: cfg.rtds_ws_url.clone(),
+            idle_timeout: Duration::from_secs(cfg.rtds_idle_timeout_secs),
+        }
+    }
+}
+
+/// Price-to-beat capture rules. A message with feed_ts in [period_start, period_start + window)
+/// is used as-is; if none lands there, the nearest message within ±tolerance of period_start
+/// (the last one before the boundary or the first one after the window) is accepted.
+#[derive(Debug, Clone)]
+pub struct CaptureWindow {
+    default_window_secs: i64,
+    window_secs: HashMap<String, i64>,
+    tolerance_secs: i64,
+}
+
+impl CaptureWindow {
+    pub fn from_config(cfg: &StrategyConfig) -> Self {
+        Self {
+            default_window_secs: cfg.ptb_capture_window_secs as i64,
+            window_secs: cfg
+                .ptb_capture_window_overrides
+                .iter()
+                .map(|(s, w)| (s.to_lowercase(), *w as i64))
+                .collect(),
+            tolerance_secs: cfg.ptb_capture_tolerance_secs as i64,
+        }
+    }
+
+    pub fn window_secs(&self, symbol: &str) -> i64 {
+        self.window_secs.get(symbol).copied().unwrap_or(self.default_window_secs)
+    }
+
+    /// Seconds after period start by which RTDS has either captured a price-to-beat or missed it.
+    pub fn deadline_secs(&self, symbol: &str) -> i64 {
+        self.window_secs(symbol).max(self.tolerance_secs)
+    }
+}
 
 #[derive(Debug, Deserialize)]
 struct ChainlinkPayload {
     symbol: String,
     #[serde(deserialize_with = "deser_ts")]
     timestamp: i64,
-    #[serde(deserialize_with = "deser_f64")]
-    value: f64,
+    #[serde(deserialize_with = "deser_decimal")]
+    value: Decimal,
 }
 
 fn deser_ts<'de, D>(d: D) -> Result<i64, D::Error>
@@ -53,6 +116,23 @@ where
     }
 }
 
+/// Price as an exact decimal. JSON numbers go through their shortest text form, so `67123.45`
+/// stays `67123.45` instead of the nearest binary float.
+fn deser_decimal<'de, D>(d: D) -> Result<Decimal, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    use serde::de::Error;
+    let text = match serde_json::Value::deserialize(d)? {
+        serde_json::Value::Number(n) => n.to_string(),
+        serde_json::Value::String(s) => s,
+        _ => return Err(D::Error::custom("value must be number or string")),
+    };
+    Decimal::from_str(&text)
+        .or_else(|_| Decimal::from_scientific(&text))
+        .map_err(D::Error::custom)
+}
+
 #[derive(Debug, Deserialize)]
 struct ChainlinkMessage {
     topic: Option<String>,
@@ -60,10 +140,16 @@ struct ChainlinkMessage {
 }
 
 /// Map symbol (e.g. "btc") -> period_start -> price-to-beat.
-pub type PriceCacheMulti = Arc<RwLock<HashMap<String, HashMap<i64, f64>>>>;
+pub type PriceCacheMulti = Arc<RwLock<HashMap<String, HashMap<i64, Decimal>>>>;
 
-/// Latest price per symbol: symbol -> (latest_price_usd, timestamp_ms, raw_json).
-pub type LatestPriceCache = Arc<RwLock<HashMap<String, (f64, i64, String)>>>;
+/// One RTDS price: (price_usd, timestamp_ms, raw_json).
+pub type LatestPrice = (Decimal, i64, String);
+
+/// Latest price per symbol.
+pub type LatestPriceCache = Arc<RwLock<HashMap<String, LatestPrice>>>;
+
+/// Symbols being traded, as configured or last auto-discovered.
+pub type SharedSymbols = Arc<std::sync::RwLock<Vec<String>>>;
 
 /// Normalize payload symbol "btc/usd" -> "btc". Returns None if not a known format.
 fn payload_symbol_to_key(s: &str) -> Option<String> {
@@ -75,68 +161,287 @@ fn payload_symbol_to_key(s: &str) -> Option<String> {
     }
 }
 
-/// Connect to Polymarket RTDS and subscribe to crypto_prices_chainlink for all symbols.
-/// Per docs: type "*" with empty filters subscribes to all available symbols on one connection.
-pub async fn run_rtds_chainlink_all(
-    ws_url: &str,
-    symbols: &[String],
-    price_cache_5: PriceCacheMulti,
-    latest_prices: LatestPriceCache,
+/// Apply one raw RTDS text message to the price caches. Shared by the live socket and backtest replay.
+/// Returns the symbol if the message was a chainlink price for one of the tracked symbols.
+pub async fn apply_chainlink_message(
+    text: &str,
+    symbol_set: &HashSet<String>,
+    capture: &CaptureWindow,
+    price_cache_5: &PriceCacheMulti,
+    latest_prices: &LatestPriceCache,
+) -> Option<String> {
+    let m = match serde_json::from_str::<ChainlinkMessage>(text) {
+        Ok(m) => m,
+        Err(_) => return None,
+    };
+    if m.topic.as_deref() != Some(CHAINLINK_TOPIC) {
+        return None;
+    }
+    let p = m.payload?;
+    let key = match payload_symbol_to_key(&p.symbol) {
+        Some(k) if symbol_set.contains(&k) => k,
+        _ => return None,
+    };
+    // Always update latest price cache (for post-close sweep)
+    let prev = latest_prices.write().await.insert(key.clone(), (p.value, p.timestamp, text.to_string()));
+
+    let ts_sec = p.timestamp / 1000;
+    let period_5 = period_start_et_unix_for_timestamp(ts_sec, 5);
+    let period_ms = period_5 * 1000;
+    let offset_ms = p.timestamp - period_ms;
+    let candidate = if ts_sec < period_5 + capture.window_secs(&key) {
+        Some((p.value, offset_ms, true))
+    } else {
+        // Past the window: nearest of the last pre-boundary message and this one, within tolerance
+        let before = prev
+            .filter(|(_, prev_ts, _)| *prev_ts < period_ms)
+            .map(|(v, prev_ts, _)| (v, prev_ts - period_ms));
+        [Some((p.value, offset_ms)), before]
+            .into_iter()
+            .flatten()
+            .filter(|(_, off)| off.abs() <= capture.tolerance_secs * 1000)
+            .min_by_key(|(_, off)| off.abs())
+            .map(|(v, off)| (v, off, false))
+    };
+    if let Some((value, offset, exact)) = candidate {
+        let mut cache = price_cache_5.write().await;
+        let per_symbol = cache.entry(key.clone()).or_default();
+        if let Entry::Vacant(e) = per_symbol.entry(period_5) {
+            e.insert(value);
+            info!(
+                "PTB captured {}: {} (period {}, feed_ts offset {:+}ms{})",
+                key, usd(value), period_5, offset,
+                if exact { "" } else { ", nearest within tolerance" }
+            );
+        }
+    }
+    Some(key)
+}
+
+/// One entry of an RTDS subscribe/unsubscribe frame.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RtdsSubscription {
+    pub topic: String,
+    #[serde(rename = "type")]
+    pub msg_type: String,
+    pub filters: String,
+}
+
+impl RtdsSubscription {
+    /// Chainlink prices for all symbols.
+    pub fn chainlink_prices() -> Self {
+        Self {
+            topic: CHAINLINK_TOPIC.to_string(),
+            msg_type: "*".to_string(),
+            filters: String::new(),
+        }
+    }
+
+    /// Last trade prints for the given CLOB token ids.
+    pub fn last_trade_prices(token_ids: &[String]) -> Self {
+        Self {
+            topic: CLOB_MARKET_TOPIC.to_string(),
+            msg_type: LAST_TRADE_PRICE_TYPE.to_string(),
+            filters: serde_json::to_string(token_ids).unwrap_or_default(),
+        }
+    }
+}
+
+/// Build a `{"action": ..., "subscriptions": [...]}` frame.
+pub fn subscription_frame(action: &str, subs: &[RtdsSubscription]) -> String {
+    serde_json::json!({ "action": action, "subscriptions": subs }).to_string()
+}
+
+/// Runtime subscription changes sent to the live socket.
+#[derive(Debug, Clone)]
+pub enum RtdsCommand {
+    Subscribe(Vec<RtdsSubscription>),
+    Unsubscribe(Vec<RtdsSubscription>),
+}
+
+/// Routing fields shared by every RTDS message.
+#[derive(Debug, Deserialize)]
+pub struct RtdsEnvelope {
+    pub topic: Option<String>,
+    #[serde(rename = "type")]
+    pub msg_type: Option<String>,
+    pub payload: Option<serde_json::Value>,
+}
+
+/// Consumer of one RTDS topic. The socket subscribes to every handler's `subscriptions()` on
+/// (re)connect and routes each message to the handler whose `topic()` matches.
+pub trait RtdsHandler: Send + Sync {
+    fn topic(&self) -> &str;
+    fn subscriptions(&self) -> Vec<RtdsSubscription>;
+    fn handle<'a>(&'a self, raw: &'a str, msg: &'a RtdsEnvelope) -> BoxFuture<'a, ()>;
+}
+
+/// Chainlink price handler: price-to-beat capture, latest prices, recording and per-symbol counts.
+pub struct ChainlinkHandler {
+    /// Symbols traded right now; auto-discovery can change them while the feed runs.
+    pub symbols: SharedSymbols,
+    pub capture: CaptureWindow,
+    pub price_cache_5: PriceCacheMulti,
+    pub latest_prices: LatestPriceCache,
+    pub recorder: Option<Recorder>,
+    pub metrics: SharedMetrics,
+}
+
+impl RtdsHandler for ChainlinkHandler {
+    fn topic(&self) -> &str {
+        CHAINLINK_TOPIC
+    }
+
+    fn subscriptions(&self) -> Vec<RtdsSubscription> {
+        vec![RtdsSubscription::chainlink_prices()]
+    }
+
+    fn handle<'a>(&'a self, raw: &'a str, _msg: &'a RtdsEnvelope) -> BoxFuture<'a, ()> {
+        Box::pin(async move {
+            let symbol_set: HashSet<String> = self.symbols.read().unwrap().iter().map(|s| s.to_lowercase()).collect();
+            let applied = apply_chainlink_message(
+                raw, &symbol_set, &self.capture, &self.price_cache_5, &self.latest_prices,
+            ).await;
+            if let Some(symbol) = applied {
+                self.metrics.rtds.record_symbol(&symbol);
+                if let Some((price, _, _)) = self.latest_prices.read().await.get(&symbol) {
+                    self.metrics.set_price(&symbol, price.to_f64().unwrap_or_default());
+                }
+                if let Some(rec) = &self.recorder {
+                    rec.record(&RecordedEvent::rtds(raw.to_string())).await;
+                }
+            }
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct LastTradePayload {
+    asset_id: String,
+    #[serde(default)]
+    market: String,
+    #[serde(deserialize_with = "deser_f64")]
+    price: f64,
+    #[serde(deserialize_with = "deser_f64")]
+    size: f64,
+    #[serde(default)]
+    side: String,
+}
+
+/// Last trade prints for the tokens currently being traded, kept in `Metrics` for the dashboard.
+/// `set_tokens` swaps the subscription on the live socket.
+pub struct LastTradeHandler {
+    tokens: std::sync::Mutex<Vec<String>>,
+    commands: mpsc::UnboundedSender<RtdsCommand>,
+    metrics: SharedMetrics,
+}
+
+impl LastTradeHandler {
+    pub fn new(commands: mpsc::UnboundedSender<RtdsCommand>, metrics: SharedMetrics) -> Self {
+        Self {
+            tokens: std::sync::Mutex::new(Vec::new()),
+            commands,
+            metrics,
+        }
+    }
+
+    /// Replace the tracked token ids, unsubscribing the previous set.
+    pub fn set_tokens(&self, token_ids: Vec<String>) {
+        let prev = std::mem::replace(&mut *self.tokens.lock().unwrap(), token_ids.clone());
+        if prev == token_ids {
+            return;
+        }
+        if !prev.is_empty() {
+            let _ = self.commands.send(RtdsCommand::Unsubscribe(vec![RtdsSubscription::last_trade_prices(&prev)]));
+        }
+        if !token_ids.is_empty() {
+            let _ = self.commands.send(RtdsCommand::Subscribe(vec![RtdsSubscription::last_trade_prices(&token_ids)]));
+        }
+    }
+}
+
+impl RtdsHandler for LastTradeHandler {
+    fn topic(&self) -> &str {
+        CLOB_MARKET_TOPIC
+    }
+
+    fn subscriptions(&self) -> Vec<RtdsSubscription> {
+        let tokens = self.tokens.lock().unwrap();
+        if tokens.is_empty() {
+            Vec::new()
+        } else {
+            vec![RtdsSubscription::last_trade_prices(&tokens)]
+        }
+    }
+
+    fn handle<'a>(&'a self, _raw: &'a str, msg: &'a RtdsEnvelope) -> BoxFuture<'a, ()> {
+        Box::pin(async move {
+            if msg.msg_type.as_deref() != Some(LAST_TRADE_PRICE_TYPE) {
+                return;
+            }
+            let payload = match msg.payload.clone().map(serde_json::from_value::<LastTradePayload>) {
+                Some(Ok(p)) => p,
+                Some(Err(e)) => {
+                    debug!("RTDS last_trade_price parse error: {}", e);
+                    return;
+                }
+                None => return,
+            };
+            self.metrics.record_trade(TradePrint {
+                ts_ms: chrono::Utc::now().timestamp_millis(),
+                asset_id: payload.asset_id,
+                market: payload.market,
+                price: payload.price,
+                size: payload.size,
+                side: payload.side,
+            });
+        })
+    }
+}
+
+/// Connect to Polymarket RTDS, subscribe every handler's topics and route messages to them.
+/// `commands` carries runtime subscription changes; commands queued while disconnected are
+/// dropped since the (re)connect subscribe already reflects each handler's current state.
+/// Returns an error when the socket goes silent for `idle_timeout` (half-open connection), so the
+/// caller reconnects.
+pub async fn run_rtds(
+    socket: &RtdsSocketConfig,
+    handlers: &[Arc<dyn RtdsHandler>],
+    commands: &mut mpsc::UnboundedReceiver<RtdsCommand>,
+    metrics: &SharedMetrics,
 ) -> Result<()> {
-    let url = ws_url.trim_end_matches('/');
-    let symbol_set: std::collections::HashSet<String> =
-        symbols.iter().map(|s| s.to_lowercase()).collect();
-    debug!("RTDS WS connecting: {} (symbols: {:?})", url, symbols);
+    let url = socket.ws_url.trim_end_matches('/');
+    debug!("RTDS WS connecting: {}", url);
 
     let (mut ws_stream, _) = connect_async(url).await.context("RTDS WS connect failed")?;
 
-    // Per docs: subscribe to all chainlink symbols with type: "*" and filters: ""
-    let sub = serde_json::json!({
-        "action": "subscribe",
-        "subscriptions": [{
-            "topic": "crypto_prices_chainlink",
-            "type": "*",
-            "filters": ""
-        }]
-    });
+    while commands.try_recv().is_ok() {}
+    let subs: Vec<RtdsSubscription> = handlers.iter().flat_map(|h| h.subscriptions()).collect();
     ws_stream
-        .send(Message::Text(sub.to_string()))
+        .send(Message::Text(subscription_frame("subscribe", &subs)))
         .await
         .context("RTDS WS subscribe failed")?;
-    debug!("RTDS WS subscribed to crypto_prices_chainlink (all symbols)");
+    debug!("RTDS WS subscribed: {:?}", subs.iter().map(|s| &s.topic).collect::<Vec<_>>());
 
     let mut ping = interval(Duration::from_secs(PING_INTERVAL_SECS));
     ping.tick().await;
+    let mut last_frame = Instant::now();
 
     loop {
         tokio::select! {
             Some(msg) = ws_stream.next() => {
                 let msg = msg.context("RTDS WS stream error")?;
+                last_frame = Instant::now();
                 match msg {
                     Message::Text(text) => {
-                        if let Ok(m) = serde_json::from_str::<ChainlinkMessage>(&text) {
-                            if m.topic.as_deref() == Some("crypto_prices_chainlink") {
-                                if let Some(p) = m.payload {
-                                    let key = match payload_symbol_to_key(&p.symbol) {
-                                        Some(k) if symbol_set.contains(&k) => k,
-                                        _ => continue,
-                                    };
-                                    // Always update latest price cache (for post-close sweep)
-                                    latest_prices.write().await.insert(key.clone(), (p.value, p.timestamp, text.clone()));
-
-                                    let ts_sec = p.timestamp / 1000;
-                                    let period_5 = period_start_et_unix_for_timestamp(ts_sec, 5);
-                                    let in_capture_5 = ts_sec >= period_5 && ts_sec < period_5 + FEED_TS_CAPTURE_WINDOW_SECS;
-                                    if in_capture_5 {
-                                        let mut cache = price_cache_5.write().await;
-                                        let per_symbol = cache.entry(key.clone()).or_default();
-                                        if !per_symbol.contains_key(&period_5) {
-                                            per_symbol.insert(period_5, p.value);
-                                            info!("PTB captured {}: ${} (period {})", key, p.value, period_5);
-                                        }
-                                    }
-                                }
-                            }
+                        metrics.rtds.record_message();
+                        let envelope = match serde_json::from_str::<RtdsEnvelope>(&text) {
+                            Ok(e) => e,
+                            Err(_) => continue,
+                        };
+                        let topic = envelope.topic.as_deref().unwrap_or_default();
+                        if let Some(handler) = handlers.iter().find(|h| h.topic() == topic) {
+                            handler.handle(&text, &envelope).await;
                         }
                     }
                     Message::Ping(data) => {
@@ -146,7 +451,19 @@ pub async fn run_rtds_chainlink_all(
                     _ => {}
                 }
             }
+            Some(cmd) = commands.recv() => {
+                let frame = match &cmd {
+                    RtdsCommand::Subscribe(subs) => subscription_frame("subscribe", subs),
+                    RtdsCommand::Unsubscribe(subs) => subscription_frame("unsubscribe", subs),
+                };
+                ws_stream.send(Message::Text(frame)).await.context("RTDS WS subscription update failed")?;
+                debug!("RTDS WS {:?}", cmd);
+            }
             _ = ping.tick() => {
+                let idle = last_frame.elapsed();
+                if idle > socket.idle_timeout {
+                    anyhow::bail!("RTDS WS silent for {:.1}s (half-open connection?)", idle.as_secs_f64());
+                }
                 if ws_stream.send(Message::Ping(vec![])).await.is_err() {
                     break;
                 }
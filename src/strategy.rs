@@ -1,26 +1,265 @@
 //! 5m post-close sweep bot: BTC, ETH, SOL, XRP. Monitor live prices during round,
 //! then sweep stale limit orders after market closes using FOK orders.
 
-use crate::api::PolymarketApi;
+use crate::api_traits::TradingApi;
 use crate::chainlink::run_chainlink_multi_poller;
-use crate::config::Config;
-use crate::discovery::{current_5m_period_start, parse_price_to_beat_from_question, MarketDiscovery, MARKET_5M_DURATION_SECS};
+use crate::config::{Config, StrategyConfig};
+use crate::control::SharedControl;
+use crate::decision::{crossed_book, decide_winner, edge_holds, plausible, Decision};
+use crate::display::{dollars, short, token_price, usd, usd_diff};
+use crate::discovery::{
+    current_5m_period_start, parse_price_to_beat_from_question, MarketDiscovery, ThresholdDirection, MARKET_5M_DURATION_SECS,
+};
+use crate::error::ApiError;
 use crate::log_buffer::LogBuffer;
+use crate::metrics::{FillHistogram, SharedMetrics, SymbolSchedule};
+use crate::models::{OrderBook, OrderResponse};
+use crate::notify::{self, Notifier};
 use crate::orderbook_ws::OrderbookMirror;
-use crate::paper_trade::{PaperTradeLogger, PredictionRecord};
-use crate::rtds::{LatestPriceCache, PriceCacheMulti};
+use crate::paper_trade::{PaperTradeLogger, PostCloseTrades, PredictionRecord, SkipReason, SweepOutcome};
+use crate::recorder::{self, BookSnapshot, RecordedEvent, Recorder};
+use crate::rtds::{
+    apply_chainlink_message, CaptureWindow, ChainlinkHandler, LastTradeHandler, LatestPrice, LatestPriceCache, PriceCacheMulti,
+    RtdsHandler, RtdsSocketConfig, SharedSymbols,
+};
 use anyhow::Result;
 use chrono::Utc;
+use futures_util::future::join_all;
 use log::{debug, error, info, warn};
-use std::collections::HashMap;
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::{Decimal, RoundingStrategy};
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::Arc;
-use tokio::sync::RwLock;
-use tokio::time::{sleep, Duration};
+use tokio::sync::{mpsc, RwLock};
+use tokio::time::{sleep, sleep_until, Duration, Instant};
 
 /// How long to wait for RTDS prices to arrive before giving up on a period (seconds).
 const PRICE_WAIT_TIMEOUT_SECS: u64 = 45;
 /// How often to re-check for RTDS prices while waiting (seconds).
 const PRICE_POLL_INTERVAL_SECS: u64 = 3;
+/// Minimum seconds into the period before a missing RTDS price-to-beat is looked up on-chain
+/// (also never before the RTDS capture tolerance has elapsed).
+const CHAINLINK_FALLBACK_AFTER_SECS: i64 = 10;
+
+/// Tokio instant corresponding to a Unix close time, with millisecond precision.
+fn close_instant(close_time_unix: i64) -> Instant {
+    let remaining_ms = close_time_unix * 1000 - Utc::now().timestamp_millis();
+    Instant::now() + Duration::from_millis(remaining_ms.max(0) as u64)
+}
+
+/// Pseudo-random delay in [0, max_ms) for the i-th symbol, so loops don't hit the API in lockstep.
+fn discovery_jitter(i: usize, max_ms: u64) -> Duration {
+    if max_ms == 0 {
+        return Duration::ZERO;
+    }
+    let seed = Utc::now().timestamp_subsec_nanos() as u64 ^ (i as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    Duration::from_millis(seed % max_ms)
+}
+
+/// `max`, shortened to the time left before `deadline_ms` (Unix ms) when one is set.
+fn until_deadline(deadline_ms: Option<i64>, max: Duration) -> Duration {
+    match deadline_ms {
+        Some(d) => max.min(Duration::from_millis((d - Utc::now().timestamp_millis()).max(0) as u64)),
+        None => max,
+    }
+}
+
+/// Poll REST books every `every` for the tokens the WS hasn't delivered yet and seed them into
+/// the mirror, so a slow stream still leaves a recent book at close. Runs until aborted.
+fn spawn_book_poller(
+    api: Arc<dyn TradingApi>,
+    mirror: Arc<OrderbookMirror>,
+    tokens: Vec<String>,
+    every: Duration,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        loop {
+            let pending: Vec<&str> = tokens.iter().map(String::as_str).filter(|t| !mirror.has_ws_book(t)).collect();
+            if !pending.is_empty() {
+                match api.get_orderbooks(&pending).await {
+                    Ok(books) => {
+                        for (token, book) in books {
+                            if mirror.seed_orderbook(&token, book).await {
+                                debug!("Seeded REST orderbook for {}", short(&token));
+                            }
+                        }
+                    }
+                    Err(e) => debug!("REST orderbook poll failed for {} tokens: {}", pending.len(), e),
+                }
+            }
+            sleep(every).await;
+        }
+    })
+}
+
+/// Decimal places of the CLOB lot size: order sizes have at most 2, whatever the tick.
+pub(crate) const LOT_SIZE_DECIMALS: u32 = 2;
+
+/// `size` rounded down to `decimals` places. Goes through `Decimal`, so float error can't drop
+/// a whole lot (`(1.15 * 100.0).floor()` gives 1.14) or leave decimals the SDK rejects.
+pub(crate) fn floor_to_lot(size: f64, decimals: u32) -> f64 {
+    Decimal::from_f64(size)
+        .map(|d| d.round_dp_with_strategy(decimals, RoundingStrategy::ToZero))
+        .and_then(|d| d.to_f64())
+        .unwrap_or(0.0)
+}
+
+/// Decimal places for FOK price and size strings on one token.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct OrderPrecision {
+    price_decimals: u32,
+    size_decimals: u32,
+}
+
+impl OrderPrecision {
+    fn from_tick(tick: Decimal) -> Self {
+        Self { price_decimals: tick.normalize().scale(), size_decimals: LOT_SIZE_DECIMALS }
+    }
+
+    fn price_str(&self, price: Decimal) -> String {
+        format!("{:.*}", self.price_decimals as usize, price.round_dp(self.price_decimals))
+    }
+
+    /// FOK limit price for an observed `ask`: `buffer` above it (`fok_price_buffer`), rounded
+    /// down to the tick and capped at `max_price`, but never below the ask itself.
+    fn limit_price(&self, ask: Decimal, buffer: f64, max_price: f64) -> Decimal {
+        let (Some(buffer), Some(cap)) = (Decimal::from_f64(buffer), Decimal::from_f64(max_price)) else {
+            return ask;
+        };
+        (ask + buffer)
+            .min(cap)
+            .round_dp_with_strategy(self.price_decimals, RoundingStrategy::ToZero)
+            .max(ask)
+    }
+
+    /// `size` rounded down to the lot size, so the order never exceeds the ask or the budget.
+    fn floor_size(&self, size: f64) -> f64 {
+        floor_to_lot(size, self.size_decimals)
+    }
+
+    fn size_str(&self, size: f64) -> String {
+        format!("{:.*}", self.size_decimals as usize, size)
+    }
+
+    fn min_size(&self) -> f64 {
+        10f64.powi(-(self.size_decimals as i32))
+    }
+
+    /// FOK buying as much of `ask_size` at `price` as `budget` allows, or None when that rounds
+    /// below the lot size or `min_notional`.
+    fn fok_order(&self, price: Decimal, ask_size: f64, budget: f64, min_notional: f64) -> Option<FokOrder> {
+        let price_str = self.price_str(price);
+        let price: f64 = price_str.parse().ok()?;
+        let max_affordable = if price > 0.0 { budget / price } else { 0.0 };
+        let size = self.floor_size(ask_size.min(max_affordable));
+        if size < self.min_size() || size * price < min_notional {
+            return None;
+        }
+        Some(FokOrder { price, size, price_str, size_str: self.size_str(size) })
+    }
+}
+
+/// One FOK buy as sent to the API: numeric values and their exact order strings.
+struct FokOrder {
+    price: f64,
+    size: f64,
+    price_str: String,
+    size_str: String,
+}
+
+impl Default for OrderPrecision {
+    /// 0.01 tick, the common case when the token's tick size isn't known.
+    fn default() -> Self {
+        Self::from_tick(Decimal::new(1, 2))
+    }
+}
+
+/// Sweep budget out of `max_sweep_cost` after accounting for `held` (cost basis, USD) of the
+/// winning token already in the wallet, per `subtract_holdings_from_budget` /
+/// `max_holding_notional_per_market`.
+fn effective_sweep_budget(cfg: &StrategyConfig, max_sweep_cost: f64, held: f64) -> f64 {
+    let mut budget = max_sweep_cost;
+    if cfg.subtract_holdings_from_budget {
+        budget -= held;
+    }
+    if let Some(cap) = cfg.max_holding_notional_per_market {
+        budget = budget.min(cap - held);
+    }
+    budget.max(0.0)
+}
+
+/// How often the config file's mtime is checked for hot reload.
+const CONFIG_WATCH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Strategy config in effect: the base section and the effective config per symbol (lowercase,
+/// `per_symbol` overrides applied). Replaced as a whole on hot reload; each round works from
+/// one snapshot.
+pub struct StrategySettings {
+    pub base: StrategyConfig,
+    by_symbol: HashMap<String, StrategyConfig>,
+}
+
+impl StrategySettings {
+    pub fn new(base: StrategyConfig) -> Self {
+        // Overrides for symbols outside `symbols` apply once auto-discovery adds them.
+        let by_symbol = base
+            .symbols
+            .iter()
+            .chain(base.per_symbol.keys())
+            .map(|s| (s.to_lowercase(), base.for_symbol(s)))
+            .collect();
+        Self { base, by_symbol }
+    }
+
+    /// Config for `symbol`, falling back to the base config for unlisted symbols.
+    pub fn for_symbol(&self, symbol: &str) -> &StrategyConfig {
+        self.by_symbol.get(&symbol.to_lowercase()).unwrap_or(&self.base)
+    }
+}
+
+pub type SharedSettings = Arc<std::sync::RwLock<Arc<StrategySettings>>>;
+
+/// Strategy fields only read at startup (RTDS handlers, recorder, capture window). A reload
+/// keeps the running values and warns if the file changed them.
+fn keep_restart_only_fields(current: &StrategyConfig, mut new: StrategyConfig) -> StrategyConfig {
+    let mut ignored = Vec::new();
+    if new.symbols != current.symbols
+        || new.auto_discover_symbols != current.auto_discover_symbols
+        || new.symbol_discovery_interval_secs != current.symbol_discovery_interval_secs
+    {
+        ignored.push("symbols / auto_discover_symbols");
+        new.symbols = current.symbols.clone();
+        new.auto_discover_symbols = current.auto_discover_symbols;
+        new.symbol_discovery_interval_secs = current.symbol_discovery_interval_secs;
+    }
+    if new.record_dir != current.record_dir {
+        ignored.push("record_dir");
+        new.record_dir = current.record_dir.clone();
+    }
+    if new.ptb_capture_window_secs != current.ptb_capture_window_secs
+        || new.ptb_capture_window_overrides != current.ptb_capture_window_overrides
+        || new.ptb_capture_tolerance_secs != current.ptb_capture_tolerance_secs
+    {
+        ignored.push("ptb_capture_*");
+        new.ptb_capture_window_secs = current.ptb_capture_window_secs;
+        new.ptb_capture_window_overrides = current.ptb_capture_window_overrides.clone();
+        new.ptb_capture_tolerance_secs = current.ptb_capture_tolerance_secs;
+    }
+    if new.live_after_rounds != current.live_after_rounds {
+        ignored.push("live_after_rounds");
+        new.live_after_rounds = current.live_after_rounds;
+    }
+    if new.paper_log_rotation != current.paper_log_rotation || new.paper_log_max_bytes != current.paper_log_max_bytes {
+        ignored.push("paper_log_*");
+        new.paper_log_rotation = current.paper_log_rotation;
+        new.paper_log_max_bytes = current.paper_log_max_bytes;
+    }
+    if !ignored.is_empty() {
+        warn!("Config reload: {} changed but needs a restart; keeping the running values", ignored.join(", "));
+    }
+    new
+}
 
 /// Per-symbol market info discovered for a period.
 struct SymbolRound {
@@ -29,80 +268,329 @@ struct SymbolRound {
     up_token: String,
     down_token: String,
     period_5: i64,
-    price_to_beat: f64,
+    price_to_beat: Decimal,
+    /// Close time (Unix): the market's `end_date_iso`, or period start + 5m if unusable.
+    close_time: i64,
 }
 
 pub struct ArbStrategy {
-    api: Arc<PolymarketApi>,
+    api: Arc<dyn TradingApi>,
     config: Config,
+    /// Live strategy config (hot-reloadable); `config.strategy` is the startup copy.
+    settings: SharedSettings,
     discovery: MarketDiscovery,
+    /// Symbols traded: `strategy.symbols`, or the latest `auto_discover_symbols` result.
+    symbols: SharedSymbols,
     /// symbol -> period_start -> price-to-beat (from RTDS Chainlink).
     price_cache_5: PriceCacheMulti,
     /// Latest RTDS price per symbol (for post-close sweep winner determination).
     latest_prices: LatestPriceCache,
+    /// RTDS price-to-beat capture window / tolerance.
+    ptb_capture: CaptureWindow,
     /// Paper trade logger.
     paper_trader: PaperTradeLogger,
     /// Web dashboard log buffer.
     log_buffer: LogBuffer,
     /// Single orderbook mirror shared across the unified loop.
     orderbook_mirror: Arc<OrderbookMirror>,
+    /// Market data recorder for backtesting (None unless `record_dir` is set).
+    recorder: Option<Recorder>,
+    /// Webhook alerts.
+    notifier: Notifier,
+    /// Runtime controls (pause/resume) set from the dashboard.
+    control: SharedControl,
+    /// Shared metrics (fill histogram) exposed on the dashboard.
+    metrics: SharedMetrics,
+    /// token_id -> order precision from its tick size, filled by `warm_order_cache` and cleared
+    /// with the presigned orders once the round's sweeps are done.
+    precisions: RwLock<HashMap<String, OrderPrecision>>,
+    /// Rounds still to simulate before trading live (`live_after_rounds`).
+    warmup_rounds_left: AtomicU32,
 }
 
 impl ArbStrategy {
-    pub fn new(api: Arc<PolymarketApi>, config: Config, log_buffer: LogBuffer) -> Self {
+    pub fn new(
+        api: Arc<dyn TradingApi>,
+        config: Config,
+        log_buffer: LogBuffer,
+        notifier: Notifier,
+        control: SharedControl,
+        metrics: SharedMetrics,
+        orderbook_mirror: Arc<OrderbookMirror>,
+    ) -> Self {
         let latest_prices: LatestPriceCache = Arc::new(RwLock::new(HashMap::new()));
-        let paper_trader = PaperTradeLogger::new(Arc::clone(&latest_prices), log_buffer.clone());
+        let ptb_capture = CaptureWindow::from_config(&config.strategy);
+        let paper_trader = PaperTradeLogger::new(log_buffer.clone())
+            .with_rotation(config.strategy.paper_log_rotation, config.strategy.paper_log_max_bytes);
+        let settings = Arc::new(std::sync::RwLock::new(Arc::new(StrategySettings::new(config.strategy.clone()))));
+        let recorder = config.strategy.record_dir.as_deref().and_then(|dir| match Recorder::new(dir) {
+            Ok(r) => {
+                info!("Recording market data to {}", dir);
+                Some(r)
+            }
+            Err(e) => {
+                warn!("Recording disabled: {}", e);
+                None
+            }
+        });
         Self {
             discovery: MarketDiscovery::new(api.clone()),
+            symbols: Arc::new(std::sync::RwLock::new(config.strategy.symbols.clone())),
+            warmup_rounds_left: AtomicU32::new(config.strategy.live_after_rounds),
             api,
             config,
+            settings,
             price_cache_5: Arc::new(RwLock::new(HashMap::new())),
             latest_prices,
+            ptb_capture,
             paper_trader,
             log_buffer,
-            orderbook_mirror: Arc::new(OrderbookMirror::new()),
+            orderbook_mirror,
+            recorder,
+            notifier,
+            control,
+            metrics,
+            precisions: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Snapshot of the strategy config currently in effect.
+    fn settings(&self) -> Arc<StrategySettings> {
+        Arc::clone(&self.settings.read().unwrap())
+    }
+
+    /// The live settings, swapped on every hot reload; for the dashboard.
+    pub fn shared_settings(&self) -> SharedSettings {
+        Arc::clone(&self.settings)
+    }
+
+    fn symbols(&self) -> Vec<String> {
+        self.symbols.read().unwrap().clone()
+    }
+
+    /// Replace the traded symbols with the active up/down markets on Gamma that have a Chainlink
+    /// aggregator configured (`auto_discover_symbols`). The current symbols stay when the lookup
+    /// fails or finds none.
+    async fn refresh_symbols(&self) {
+        let duration = format!("{}m", MARKET_5M_DURATION_SECS / 60);
+        let active = match self.api.discover_active_updown_markets(&duration).await {
+            Ok(active) => active,
+            Err(e) => {
+                warn!("Symbol discovery failed, keeping {:?}: {}", self.symbols(), e);
+                return;
+            }
+        };
+        let aggregators = &self.config.polymarket.chainlink_aggregators;
+        let found: Vec<String> = active
+            .into_iter()
+            .filter(|s| aggregators.keys().any(|k| k.eq_ignore_ascii_case(s)))
+            .collect();
+        if found.is_empty() {
+            warn!("Symbol discovery found no active {} market with a Chainlink aggregator, keeping {:?}", duration, self.symbols());
+            return;
+        }
+        let previous = std::mem::replace(&mut *self.symbols.write().unwrap(), found.clone());
+        if previous != found {
+            info!("Auto-discovered symbols: {:?} (was {:?})", found, previous);
+            self.log_buffer.push("SYS", "info", format!("symbols now {}", found.join(", "))).await;
+        }
+    }
+
+    /// `max_sweep_cost` in effect for `cfg`: the dashboard's budget override if one is set.
+    fn max_sweep_cost(&self, cfg: &StrategyConfig) -> f64 {
+        self.control.budget_override().unwrap_or(cfg.max_sweep_cost)
+    }
+
+    /// Monitor mode: no private key, so nothing can be signed or sent whatever the config says.
+    /// Every order path checks this one guard; sweeps still run, simulated against the book, so
+    /// the paper log and `/pnl` track what they would have made.
+    fn monitor_mode(&self) -> bool {
+        !self.api.has_private_key()
+    }
+
+    /// Still in the `live_after_rounds` warmup: sweeps are simulated like `simulation_mode`.
+    fn warming_up(&self) -> bool {
+        self.warmup_rounds_left.load(Ordering::Relaxed) > 0
+    }
+
+    /// Log where the warmup stands at the start of a round.
+    async fn log_warmup(&self) {
+        let left = self.warmup_rounds_left.load(Ordering::Relaxed);
+        if left == 0 {
+            return;
         }
+        let total = self.config.strategy.live_after_rounds;
+        let message = format!(
+            "warmup round {}/{}: paper only, live orders after {} more round(s)",
+            total - left + 1,
+            total,
+            left
+        );
+        info!("{}", message);
+        self.log_buffer.push("SYS", "info", message).await;
+    }
+
+    /// Count a finished round against the warmup.
+    async fn finish_warmup_round(&self) {
+        let left = self.warmup_rounds_left.load(Ordering::Relaxed);
+        if left == 0 {
+            return;
+        }
+        self.warmup_rounds_left.store(left - 1, Ordering::Relaxed);
+        if left == 1 {
+            let message = "warmup complete: live orders from the next round".to_string();
+            info!("{}", message);
+            self.log_buffer.push("SYS", "warn", message).await;
+        }
+    }
+
+    /// Watch `path` and hot-reload the strategy section when its mtime changes. Reloads go
+    /// through `Config::load`, so invalid files are rejected and the running config kept.
+    /// Credentials, URLs and the other sections are never reloaded.
+    pub fn spawn_config_watch(&self, path: std::path::PathBuf) {
+        let settings = Arc::clone(&self.settings);
+        let log_buffer = self.log_buffer.clone();
+        let mtime = |p: &std::path::Path| std::fs::metadata(p).and_then(|m| m.modified()).ok();
+        tokio::spawn(async move {
+            let mut last = mtime(&path);
+            loop {
+                sleep(CONFIG_WATCH_INTERVAL).await;
+                let current = mtime(&path);
+                // A missing file would load as defaults: ignore it until it's back.
+                if current.is_none() || current == last {
+                    continue;
+                }
+                last = current;
+                match Config::load(&path) {
+                    Ok(config) => {
+                        let running = settings.read().unwrap().base.clone();
+                        let strategy = keep_restart_only_fields(&running, config.strategy);
+                        *settings.write().unwrap() = Arc::new(StrategySettings::new(strategy));
+                        info!("Config reload: strategy settings from {} apply from the next round", path.display());
+                        log_buffer.push("SYS", "info", format!("strategy config reloaded from {}", path.display())).await;
+                    }
+                    Err(e) => {
+                        warn!("Config reload rejected, keeping the running config: {:#}", e);
+                        log_buffer.push("SYS", "warn", format!("config reload rejected: {:#}", e)).await;
+                    }
+                }
+            }
+        });
     }
 
-    /// Discover market + price-to-beat for a single symbol in the current period.
+    /// Discover market + price-to-beat for a single symbol in the period starting at `period_5`.
     /// Returns None if the market or price is not available.
-    async fn discover_symbol(&self, symbol: &str) -> Result<Option<SymbolRound>> {
-        let period_5 = current_5m_period_start();
-        let (m5_cid, question) = match self.discovery.get_5m_market(symbol, period_5).await? {
-            Some(v) => v,
+    async fn discover_symbol(&self, symbol: &str, period_5: i64) -> Result<Option<SymbolRound>> {
+        let market = match self.discovery.get_5m_market(symbol, period_5).await? {
+            Some(m) => m,
             None => {
                 warn!("{} no market for period {}", symbol, period_5);
                 return Ok(None);
             }
         };
-        // Try RTDS WS cache first, fall back to parsing market question
+        // Up wins at or above the threshold. A question phrased the other way round ("below $X")
+        // would flip which outcome that is, so such a market is not traded.
+        if let Some((_, ThresholdDirection::Below)) = parse_price_to_beat_from_question(&market.question) {
+            warn!("{} market {} asks for a close below its threshold, skipping: {}", symbol, market.slug, market.question);
+            return Ok(None);
+        }
+        let m5_cid = market.condition_id;
+        // Try RTDS WS cache first, fall back to the market's own price-to-beat
         let price_to_beat = {
             let cache = self.price_cache_5.read().await;
             cache.get(symbol).and_then(|per_period| per_period.get(&period_5).copied())
         };
+        let price_to_beat = match price_to_beat {
+            Some(p) => Some(p),
+            None => self.chainlink_price_to_beat(symbol, period_5).await,
+        };
+        // The market's structured threshold when Gamma has one, else the one in its question text.
+        let (market_ptb_source, market_ptb) = match market.price_to_beat {
+            Some(p) => ("metadata", Some(p)),
+            None => ("question", parse_price_to_beat_from_question(&market.question).map(|(p, _)| p)),
+        };
+        let settings = self.settings();
+        let cfg = settings.for_symbol(symbol);
+        // A source outside the symbol's plausible range is a glitch; drop it so the other is used.
+        let plausible_source = |source: &str, ptb: Option<Decimal>| {
+            ptb.filter(|p| {
+                let ok = plausible(*p, cfg);
+                if !ok {
+                    warn!(
+                        "{} {} PTB {} outside plausible range [{}, {}], ignoring it",
+                        symbol, source, usd(*p), cfg.min_plausible_price, cfg.max_plausible_price
+                    );
+                }
+                ok
+            })
+        };
+        let price_to_beat = plausible_source("feed", price_to_beat);
+        let market_ptb = plausible_source(market_ptb_source, market_ptb);
+        if let (Some(feed), Some(q)) = (price_to_beat, market_ptb) {
+            // Cross-check: a large disagreement means the wrong period was captured or the feed glitched
+            let diff_pct = ((feed - q).abs() / q * Decimal::ONE_HUNDRED).to_f64().unwrap_or(f64::MAX);
+            if diff_pct > settings.base.ptb_question_tolerance_pct {
+                warn!(
+                    "{} PTB mismatch: feed={} {}={} ({:.3}% > {}% tolerance)",
+                    symbol, usd(feed), market_ptb_source, usd(q), diff_pct, settings.base.ptb_question_tolerance_pct
+                );
+                self.log_buffer.push(symbol, "warn", format!(
+                    "PTB mismatch feed={} {}={} ({:.3}%)", usd(feed), market_ptb_source, usd(q), diff_pct
+                )).await;
+                if settings.base.skip_on_ptb_mismatch {
+                    return Ok(None);
+                }
+            }
+        }
         let price_to_beat = match price_to_beat {
             Some(p) => p,
             None => {
-                // Fallback: parse price-to-beat from market question text
-                match parse_price_to_beat_from_question(&question) {
+                // Fallback: the market's metadata or question text
+                match market_ptb {
                     Some(p) => {
-                        info!("{} PTB from market question: ${} (RTDS not yet available)", symbol, p);
+                        info!("{} PTB from market {}: {} (RTDS not yet available)", symbol, market_ptb_source, usd(p));
                         p
                     }
                     None => {
-                        warn!("{} no price-to-beat from RTDS or market question for period {}", symbol, period_5);
+                        warn!("{} no price-to-beat from RTDS or market metadata/question for period {}", symbol, period_5);
                         return Ok(None);
                     }
                 }
             }
         };
-        let (m5_up, m5_down) = self.discovery.get_market_tokens(&m5_cid).await?;
-        debug!("{} period={} ptb=${} up={}.. down={}..)",
-            symbol, period_5, price_to_beat,
-            &m5_up[..m5_up.len().min(12)],
-            &m5_down[..m5_down.len().min(12)],
+        let market = match self.discovery.get_market_tokens(&m5_cid).await? {
+            Some(market) => market,
+            None => return Ok(None),
+        };
+        let (m5_up, m5_down) = (market.up_token, market.down_token);
+        if self.settings().base.is_denylisted(&m5_cid, &[&m5_up, &m5_down]) {
+            warn!("{} market {} is denylisted: paper-logging only, no orders", symbol, m5_cid);
+            self.log_buffer.push(symbol, "warn", format!("market {} denylisted, no orders this round", m5_cid)).await;
+        }
+        let computed_close = period_5 + MARKET_5M_DURATION_SECS;
+        let close_time = match market.end_time {
+            // An end date more than a period away from ours is not this market's boundary.
+            Some(end) if (end - computed_close).abs() < MARKET_5M_DURATION_SECS => {
+                if end != computed_close {
+                    info!("{} market end date is {}s off the computed close; using the market's", symbol, end - computed_close);
+                }
+                end
+            }
+            Some(end) => {
+                warn!("{} market end date {} is far from computed close {}; using computed", symbol, end, computed_close);
+                computed_close
+            }
+            None => {
+                debug!("{} market end date missing or unparsable; using computed close", symbol);
+                computed_close
+            }
+        };
+        debug!("{} period={} ptb={} up={}.. down={}..)",
+            symbol, period_5, usd(price_to_beat),
+            short(&m5_up),
+            short(&m5_down),
         );
-        self.log_buffer.push(symbol, "info", format!("period={} ptb=${}", period_5, price_to_beat)).await;
+        self.log_buffer.push(symbol, "info", format!("period={} ptb={}", period_5, usd(price_to_beat))).await;
         Ok(Some(SymbolRound {
             symbol: symbol.to_string(),
             condition_id: m5_cid,
@@ -110,215 +598,907 @@ impl ArbStrategy {
             down_token: m5_down,
             period_5,
             price_to_beat,
+            close_time,
         }))
     }
 
-    /// Post-close sweep: determine winner from latest RTDS WS price,
-    /// then buy winning tokens from stale limit orders using FOK orders.
-    async fn sweep_stale_asks(
-        &self,
-        symbol: &str,
-        price_to_beat: f64,
-        m5_up: &str,
-        m5_down: &str,
-    ) -> Result<(u32, f64, f64)> {
-        let cfg = &self.config.strategy;
-        let now_ms = Utc::now().timestamp_millis();
+    /// On-chain fallback for price-to-beat: only once RTDS has clearly missed the capture
+    /// window, look up the Chainlink aggregator round in effect at period start.
+    async fn chainlink_price_to_beat(&self, symbol: &str, period_5: i64) -> Option<Decimal> {
+        let after_secs = CHAINLINK_FALLBACK_AFTER_SECS.max(self.ptb_capture.deadline_secs(symbol));
+        if Utc::now().timestamp() < period_5 + after_secs {
+            return None;
+        }
+        let aggregator = self.config.polymarket.chainlink_aggregators.get(symbol)?;
+        match self.api.get_chainlink_price_rpc(aggregator, period_5).await {
+            Ok((price, updated_at)) => {
+                info!(
+                    "{} PTB from Chainlink RPC: {} (round updated {}s before period start)",
+                    symbol, usd(price), period_5 - updated_at
+                );
+                self.price_cache_5.write().await
+                    .entry(symbol.to_string()).or_default()
+                    .entry(period_5).or_insert(price);
+                Some(price)
+            }
+            Err(e) => {
+                warn!("{} Chainlink RPC PTB lookup failed: {}", symbol, e);
+                None
+            }
+        }
+    }
 
-        let rtds_result = {
-            let cache = self.latest_prices.read().await;
-            cache.get(symbol).cloned()
+    /// Count an ambiguous network error toward the circuit breaker; when it trips, alert on the
+    /// dashboard and the webhook.
+    async fn record_network_error(&self, symbol: &str, cfg: &StrategyConfig) {
+        let now = Utc::now().timestamp();
+        let threshold = cfg.network_error_breaker_threshold;
+        let Some(until) = self.control.record_network_error(threshold, cfg.network_error_breaker_cooldown_secs, now) else {
+            return;
         };
+        let msg = format!(
+            "{} network errors in a row on live orders, circuit breaker open: monitor-only for {}s",
+            threshold, cfg.network_error_breaker_cooldown_secs
+        );
+        error!("Sweep {}: {}", symbol, msg);
+        self.log_buffer.push(symbol, "error", msg.clone()).await;
+        self.notifier.notify(
+            notify::EVENT_CIRCUIT_BREAKER, symbol, msg,
+            serde_json::json!({"consecutive_errors": threshold, "open_until": until}),
+        );
+    }
+
+    /// Count a resolved live sweep toward `daily_loss_limit`; the sweep that takes the day's net
+    /// loss to the limit pauses trading and sends an alert.
+    async fn record_sweep_pnl(&self, symbol: &str, sweep: &SweepOutcome, won: bool, cfg: &StrategyConfig) {
+        if !sweep.live || sweep.shares <= 0.0 {
+            return;
+        }
+        let pnl = if won { sweep.shares } else { 0.0 } - sweep.cost;
+        let day = Utc::now().timestamp().div_euclid(86_400);
+        let total = self.control.record_sweep_pnl(pnl, day);
+        let limit = cfg.daily_loss_limit;
+        if limit <= 0.0 || total > -limit || total - pnl <= -limit {
+            return;
+        }
+        self.control.set_paused(true);
+        let msg = format!(
+            "daily loss limit hit: net {} today (limit {}), trading paused",
+            dollars(total), dollars(limit)
+        );
+        error!("{}: {}", symbol, msg);
+        self.log_buffer.push(symbol, "error", msg.clone()).await;
+        self.notifier.notify(
+            notify::EVENT_DAILY_LOSS_LIMIT, symbol, msg,
+            serde_json::json!({"day_pnl": total, "limit": limit, "sweep_pnl": pnl}),
+        );
+    }
 
-        let latest_price = match rtds_result {
-            Some((p, ts, _)) => {
-                let age = (now_ms - ts) / 1000;
-                debug!("Sweep {} RTDS WS: ${} (age={}s)", symbol, p, age);
-                p
+    /// `require_source_agreement_for_live`: whether the Chainlink aggregator, read over RPC at
+    /// close, puts the price on the same side of the price-to-beat as RTDS did (`winner`). No
+    /// aggregator or a failed lookup counts as no confirmation.
+    async fn rpc_confirms_winner(&self, round: &SymbolRound, winner: &str) -> bool {
+        let symbol = &round.symbol;
+        let Some(aggregator) = self.config.polymarket.chainlink_aggregators.get(symbol) else {
+            warn!("Sweep {}: no Chainlink aggregator to confirm the winner, skipping live orders", symbol);
+            return false;
+        };
+        let rpc_price = match self.api.get_chainlink_price_rpc(aggregator, round.close_time).await {
+            Ok((price, _)) => price,
+            Err(e) => {
+                warn!("Sweep {}: Chainlink RPC price unavailable ({}), skipping live orders", symbol, e);
+                return false;
             }
-            None => {
-                warn!("Sweep {}: no RTDS WS price available, skipping.", symbol);
-                return Ok((0, 0.0, 0.0));
+        };
+        let rpc_winner = match rpc_price.cmp(&round.price_to_beat) {
+            std::cmp::Ordering::Greater => "Up",
+            std::cmp::Ordering::Less => "Down",
+            std::cmp::Ordering::Equal => "tie",
+        };
+        if rpc_winner != winner {
+            warn!(
+                "Sweep {}: sources disagree on winner, skipping live orders (RTDS {}, Chainlink RPC {} at {} vs ptb {})",
+                symbol, winner, rpc_winner, usd(rpc_price), usd(round.price_to_beat)
+            );
+            return false;
+        }
+        debug!("Sweep {}: Chainlink RPC {} confirms {}", symbol, usd(rpc_price), winner);
+        true
+    }
+
+    /// Compare each round's RTDS close price with the Chainlink RPC price at the same feed
+    /// time and add the difference to the per-symbol agreement stats on `/health`. Runs after
+    /// the sweeps; symbols without an aggregator or a failed RPC lookup are left out.
+    async fn record_price_agreement(&self, predictions: &[PredictionRecord]) {
+        let tolerance = self.settings().base.price_agreement_tolerance_pct;
+        for pred in predictions {
+            let Some(aggregator) = self.config.polymarket.chainlink_aggregators.get(&pred.symbol) else {
+                continue;
+            };
+            let rpc_price = match self.api.get_chainlink_price_rpc(aggregator, pred.close_rtds_ts_ms / 1000).await {
+                Ok((price, _)) if price > Decimal::ZERO => price,
+                Ok(_) => continue,
+                Err(e) => {
+                    debug!("{} Chainlink RPC close lookup failed: {}", pred.symbol, e);
+                    continue;
+                }
+            };
+            let diff_pct = ((pred.close_price - rpc_price).abs() / rpc_price * Decimal::ONE_HUNDRED).to_f64().unwrap_or(f64::MAX);
+            let disagrees = diff_pct > tolerance;
+            if disagrees {
+                warn!(
+                    "{} RTDS close {} vs Chainlink RPC {} differ by {:.3}% (> {}% tolerance)",
+                    pred.symbol, usd(pred.close_price), usd(rpc_price), diff_pct, tolerance
+                );
+            }
+            self.metrics.price_agreement.record(&pred.symbol, diff_pct, disagrees);
+        }
+    }
+
+    /// Cost basis (USD) held per token of this period's markets, fetched before close so the
+    /// sweep doesn't wait on the data API. Empty when no holdings option is enabled; on lookup
+    /// failure the sweep falls back to the plain `max_sweep_cost`.
+    async fn fetch_holdings(&self, rounds: &[SymbolRound]) -> HashMap<String, f64> {
+        let settings = self.settings();
+        let needed = rounds.iter().any(|r| {
+            let cfg = settings.for_symbol(&r.symbol);
+            cfg.sweep_enabled
+                && (cfg.subtract_holdings_from_budget || cfg.max_holding_notional_per_market.is_some())
+        });
+        if !needed {
+            return HashMap::new();
+        }
+        let Some(wallet) = self.api.wallet_address() else {
+            warn!("Holdings-aware sweep budget needs a private key or proxy wallet; ignoring holdings");
+            return HashMap::new();
+        };
+        let tokens: HashSet<&str> = rounds
+            .iter()
+            .flat_map(|r| [r.up_token.as_str(), r.down_token.as_str()])
+            .collect();
+        match self.api.get_positions(&wallet, false).await {
+            Ok(positions) => positions
+                .into_iter()
+                .filter(|p| tokens.contains(p.asset.as_str()) && p.size > 0.0)
+                .map(|p| (p.asset, p.size * p.avg_price))
+                .collect(),
+            Err(e) => {
+                warn!("Positions lookup failed, sweeping without holdings: {}", e);
+                HashMap::new()
             }
+        }
+    }
+
+    /// Persist the resolved winner's orderbook at close for `symbol` (see `book_snapshot_dir`).
+    fn write_winner_book(
+        &self,
+        dir: &str,
+        rounds: &[SymbolRound],
+        symbol: &str,
+        winner: &str,
+        close_books: &HashMap<String, OrderBook>,
+        captured_ms: i64,
+    ) {
+        let Some(round) = rounds.iter().find(|r| r.symbol == symbol) else {
+            return;
+        };
+        let token_id = if winner == "Up" { &round.up_token } else { &round.down_token };
+        let snapshot = BookSnapshot {
+            symbol: symbol.to_string(),
+            period_5: round.period_5,
+            condition_id: round.condition_id.clone(),
+            winner: winner.to_string(),
+            token_id: token_id.clone(),
+            captured_ms,
+            book: close_books.get(token_id).cloned(),
         };
+        match recorder::write_book_snapshot(std::path::Path::new(dir), &snapshot) {
+            Ok(path) => debug!("{} close book saved to {}", symbol, path.display()),
+            Err(e) => warn!("{} close book not saved: {:#}", symbol, e),
+        }
+    }
 
-        if latest_price.is_nan() || latest_price.is_infinite() || latest_price <= 0.0
-            || latest_price < 0.001 || latest_price > 1_000_000.0
-        {
-            warn!("Sweep {}: latest_price {} fails sanity check, skipping.", symbol, latest_price);
-            return Ok((0, 0.0, 0.0));
+    /// Market trades on the predicted side since the round closed, for the paper log. None if
+    /// the lookup failed.
+    async fn post_close_trades(&self, rounds: &[SymbolRound], pred: &PredictionRecord) -> Option<PostCloseTrades> {
+        let round = rounds.iter().find(|r| r.symbol == pred.symbol)?;
+        let token_id = if pred.prediction == "Up" { &round.up_token } else { &round.down_token };
+        match self.api.get_trades(&round.condition_id, token_id, round.close_time).await {
+            Ok(trades) => Some(PostCloseTrades::from_trades(&trades)),
+            Err(e) => {
+                debug!("{} post-close trades unavailable: {}", pred.symbol, e);
+                None
+            }
         }
-        if price_to_beat.is_nan() || price_to_beat.is_infinite() || price_to_beat <= 0.0
-            || price_to_beat < 0.001 || price_to_beat > 1_000_000.0
-        {
-            warn!("Sweep {}: price_to_beat {} fails sanity check, skipping.", symbol, price_to_beat);
-            return Ok((0, 0.0, 0.0));
+    }
+
+    /// Drop the round's orderbook subscription (WS and REST poller): right after the sweep, or
+    /// once every market has resolved (`resolved`) with `keep_orderbook_until_resolved`. The
+    /// other call is a no-op, so both calls take the `cfg` the round started with.
+    async fn release_orderbook(&self, period_5: i64, rounds: &[SymbolRound], resolved: bool, cfg: &StrategyConfig) {
+        if resolved != cfg.keep_orderbook_until_resolved {
+            return;
         }
+        self.orderbook_mirror.unsubscribe_all().await;
+        self.publish_schedule(period_5, rounds, false).await;
+    }
+
+    /// Publish each configured symbol's round state for the dashboard `/schedule` grid.
+    /// Symbols not (yet) discovered report the computed close and whether RTDS has a PTB.
+    async fn publish_schedule(&self, period_5: i64, rounds: &[SymbolRound], subscribed: bool) {
+        let cache = self.price_cache_5.read().await;
+        let entries = self
+            .symbols()
+            .iter()
+            .map(|symbol| {
+                let round = rounds.iter().find(|r| r.symbol.eq_ignore_ascii_case(symbol));
+                SymbolSchedule {
+                    symbol: symbol.to_lowercase(),
+                    period_start: period_5,
+                    close_time: round.map_or(period_5 + MARKET_5M_DURATION_SECS, |r| r.close_time),
+                    has_price_to_beat: round.is_some()
+                        || cache.get(&symbol.to_lowercase()).is_some_and(|p| p.contains_key(&period_5)),
+                    subscribed: round.is_some() && subscribed,
+                    up_token: round.map(|r| r.up_token.clone()),
+                    down_token: round.map(|r| r.down_token.clone()),
+                }
+            })
+            .collect();
+        self.metrics.set_schedule(entries);
+    }
 
-        let diff = latest_price - price_to_beat;
+    /// Warm the order cache for `(symbol, token)` pairs and remember each token's tick size;
+    /// failures are only logged.
+    async fn warm_order_cache(&self, tokens: &[(&str, &str)]) {
+        if self.monitor_mode() || !self.api.is_authenticated() {
+            return;
+        }
+        for (symbol, token) in tokens {
+            match self.api.warm_order_cache(token).await {
+                Ok(tick) => {
+                    self.precisions.write().await.insert(token.to_string(), OrderPrecision::from_tick(tick));
+                }
+                Err(e) => debug!("{} cache warm failed for {}..): {}", symbol, short(token), e),
+            }
+        }
+    }
 
-        if diff == 0.0 {
-            debug!("Sweep {}: diff=0 (tied), skipping.", symbol);
-            return Ok((0, 0.0, 0.0));
+    /// Token the latest RTDS price favours and its relative margin over the price to beat,
+    /// or `None` without a usable price.
+    async fn likely_winner<'a>(&self, round: &'a SymbolRound) -> Option<(&'a str, f64)> {
+        let price = self.latest_prices.read().await.get(&round.symbol).map(|(p, _, _)| *p)?;
+        if round.price_to_beat <= Decimal::ZERO || price == round.price_to_beat {
+            return None;
         }
+        let margin = ((price - round.price_to_beat).abs() / round.price_to_beat).to_f64()?;
+        let token = if price > round.price_to_beat { &round.up_token } else { &round.down_token };
+        Some((token.as_str(), margin))
+    }
 
-        let min_margin_abs = cfg.sweep_min_margin_pct * price_to_beat;
-        if diff.abs() < min_margin_abs {
-            debug!(
-                "Sweep {}: diff ${} < min margin ${} ({}%), skipping.",
-                symbol, diff.abs(), min_margin_abs, cfg.sweep_min_margin_pct * 100.0
-            );
-            return Ok((0, 0.0, 0.0));
+    /// Sign FOK orders for the likely winner's eligible asks before close, sized like the sweep
+    /// will size them against the current book. Unused ones are cleared after the round.
+    async fn presign_sweep_orders(&self, rounds: &[SymbolRound], holdings: &HashMap<String, f64>) {
+        if self.monitor_mode() || !self.api.is_authenticated() {
+            return;
+        }
+        let settings = self.settings();
+        let started = std::time::Instant::now();
+        let mut orders: Vec<(&str, FokOrder)> = Vec::new();
+        for round in rounds {
+            let cfg = settings.for_symbol(&round.symbol);
+            if !cfg.sweep_enabled || cfg.simulation_mode || self.warming_up() || cfg.presign_orders == 0 {
+                continue;
+            }
+            if cfg.is_denylisted(&round.condition_id, &[&round.up_token, &round.down_token]) {
+                continue;
+            }
+            let Some((token, _)) = self.likely_winner(round).await else {
+                continue;
+            };
+            let Some(book) = self.orderbook_mirror.get_orderbook(token).await else {
+                continue;
+            };
+            let precision = self.precisions.read().await.get(token).copied().unwrap_or_default();
+            let max_cost = self.max_sweep_cost(cfg);
+            let mut budget = effective_sweep_budget(cfg, max_cost, holdings.get(token).copied().unwrap_or(0.0));
+            let mut count = 0;
+            for ask in book.eligible_asks(0.0, cfg.sweep_max_price) {
+                if count >= cfg.presign_orders || budget <= 0.0 {
+                    break;
+                }
+                let Some(ask_size) = ask.size.to_f64() else { continue };
+                let limit = precision.limit_price(ask.price, cfg.fok_price_buffer, cfg.sweep_max_price);
+                if let Some(order) = precision.fok_order(limit, ask_size, budget, cfg.min_order_notional) {
+                    budget -= order.size * order.price;
+                    count += 1;
+                    orders.push((token, order));
+                }
+            }
+        }
+        if orders.is_empty() {
+            return;
+        }
+        let results = join_all(
+            orders
+                .iter()
+                .map(|(token, o)| self.api.presign_fok_buy(token, &o.size_str, &o.price_str)),
+        )
+        .await;
+        let signed = results.iter().filter(|r| r.is_ok()).count();
+        if let Some(Err(e)) = results.iter().find(|r| r.is_err()) {
+            debug!("Presign failed: {}", e);
+        }
+        info!("Presigned {}/{} FOK orders in {:?}", signed, orders.len(), started.elapsed());
+    }
+
+    /// Paper-log and sweep closed rounds one after another, in `order`. Every round is decided
+    /// from its symbol's RTDS price read before the first sweep: a long sweep of one symbol
+    /// would otherwise leave the next deciding from a price well past close, which fails
+    /// `winner_price_max_skew_ms`. Returns the predictions and sweep outcomes by symbol.
+    async fn log_and_sweep(
+        &self,
+        order: &[&SymbolRound],
+        holdings: &HashMap<String, f64>,
+        settings: &StrategySettings,
+    ) -> (Vec<PredictionRecord>, HashMap<String, SweepOutcome>) {
+        let close_ticks: HashMap<String, LatestPrice> = {
+            let cache = self.latest_prices.read().await;
+            order
+                .iter()
+                .filter_map(|r| cache.get(&r.symbol).map(|tick| (r.symbol.clone(), tick.clone())))
+                .collect()
+        };
+        let mut predictions = Vec::new();
+        let mut sweeps = HashMap::new();
+        for round in order {
+            let close_tick = close_ticks.get(&round.symbol);
+            // Paper trade log
+            let symbol_cfg = settings.for_symbol(&round.symbol);
+            if let Some(pred) = self.paper_trader
+                .log(&round.symbol, round.period_5, round.price_to_beat, &round.condition_id, round.close_time, close_tick, symbol_cfg)
+                .await
+            {
+                predictions.push(pred);
+            }
+
+            // Sweep
+            if symbol_cfg.sweep_enabled {
+                match self.sweep_at_close(round, holdings, close_tick).await {
+                    Ok(outcome) => {
+                        sweeps.insert(round.symbol.clone(), outcome);
+                    }
+                    Err(e) => error!("Sweep {} error: {}", round.symbol, e),
+                }
+            }
         }
+        (predictions, sweeps)
+    }
+
+    /// `sweep_at_close` with the symbol's latest RTDS price as the close price.
+    #[cfg(test)]
+    async fn sweep_stale_asks(
+        &self,
+        round: &SymbolRound,
+        holdings: &HashMap<String, f64>,
+    ) -> Result<SweepOutcome> {
+        let close_tick = self.latest_prices.read().await.get(&round.symbol).cloned();
+        self.sweep_at_close(round, holdings, close_tick.as_ref()).await
+    }
+
+    /// Post-close sweep: determine winner from the RTDS price at close (`close_tick`),
+    /// then buy winning tokens from stale limit orders using FOK orders.
+    async fn sweep_at_close(
+        &self,
+        round: &SymbolRound,
+        holdings: &HashMap<String, f64>,
+        close_tick: Option<&LatestPrice>,
+    ) -> Result<SweepOutcome> {
+        let symbol = round.symbol.as_str();
+        let price_to_beat = round.price_to_beat;
+        let (m5_up, m5_down) = (round.up_token.as_str(), round.down_token.as_str());
+        let settings = self.settings();
+        let cfg = settings.for_symbol(symbol);
+        let now_ms = Utc::now().timestamp_millis();
+
+        let (latest_price, feed_ts) = match close_tick {
+            Some(&(p, ts, _)) => {
+                debug!("Sweep {} RTDS WS: {} (age={}s)", symbol, usd(p), (now_ms - ts) / 1000);
+                (p, ts)
+            }
+            None => {
+                warn!("Sweep {}: no RTDS WS price available, skipping.", symbol);
+                self.notifier.notify(
+                    notify::EVENT_RTDS_STALE, symbol,
+                    format!("{}: no RTDS price at sweep time", symbol),
+                    serde_json::Value::Null,
+                );
+                return Ok(SweepOutcome::skipped(SkipReason::NoClosePrice));
+            }
+        };
 
-        let (winner, winning_token) = if diff > 0.0 {
-            ("Up", m5_up)
-        } else {
-            ("Down", m5_down)
+        let decision = decide_winner(price_to_beat, latest_price, feed_ts - round.close_time * 1000, cfg);
+        let (winner, diff) = match decision {
+            Decision::Sweep { side, diff } => (side, diff),
+            Decision::SkipStale { skew_ms } => {
+                warn!(
+                    "Sweep {}: RTDS price {} has feed_ts {}ms from close (max {}ms), skipping.",
+                    symbol, usd(latest_price), skew_ms, cfg.winner_price_max_skew_ms
+                );
+                self.notifier.notify(
+                    notify::EVENT_RTDS_STALE, symbol,
+                    format!("{}: RTDS price at sweep time is {}ms from close", symbol, skew_ms),
+                    serde_json::json!({ "feed_ts": feed_ts, "skew_ms": skew_ms }),
+                );
+                return Ok(SweepOutcome::skipped(SkipReason::RtdsStale));
+            }
+            Decision::SkipSanity => {
+                warn!(
+                    "Sweep {}: latest_price {} or price_to_beat {} outside plausible range [{}, {}], skipping.",
+                    symbol, latest_price, price_to_beat, cfg.min_plausible_price, cfg.max_plausible_price
+                );
+                return Ok(SweepOutcome::skipped(SkipReason::InvalidPrice));
+            }
+            Decision::SkipTied => {
+                debug!("Sweep {}: diff=0 (tied), skipping.", symbol);
+                return Ok(SweepOutcome::skipped(SkipReason::Tied));
+            }
+            Decision::SkipBelowMargin { diff, min_margin } => {
+                debug!(
+                    "Sweep {}: diff {} < min margin {} ({}%), skipping.",
+                    symbol, usd_diff(diff, price_to_beat), usd_diff(min_margin, price_to_beat), cfg.sweep_min_margin_pct * 100.0
+                );
+                return Ok(SweepOutcome::skipped(SkipReason::BelowMargin));
+            }
         };
+        let winning_token = if winner == "Up" { m5_up } else { m5_down };
         info!(
-            "Sweep {}: winner={} | price=${} ptb=${} diff={}",
-            symbol, winner, latest_price, price_to_beat, diff
+            "Sweep {}: winner={} | price={} ptb={} diff={}",
+            symbol, winner, usd(latest_price), usd(price_to_beat), usd_diff(diff, price_to_beat)
         );
-        self.log_buffer.push(symbol, "info", format!("sweep winner={} (price=${}, ptb=${}, diff={})", winner, latest_price, price_to_beat, diff)).await;
+        self.log_buffer.push(symbol, "info", format!(
+            "sweep winner={} (price={}, ptb={}, diff={})",
+            winner, usd(latest_price), usd(price_to_beat), usd_diff(diff, price_to_beat)
+        )).await;
+        if winning_token.is_empty() || !winning_token.bytes().all(|b| b.is_ascii_digit()) {
+            error!("Sweep {}: winning token ID {:?} is not a decimal token ID, skipping", symbol, short(winning_token));
+            self.log_buffer.push(symbol, "error", format!("invalid {} token ID, sweep skipped", winner)).await;
+            return Ok(SweepOutcome::skipped(SkipReason::InvalidToken));
+        }
+        if cfg.is_denylisted(&round.condition_id, &[m5_up, m5_down]) {
+            info!("Sweep {}: market {} is denylisted, skipping", symbol, round.condition_id);
+            self.log_buffer.push(symbol, "warn", "market denylisted, sweep skipped".to_string()).await;
+            return Ok(SweepOutcome::skipped(SkipReason::Denylisted));
+        }
+        let simulated = cfg.simulation_mode || self.monitor_mode() || self.warming_up();
+        if self.monitor_mode() {
+            info!("Sweep {}: monitor mode (no private key), fills are simulated against the observed book", symbol);
+        } else if cfg.simulation_mode {
+            info!("Sweep {}: simulation mode, fills are simulated against the observed book", symbol);
+        } else if self.warming_up() {
+            info!(
+                "Sweep {}: warmup ({} round(s) before live), fills are simulated against the observed book",
+                symbol,
+                self.warmup_rounds_left.load(Ordering::Relaxed)
+            );
+        } else if self.control.is_paused() {
+            info!("Sweep {}: trading paused, skipping live orders", symbol);
+            self.log_buffer.push(symbol, "warn", "trading paused, sweep skipped".to_string()).await;
+            return Ok(SweepOutcome::skipped(SkipReason::Paused));
+        } else if let Some(until) = self.control.breaker_open_until(Utc::now().timestamp()) {
+            info!("Sweep {}: network-error circuit breaker open for {}s more, skipping live orders", symbol, until - Utc::now().timestamp());
+            self.log_buffer.push(symbol, "warn", "circuit breaker open, sweep skipped".to_string()).await;
+            return Ok(SweepOutcome::skipped(SkipReason::CircuitOpen));
+        } else if !self.api.is_authenticated() {
+            info!("Sweep {}: not authenticated, monitor-only", symbol);
+            self.log_buffer.push(symbol, "warn", "not authenticated, sweep skipped (monitor-only)".to_string()).await;
+            return Ok(SweepOutcome::skipped(SkipReason::Unauthenticated));
+        }
+
+        if !simulated && cfg.require_source_agreement_for_live && !self.rpc_confirms_winner(round, winner).await {
+            self.log_buffer.push(symbol, "warn", "sources disagree on winner, skipping live orders".to_string()).await;
+            return Ok(SweepOutcome::skipped(SkipReason::SourcesDisagree));
+        }
+
+        let held = holdings.get(winning_token).copied().unwrap_or(0.0);
+        let max_cost = self.max_sweep_cost(cfg);
+        if max_cost != cfg.max_sweep_cost {
+            info!("Sweep {}: budget ${:.2} set from the dashboard (config ${:.2})", symbol, max_cost, cfg.max_sweep_cost);
+        }
+        let budget = effective_sweep_budget(cfg, max_cost, held);
+        if held > 0.0 || budget != max_cost {
+            info!("Sweep {}: already holding ${:.2} of {}, budget ${:.2}", symbol, held, winner, budget);
+            self.log_buffer.push(symbol, "debug", format!("holding ${:.2} of {}, budget ${:.2}", held, winner, budget)).await;
+        }
+        if budget <= 0.0 {
+            info!("Sweep {}: no budget left after existing holdings, skipping", symbol);
+            return Ok(SweepOutcome::skipped(SkipReason::NoBudget));
+        }
 
+        let precision = self.precisions.read().await.get(winning_token).copied().unwrap_or_default();
         let sweep_start = std::time::Instant::now();
         let timeout = Duration::from_secs(cfg.sweep_timeout_secs);
         let mut total_orders: u32 = 0;
+        // FOK orders sent (filled or not), for max_orders_per_sweep.
+        let mut attempted_orders: u32 = 0;
         let mut total_shares: f64 = 0.0;
         let mut total_cost: f64 = 0.0;
         let mut consecutive_empty_passes: u32 = 0;
+        let empty_pass_wait = Duration::from_millis(cfg.sweep_empty_pass_wait_ms);
+        // The depth check only applies to the first book we get.
+        let mut first_pass = true;
+        let mut skipped = None;
+        // Requested minus paid, summed over fills that reported their amounts.
+        let mut price_improvement: f64 = 0.0;
+        let mut improvement_fills: u32 = 0;
+        // Set when the sweep must stop entirely (ambiguous network error, trading paused).
+        let mut halted = false;
+        // Live attempted vs filled per ask price bucket for this round.
+        let mut round_fills = FillHistogram::default();
+        // Simulation only: shares already "bought" per price level, so the unchanged book
+        // isn't re-filled on every pass.
+        let mut simulated_taken: HashMap<Decimal, f64> = HashMap::new();
+        // (price, size) levels already targeted since the last WS book update; re-sending a FOK
+        // against an unchanged level just burns a rejection.
+        let mut attempted_levels: HashSet<(Decimal, Decimal)> = HashSet::new();
+        let mut levels_book_version = self.orderbook_mirror.update_count();
+        // Book version a crossed/locked warning was last logged for, so a stuck book warns once.
+        let mut crossed_warned_version: Option<u64> = None;
+        let deadline_ms = cfg.sweep_hard_deadline_ms.map(|ms| round.close_time * 1000 + ms as i64);
+        let past_deadline = || deadline_ms.is_some_and(|d| Utc::now().timestamp_millis() >= d);
+        let mut deadline_hit = false;
 
         while sweep_start.elapsed() < timeout {
-            if total_cost >= cfg.max_sweep_cost {
-                debug!("Sweep {}: reached max_sweep_cost ${}, stopping.", symbol, cfg.max_sweep_cost);
+            if past_deadline() {
+                deadline_hit = true;
+                break;
+            }
+            if total_cost >= budget {
+                debug!("Sweep {}: reached budget {}, stopping.", symbol, dollars(budget));
                 break;
             }
 
             let orderbook = match self.orderbook_mirror.get_orderbook(winning_token).await {
                 Some(ob) => ob,
                 None => {
-                    debug!("Sweep {}: no orderbook in WS mirror, waiting...", symbol);
-                    self.orderbook_mirror.wait_for_update(Duration::from_secs(3)).await;
+                    debug!(
+                        "Sweep {}: no orderbook in WS mirror ({} updates since subscribe), waiting...",
+                        symbol, self.orderbook_mirror.update_count()
+                    );
+                    self.orderbook_mirror.wait_for_update(until_deadline(deadline_ms, Duration::from_secs(3))).await;
                     continue;
                 }
             };
 
-            let mut eligible_asks: Vec<_> = orderbook
-                .asks
-                .iter()
-                .filter(|a| {
-                    let p = a.price.to_string().parse::<f64>().unwrap_or(1.0);
-                    p <= cfg.sweep_max_price
-                })
-                .collect();
-            eligible_asks.sort_by(|a, b| b.price.cmp(&a.price));
+            let book_version = self.orderbook_mirror.update_count();
+            if book_version != levels_book_version {
+                attempted_levels.clear();
+                levels_book_version = book_version;
+            }
 
-            if eligible_asks.is_empty() {
+            if let Some((best_bid, best_ask)) = crossed_book(&orderbook) {
+                // Ask at or below the bid can't rest on the CLOB: wait for the WS to correct it
+                // rather than firing FOKs at a price that isn't really there.
+                if crossed_warned_version != Some(book_version) {
+                    crossed_warned_version = Some(book_version);
+                    let kind = if best_ask == best_bid { "locked" } else { "crossed" };
+                    warn!("Sweep {}: {} book (bid {} >= ask {}), waiting for the next update", symbol, kind, best_bid, best_ask);
+                    self.log_buffer.push(symbol, "warn", format!("{} book: bid {} >= ask {}, pass skipped", kind, best_bid, best_ask)).await;
+                }
                 consecutive_empty_passes += 1;
-                if consecutive_empty_passes >= 3 {
+                if consecutive_empty_passes >= cfg.sweep_empty_pass_limit {
                     break;
                 }
-                self.orderbook_mirror.wait_for_update(Duration::from_secs(3)).await;
+                self.orderbook_mirror.wait_for_update(until_deadline(deadline_ms, empty_pass_wait)).await;
                 continue;
             }
 
-            let mut filled_any = false;
-            for ask in &eligible_asks {
-                if sweep_start.elapsed() >= timeout {
+            let eligible_asks = orderbook.eligible_asks(0.0, cfg.sweep_max_price);
+            if first_pass {
+                first_pass = false;
+                let depth = orderbook.total_notional(0.0, cfg.sweep_max_price);
+                if depth < cfg.min_sweepable_notional {
+                    info!(
+                        "Sweep {}: only ${:.2} of eligible asks (min ${:.2}), skipping.",
+                        symbol, depth, cfg.min_sweepable_notional
+                    );
+                    self.log_buffer.push(symbol, "info", format!(
+                        "book too thin: ${:.2} eligible < ${:.2}, sweep skipped", depth, cfg.min_sweepable_notional
+                    )).await;
+                    skipped = Some(SkipReason::ThinBook);
                     break;
                 }
-                if total_cost >= cfg.max_sweep_cost {
+            }
+
+            if eligible_asks.is_empty() {
+                consecutive_empty_passes += 1;
+                if consecutive_empty_passes >= cfg.sweep_empty_pass_limit {
                     break;
                 }
+                self.orderbook_mirror.wait_for_update(until_deadline(deadline_ms, empty_pass_wait)).await;
+                continue;
+            }
 
-                let price_str = format!("{}", ask.price);
-                let ask_price: f64 = price_str.parse().unwrap_or(1.0);
-                let ask_size: f64 = ask.size.to_string().parse().unwrap_or(0.0);
+            let mut filled_any = false;
+            for ask in &eligible_asks {
+                if sweep_start.elapsed() >= timeout {
+                    break;
+                }
+                if past_deadline() {
+                    deadline_hit = true;
+                    break;
+                }
+                if total_cost >= budget {
+                    break;
+                }
+                if let Some(max_orders) = cfg.max_orders_per_sweep {
+                    if attempted_orders >= max_orders {
+                        info!("Sweep {}: order-count cap hit ({} orders), stopping.", symbol, max_orders);
+                        halted = true;
+                        break;
+                    }
+                }
 
-                let remaining_budget = cfg.max_sweep_cost - total_cost;
-                let max_affordable = if ask_price > 0.0 {
-                    remaining_budget / ask_price
-                } else {
-                    0.0
-                };
-                let order_size = ask_size.min(max_affordable);
-                let order_size = (order_size * 100.0).floor() / 100.0;
-                if order_size < 0.01 {
+                if attempted_levels.contains(&(ask.price, ask.size)) {
+                    debug!("Sweep {}: already tried {} @ {} on this book, skipping", symbol, ask.size, ask.price);
                     continue;
                 }
-                let size_str = format!("{:.2}", order_size);
+                if let Some(min_edge) = cfg.min_edge_at_fill_pct {
+                    let now_price = self.latest_prices.read().await.get(symbol).map(|(p, _, _)| *p);
+                    if !now_price.is_some_and(|p| edge_holds(winner, price_to_beat, p, min_edge)) {
+                        let now_str = now_price.map_or("none".to_string(), usd);
+                        warn!(
+                            "Sweep {}: latest price {} no longer favours {} by {}% of ptb {}, stopping after {} FOKs.",
+                            symbol, now_str, winner, min_edge * 100.0, usd(price_to_beat), attempted_orders
+                        );
+                        self.log_buffer.push(symbol, "warn", format!(
+                            "edge lost: price {} vs ptb {}, sweep stopped after {} FOKs", now_str, usd(price_to_beat), attempted_orders
+                        )).await;
+                        if attempted_orders == 0 {
+                            skipped = Some(SkipReason::EdgeLost);
+                        }
+                        halted = true;
+                        break;
+                    }
+                }
 
-                info!("Sweep {}: FOK BUY {} @ {} (ask size={})", symbol, size_str, price_str, ask.size);
+                let Some(mut ask_size) = ask.size.to_f64() else { continue };
+                if simulated {
+                    ask_size -= simulated_taken.get(&ask.price).copied().unwrap_or(0.0);
+                }
+                let Some(ask_price) = ask.price.to_f64() else { continue };
+                // A fill costs up to the buffered limit, so that's what sizes the order and counts
+                // against the budget when the response doesn't report the cost; fill-rate stats
+                // and price improvement use the observed ask.
+                let limit = precision.limit_price(ask.price, cfg.fok_price_buffer, cfg.sweep_max_price);
+                let Some(FokOrder { size: order_size, price: limit_f64, price_str, size_str }) =
+                    precision.fok_order(limit, ask_size, budget - total_cost, cfg.min_order_notional)
+                else {
+                    continue;
+                };
+
+                let buffered = if limit > ask.price { format!(", observed ask {}", ask.price) } else { String::new() };
+                info!(
+                    "Sweep {}: {}FOK BUY {} @ {} (ask size={}{})",
+                    symbol, if simulated { "[SIM] " } else { "" }, size_str, price_str, ask.size, buffered
+                );
+                if attempted_orders == 0 {
+                    let after_close_ms = Utc::now().timestamp_millis() - round.close_time * 1000;
+                    info!("Sweep {}: first FOK {}ms after close", symbol, after_close_ms);
+                    self.log_buffer.push(symbol, "debug", format!("first FOK {}ms after close", after_close_ms)).await;
+                }
+
+                let result = if simulated {
+                    // The observed ask covers order_size, so a FOK at this price would fill.
+                    *simulated_taken.entry(ask.price).or_insert(0.0) += order_size;
+                    Ok(Some(OrderResponse {
+                        order_id: Some(format!("sim-{}", total_orders + 1)),
+                        status: "SIMULATED".to_string(),
+                        message: None,
+                        filled_shares: None,
+                        filled_cost: None,
+                    }))
+                } else if self.control.is_paused() {
+                    info!("Sweep {}: trading paused mid-sweep, stopping", symbol);
+                    halted = true;
+                    break;
+                } else {
+                    self.api.place_fok_buy(winning_token, &size_str, &price_str).await
+                };
+
+                attempted_orders += 1;
+                attempted_levels.insert((ask.price, ask.size));
+                if !simulated && matches!(result, Ok(_) | Err(ApiError::Rejected(_))) {
+                    round_fills.record(ask_price, matches!(result, Ok(Some(_))));
+                }
+                if !simulated && result.is_ok() {
+                    self.control.record_order_answered();
+                }
 
-                match self.api.place_fok_buy(winning_token, &size_str, &price_str).await {
+                match result {
                     Ok(Some(resp)) => {
                         total_orders += 1;
                         total_shares += order_size;
-                        total_cost += order_size * ask_price;
+                        total_cost += resp.filled_cost.unwrap_or(order_size * limit_f64);
                         filled_any = true;
                         info!(
-                            "Sweep {}: FILLED #{} (id={}) +{} @ {} (cost=${})",
+                            "Sweep {}: FILLED #{} (id={}) +{} @ {} (cost={})",
                             symbol, total_orders,
                             resp.order_id.as_deref().unwrap_or("?"),
-                            order_size, price_str, total_cost
+                            order_size, price_str, dollars(total_cost)
                         );
+                        if let (true, Some(effective)) = (cfg.log_price_improvement, resp.effective_price()) {
+                            let shares = resp.filled_shares.unwrap_or(order_size);
+                            let improvement = (ask_price - effective) * shares;
+                            price_improvement += improvement;
+                            improvement_fills += 1;
+                            info!(
+                                "Sweep {}: fill #{} requested {} effective {} (improvement ${:.4})",
+                                symbol, total_orders, price_str, token_price(effective), improvement
+                            );
+                        }
                     }
                     Ok(None) => {
                         debug!("Sweep {}: FOK not fillable @ {}", symbol, price_str);
                     }
-                    Err(e) => {
-                        error!("Sweep {}: FOK network error, halting: {}", symbol, e);
+                    Err(e @ (ApiError::NetworkAmbiguous(_) | ApiError::Auth(_))) => {
+                        error!("Sweep {}: FOK {}, halting", symbol, e);
+                        if matches!(e, ApiError::NetworkAmbiguous(_)) {
+                            self.record_network_error(symbol, cfg).await;
+                        }
+                        if matches!(e, ApiError::Auth(_)) && !self.api.is_authenticated() {
+                            self.log_buffer.push(symbol, "error", "re-authentication failed, monitor-only until restart".to_string()).await;
+                            self.notifier.notify(
+                                notify::EVENT_AUTH_FAILURE, symbol,
+                                format!("{}: re-authentication failed, continuing monitor-only: {}", symbol, e),
+                                serde_json::Value::Null,
+                            );
+                        }
+                        halted = true;
                         break;
                     }
+                    Err(e @ (ApiError::Rejected(_) | ApiError::Parse(_))) => {
+                        debug!("Sweep {}: FOK @ {} not placed: {}", symbol, price_str, e);
+                    }
+                    Err(e @ ApiError::RateLimited(_)) => {
+                        warn!("Sweep {}: FOK @ {} {}", symbol, price_str, e);
+                    }
                 }
 
                 sleep(Duration::from_millis(cfg.sweep_inter_order_delay_ms)).await;
             }
 
+            if halted || deadline_hit {
+                break;
+            }
             if filled_any {
                 consecutive_empty_passes = 0;
             } else {
                 consecutive_empty_passes += 1;
-                if consecutive_empty_passes >= 3 {
+                if consecutive_empty_passes >= cfg.sweep_empty_pass_limit {
                     break;
                 }
-                self.orderbook_mirror.wait_for_update(Duration::from_secs(3)).await;
+                self.orderbook_mirror.wait_for_update(until_deadline(deadline_ms, empty_pass_wait)).await;
             }
         }
 
-        info!("Sweep {} complete: {} orders, {} shares, ${} cost", symbol, total_orders, total_shares, total_cost);
-        self.log_buffer.push(symbol, "info", format!("sweep done: {} orders, {} shares, ${} cost", total_orders, total_shares, total_cost)).await;
-        Ok((total_orders, total_shares, total_cost))
+        if deadline_hit {
+            warn!(
+                "Sweep {}: hard deadline ({}ms after close) hit {}ms into the sweep: {} FOKs sent, {} filled, ${:.2} of ${:.2} budget",
+                symbol, cfg.sweep_hard_deadline_ms.unwrap_or(0), sweep_start.elapsed().as_millis(),
+                attempted_orders, total_orders, total_cost, budget
+            );
+            self.log_buffer.push(symbol, "warn", format!(
+                "hard deadline hit {}ms into sweep ({} FOKs sent, ${:.2} spent)",
+                sweep_start.elapsed().as_millis(), attempted_orders, total_cost
+            )).await;
+        }
+        if improvement_fills > 0 {
+            info!("Sweep {}: price improvement ${:.4} over {} fills", symbol, price_improvement, improvement_fills);
+            self.log_buffer.push(symbol, "debug", format!(
+                "price improvement ${:.4} over {} fills", price_improvement, improvement_fills
+            )).await;
+        }
+        if !round_fills.is_empty() {
+            debug!("Sweep {} fill rate by price: {}", symbol, round_fills.summary());
+            self.metrics.record_round_fills(&round_fills);
+        }
+        // Simulated sweeps (paper, monitor mode, warmup) placed nothing, so they don't alert.
+        if total_orders > 0 && !simulated {
+            self.notifier.notify(
+                notify::EVENT_SWEEP_EXECUTED, symbol,
+                format!("{} swept {}: {} orders, {:.2} shares, ${:.2}", symbol, winner, total_orders, total_shares, total_cost),
+                serde_json::json!({
+                    "winner": winner,
+                    "orders": total_orders,
+                    "shares": total_shares,
+                    "cost": total_cost,
+                }),
+            );
+        }
+        let mode = if simulated { " (simulated)" } else { "" };
+        info!("Sweep {} complete{}: {} orders, {:.2} shares, {} cost", symbol, mode, total_orders, total_shares, dollars(total_cost));
+        self.log_buffer.push(symbol, "info", format!(
+            "sweep done{}: {} orders, {:.2} shares, {} cost", mode, total_orders, total_shares, dollars(total_cost)
+        )).await;
+        Ok(SweepOutcome { orders: total_orders, shares: total_shares, cost: total_cost, skipped, live: !simulated })
     }
 
-    /// Unified loop: discover all symbols, subscribe at T-5s, sweep after close.
-    pub async fn run(&self) -> Result<()> {
-        let symbols = &self.config.strategy.symbols;
-        let cfg = &self.config.strategy;
-        info!("5m bot started | symbols: {:?} | sweep={}", symbols, cfg.sweep_enabled);
-
-        // Start RTDS price feed
-        let rtds_url = self.config.polymarket.rtds_ws_url.clone();
-        let cache_5 = Arc::clone(&self.price_cache_5);
-        let latest = Arc::clone(&self.latest_prices);
-        let symbols_rtds = symbols.clone();
-        if let Err(e) = run_chainlink_multi_poller(rtds_url, symbols_rtds, cache_5, latest).await {
+    /// Start the RTDS feed: chainlink prices, plus last trade prints for the traded tokens.
+    /// Returns the trade-print handler; the caller sets its tokens each round.
+    async fn start_price_feeds(&self) -> Arc<LastTradeHandler> {
+        let rtds_socket = RtdsSocketConfig::from_config(&self.config.polymarket);
+        let (rtds_tx, rtds_rx) = mpsc::unbounded_channel();
+        let chainlink: Arc<dyn RtdsHandler> = Arc::new(ChainlinkHandler {
+            symbols: Arc::clone(&self.symbols),
+            capture: self.ptb_capture.clone(),
+            price_cache_5: Arc::clone(&self.price_cache_5),
+            latest_prices: Arc::clone(&self.latest_prices),
+            recorder: self.recorder.clone(),
+            metrics: Arc::clone(&self.metrics),
+        });
+        let last_trades = Arc::new(LastTradeHandler::new(rtds_tx, Arc::clone(&self.metrics)));
+        let mut handlers = vec![chainlink];
+        if self.config.polymarket.rtds_trade_prints {
+            handlers.push(Arc::clone(&last_trades) as Arc<dyn RtdsHandler>);
+        }
+        if let Err(e) = run_chainlink_multi_poller(rtds_socket, handlers, rtds_rx, Arc::clone(&self.metrics)).await {
             warn!("RTDS WS poller start failed: {}", e);
         }
+        last_trades
+    }
+
+    /// Unified loop: discover all symbols, subscribe at T-5s, sweep after close. With `once`,
+    /// returns after the first round's resolutions are logged (an error if nothing was discovered).
+    pub async fn run(&self, once: bool) -> Result<()> {
+        let startup = &self.config.strategy;
+        let discovery_every = Duration::from_secs(startup.symbol_discovery_interval_secs);
+        let mut last_symbol_discovery = None;
+        if startup.auto_discover_symbols {
+            self.refresh_symbols().await;
+            last_symbol_discovery = Some(Instant::now());
+        }
+        let symbols = self.symbols();
+        info!("5m bot started | symbols: {:?} | sweep={}", symbols, startup.sweep_enabled);
+        if self.monitor_mode() {
+            info!("Monitor mode: no private key, sweeps are simulated and no orders are signed or sent");
+        }
+        for symbol in &symbols {
+            if let Some(o) = startup.per_symbol.iter().find(|(k, _)| k.eq_ignore_ascii_case(symbol)).map(|(_, o)| o) {
+                info!("{} strategy overrides: {:?}", symbol, o);
+            }
+        }
+
+        let last_trades = self.start_price_feeds().await;
         sleep(Duration::from_secs(2)).await;
 
         loop {
+            if last_symbol_discovery.is_some_and(|at: Instant| at.elapsed() >= discovery_every) {
+                self.refresh_symbols().await;
+                last_symbol_discovery = Some(Instant::now());
+            }
+            let symbols = self.symbols();
+            // Config for this whole round; a hot reload applies from the next one.
+            let settings = self.settings();
+            let cfg = &settings.base;
+
             // === Phase 1: Discover all markets early in the period ===
             // Retry discovery with a timeout to wait for RTDS prices to arrive.
             let period_5 = current_5m_period_start();
             let mut rounds: Vec<SymbolRound> = Vec::new();
+            self.publish_schedule(period_5, &rounds, false).await;
             let discovery_deadline = std::time::Instant::now() + Duration::from_secs(PRICE_WAIT_TIMEOUT_SECS);
 
             loop {
                 rounds.clear();
-                for symbol in symbols {
-                    match self.discover_symbol(symbol).await {
+                // Discover symbols concurrently with a small jitter each; the API's request
+                // semaphore bounds how many lookups are actually in flight.
+                let results = join_all(symbols.iter().enumerate().map(|(i, symbol)| async move {
+                    sleep(discovery_jitter(i, cfg.discovery_jitter_ms)).await;
+                    (symbol, self.discover_symbol(symbol, period_5).await)
+                }))
+                .await;
+                for (symbol, result) in results {
+                    match result {
                         Ok(Some(round)) => rounds.push(round),
                         Ok(None) => {}
                         Err(e) => error!("{} discovery error: {}", symbol, e),
@@ -344,6 +1524,9 @@ impl ArbStrategy {
             }
 
             if rounds.is_empty() {
+                if once {
+                    anyhow::bail!("No markets discovered for period {}", period_5);
+                }
                 warn!("No markets discovered for period {}, sleeping until next", period_5);
                 let remaining = (period_5 + MARKET_5M_DURATION_SECS) - Utc::now().timestamp();
                 if remaining > 0 {
@@ -353,51 +1536,156 @@ impl ArbStrategy {
             }
 
             info!("Discovered {}/{} markets for period {}", rounds.len(), symbols.len(), period_5);
+            self.log_warmup().await;
 
             // === Phase 2: Pre-warm order cache ===
-            if self.api.is_authenticated() {
+            // With `prioritize_likely_winner` this waits for the pre-close wake instead.
+            if !cfg.prioritize_likely_winner {
+                let all: Vec<(&str, &str)> = rounds
+                    .iter()
+                    .flat_map(|r| [(r.symbol.as_str(), r.up_token.as_str()), (r.symbol.as_str(), r.down_token.as_str())])
+                    .collect();
+                self.warm_order_cache(&all).await;
+            }
+
+            // === Phase 2b: Subscribe orderbook WS for all tokens ===
+            let tokens: Vec<&str> = rounds
+                .iter()
+                .flat_map(|r| [r.up_token.as_str(), r.down_token.as_str()])
+                .collect();
+            match self.orderbook_mirror.subscribe(&tokens).await {
+                Ok(()) => self.publish_schedule(period_5, &rounds, true).await,
+                Err(e) => {
+                    warn!("Orderbook WS subscribe failed: {}", e);
+                    self.publish_schedule(period_5, &rounds, false).await;
+                }
+            }
+            if cfg.orderbook_rest_poll_secs > 0 {
+                // Tracked by the mirror, so `unsubscribe_all` stops it with the WS task.
+                self.orderbook_mirror.track_task(spawn_book_poller(
+                    Arc::clone(&self.api),
+                    Arc::clone(&self.orderbook_mirror),
+                    tokens.iter().map(|t| t.to_string()).collect(),
+                    Duration::from_secs(cfg.orderbook_rest_poll_secs),
+                ));
+            }
+            if self.config.polymarket.rtds_trade_prints {
+                last_trades.set_tokens(tokens.iter().map(|t| t.to_string()).collect());
+            }
+            let snapshot_task = self.recorder.clone().map(|rec| {
+                let mirror = Arc::clone(&self.orderbook_mirror);
+                let every = Duration::from_millis(cfg.record_orderbook_interval_ms.max(100));
+                tokio::spawn(async move {
+                    loop {
+                        sleep(every).await;
+                        for (token_id, book) in mirror.snapshot_all().await {
+                            rec.record(&RecordedEvent::orderbook(&token_id, book)).await;
+                        }
+                    }
+                })
+            });
+
+            let holdings = self.fetch_holdings(&rounds).await;
+
+            // === Phase 3: Wait for period close ===
+            // Wake `pre_close_lead_ms` early to make sure every book is in the mirror,
+            // then wake again exactly at close so the sweep starts without drift. Markets
+            // normally share one end date; if not, wait for the last so none is swept early.
+            let close_time = rounds.iter().map(|r| r.close_time).max().unwrap_or(period_5 + MARKET_5M_DURATION_SECS);
+            let close_at = close_instant(close_time);
+            let lead = Duration::from_millis(cfg.pre_close_lead_ms);
+            debug!("Waiting {:?} until close", close_at.saturating_duration_since(Instant::now()));
+            sleep_until(close_at.checked_sub(lead).unwrap_or(close_at)).await;
+            // Tokens whose books must be in the mirror before close: only the expected winners
+            // when prioritizing, falling back to both sides when there is no price yet.
+            let mut margins: HashMap<String, f64> = HashMap::new();
+            let ready_tokens: Vec<(&str, &str)> = if cfg.prioritize_likely_winner {
+                let mut picked = Vec::new();
                 for round in &rounds {
-                    for token in [&round.up_token, &round.down_token] {
-                        if let Err(e) = self.api.warm_order_cache(token).await {
-                            debug!("{} cache warm failed for {}..): {}", round.symbol, &token[..token.len().min(12)], e);
+                    match self.likely_winner(round).await {
+                        Some((token, margin)) => {
+                            margins.insert(round.symbol.clone(), margin);
+                            picked.push((round.symbol.as_str(), token));
+                        }
+                        None => {
+                            picked.push((round.symbol.as_str(), round.up_token.as_str()));
+                            picked.push((round.symbol.as_str(), round.down_token.as_str()));
                         }
                     }
                 }
+                self.warm_order_cache(&picked).await;
+                picked
+            } else {
+                tokens.iter().map(|t| ("", *t)).collect()
+            };
+            while Instant::now() < close_at {
+                let mut missing = false;
+                for (_, token) in &ready_tokens {
+                    if self.orderbook_mirror.get_orderbook(token).await.is_none() {
+                        missing = true;
+                        break;
+                    }
+                }
+                if !missing {
+                    break;
+                }
+                let left = close_at.saturating_duration_since(Instant::now());
+                self.orderbook_mirror.wait_for_update(left).await;
+            }
+            self.presign_sweep_orders(&rounds, &holdings).await;
+            sleep_until(close_at).await;
+            info!("Period {} closed", period_5);
+            if let Some(since) = self.orderbook_mirror.since_subscribe().await {
+                debug!("Orderbook WS: {} updates in {:?} since subscribe", self.orderbook_mirror.update_count(), since);
             }
 
-            // === Phase 3: Wait for period close ===
-            let close_time = period_5 + MARKET_5M_DURATION_SECS;
-            let remaining = close_time - Utc::now().timestamp();
-            if remaining > 0 {
-                debug!("Waiting {}s until close", remaining);
-                sleep(Duration::from_secs(remaining as u64)).await;
+            // Books at close, kept until resolution for `book_snapshot_dir`.
+            let close_books = if cfg.book_snapshot_dir.is_some() || self.recorder.is_some() {
+                self.orderbook_mirror.snapshot_all().await
+            } else {
+                HashMap::new()
+            };
+            let close_books_ms = Utc::now().timestamp_millis();
+            if let Some(rec) = &self.recorder {
+                for (token_id, book) in &close_books {
+                    rec.record(&RecordedEvent::orderbook(token_id, book.clone())).await;
+                }
+                for round in &rounds {
+                    rec.record(&RecordedEvent::Round {
+                        ts_ms: Utc::now().timestamp_millis(),
+                        symbol: round.symbol.clone(),
+                        period_5: round.period_5,
+                        condition_id: round.condition_id.clone(),
+                        up_token: round.up_token.clone(),
+                        down_token: round.down_token.clone(),
+                        price_to_beat: round.price_to_beat,
+                    })
+                    .await;
+                }
             }
-            info!("Period {} closed", period_5);
 
             // === Phase 6: Paper trade + sweep each symbol ===
-            let mut predictions: Vec<PredictionRecord> = Vec::new();
-            for round in &rounds {
-                // Paper trade log
-                if let Some(pred) = self.paper_trader
-                    .log(&round.symbol, round.period_5, round.price_to_beat, &round.condition_id)
-                    .await
-                {
-                    predictions.push(pred);
-                }
-
-                // Sweep
-                if cfg.sweep_enabled {
-                    if let Err(e) = self
-                        .sweep_stale_asks(&round.symbol, round.price_to_beat, &round.up_token, &round.down_token)
-                        .await
-                    {
-                        error!("Sweep {} error: {}", round.symbol, e);
-                    }
-                }
+            let mut order: Vec<&SymbolRound> = rounds.iter().collect();
+            if cfg.prioritize_likely_winner {
+                // Largest pre-close margin first: those are the sweeps most likely to pay.
+                order.sort_by(|a, b| {
+                    let m = |r: &SymbolRound| margins.get(&r.symbol).copied().unwrap_or(0.0);
+                    m(b).total_cmp(&m(a))
+                });
             }
+            let (predictions, sweeps) = self.log_and_sweep(&order, &holdings, &settings).await;
+
+            self.finish_warmup_round().await;
 
             // === Phase 7: Cleanup ===
-            self.orderbook_mirror.unsubscribe_all().await;
+            if let Some(task) = snapshot_task {
+                task.abort();
+            }
+            self.release_orderbook(period_5, &rounds, false, cfg).await;
+            self.api.clear_presigned();
+            self.precisions.write().await.clear();
+
+            self.record_price_agreement(&predictions).await;
 
             // Poll resolution for all markets (in parallel)
             let mut resolution_handles = Vec::new();
@@ -419,15 +1707,14 @@ impl ArbStrategy {
                         }
                         match api.get_market(&cid).await {
                             Ok(m) => {
-                                let winner = m.tokens.iter().find(|t| t.winner).map(|t| {
-                                    if t.outcome.to_uppercase().contains("UP") || t.outcome == "1" {
-                                        "Up".to_string()
-                                    } else {
-                                        "Down".to_string()
+                                let winner = m.tokens.iter().find(|t| t.winner).and_then(|t| {
+                                    let side = api.outcome_mapping().side(&t.outcome);
+                                    if side.is_none() {
+                                        warn!("{} winner outcome {:?} matches no Up/Down mapping", symbol, t.outcome);
                                     }
+                                    side.map(|s| s.label().to_string())
                                 });
-                                if m.closed && winner.is_some() {
-                                    let w = winner.unwrap();
+                                if let (true, Some(w)) = (m.closed, winner) {
                                     info!("{} resolved: {}", symbol, w);
                                     return (symbol, Some((w, m.question)));
                                 }
@@ -441,17 +1728,1119 @@ impl ArbStrategy {
             // Wait for all resolutions and log results
             for handle in resolution_handles {
                 if let Ok((symbol, result)) = handle.await {
+                    if let Some(rec) = &self.recorder {
+                        rec.record(&RecordedEvent::Resolution {
+                            ts_ms: Utc::now().timestamp_millis(),
+                            symbol: symbol.clone(),
+                            period_5,
+                            winner: result.as_ref().map(|(w, _)| w.clone()),
+                            question: result.as_ref().map(|(_, q)| q.clone()),
+                        })
+                        .await;
+                    }
+                    if let (Some(dir), Some((winner, _))) = (&cfg.book_snapshot_dir, &result) {
+                        self.write_winner_book(dir, &rounds, &symbol, winner, &close_books, close_books_ms);
+                    }
                     if let Some(pred) = predictions.iter().find(|p| p.symbol == symbol) {
                         let (actual, question) = match &result {
                             Some((w, q)) => (Some(w.as_str()), Some(q.as_str())),
                             None => (None, None),
                         };
-                        self.paper_trader.log_resolution(pred, actual, question).await;
+                        if let (Some(actual), Some(sweep)) = (actual, sweeps.get(&symbol)) {
+                            self.record_sweep_pnl(&symbol, sweep, actual == pred.prediction, cfg).await;
+                        }
+                        if let Some(actual) = actual.filter(|a| *a != pred.prediction) {
+                            let (orders, shares, cost) = sweeps.get(&symbol).map(SweepOutcome::totals).unwrap_or((0, 0.0, 0.0));
+                            self.notifier.notify(
+                                notify::EVENT_WRONG_WINNER, &symbol,
+                                format!("{} predicted {} but resolved {} (swept {} orders, ${:.2})", symbol, pred.prediction, actual, orders, cost),
+                                serde_json::json!({
+                                    "period": period_5,
+                                    "predicted": pred.prediction,
+                                    "actual": actual,
+                                    "orders": orders,
+                                    "shares": shares,
+                                    "cost": cost,
+                                }),
+                            );
+                        }
+                        let post_close = self.post_close_trades(&rounds, pred).await;
+                        self.paper_trader
+                            .log_resolution(pred, actual, question, sweeps.get(&symbol), post_close.as_ref())
+                            .await;
                     }
                 }
             }
 
+            self.release_orderbook(period_5, &rounds, true, cfg).await;
+
+            if once {
+                info!("Single round complete (--once), exiting");
+                return Ok(());
+            }
             sleep(Duration::from_secs(5)).await;
         }
     }
+
+    /// Replay recorded events (see `recorder`) through the same price capture, discovery, paper
+    /// logger and sweep path as the live loop. Discovery looks markets up through the strategy's
+    /// API, which for a backtest is a `ReplayApi` over the same events. Orders are never placed:
+    /// callers force `simulation_mode`.
+    pub async fn run_backtest(&self, events: Vec<RecordedEvent>) -> Result<()> {
+        let cfg = &self.config.strategy;
+        let symbol_set: HashSet<String> = cfg.symbols.iter().map(|s| s.to_lowercase()).collect();
+        let mut pending: HashMap<(String, i64), PredictionRecord> = HashMap::new();
+        let mut sweeps: HashMap<(String, i64), SweepOutcome> = HashMap::new();
+        let mut rounds: u32 = 0;
+        info!("Backtest: replaying {} events | symbols: {:?}", events.len(), cfg.symbols);
+
+        for event in events {
+            match event {
+                RecordedEvent::Rtds { raw, .. } => {
+                    apply_chainlink_message(&raw, &symbol_set, &self.ptb_capture, &self.price_cache_5, &self.latest_prices).await;
+                }
+                RecordedEvent::Orderbook { token_id, book, .. } => {
+                    self.orderbook_mirror.set_orderbook(&token_id, book).await;
+                }
+                RecordedEvent::Round { symbol, period_5, .. } => {
+                    if !symbol_set.contains(&symbol) {
+                        continue;
+                    }
+                    // Same discovery as live: the PTB captured from replayed RTDS first, cross-
+                    // checked against the recorded one, which stands in for the market's own.
+                    let round = match self.discover_symbol(&symbol, period_5).await {
+                        Ok(Some(round)) => round,
+                        Ok(None) => {
+                            warn!("Backtest {} period {}: not discovered, skipping", symbol, period_5);
+                            continue;
+                        }
+                        Err(e) => {
+                            error!("Backtest {} discovery error: {}", symbol, e);
+                            continue;
+                        }
+                    };
+                    let (predictions, outcomes) = self.log_and_sweep(&[&round], &HashMap::new(), &self.settings()).await;
+                    if let Some(pred) = predictions.into_iter().next() {
+                        pending.insert((symbol.clone(), period_5), pred);
+                    }
+                    if let Some(outcome) = outcomes.into_values().next() {
+                        sweeps.insert((symbol.clone(), period_5), outcome);
+                    }
+                    rounds += 1;
+                }
+                RecordedEvent::Resolution { symbol, period_5, winner, question, .. } => {
+                    let key = (symbol, period_5);
+                    if let Some(pred) = pending.remove(&key) {
+                        let sweep = sweeps.remove(&key);
+                        self.paper_trader.log_resolution(&pred, winner.as_deref(), question.as_deref(), sweep.as_ref(), None).await;
+                    }
+                }
+            }
+        }
+
+        info!("Backtest complete: {} rounds replayed, {} unresolved", rounds, pending.len());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod pipeline_tests;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control::TradingControl;
+    use crate::error::ApiError;
+    use crate::metrics::Metrics;
+    use crate::mock_api::MockApi;
+    use crate::models::{OrderBook, OrderBookEntry};
+    use rust_decimal::prelude::FromPrimitive;
+    use std::str::FromStr;
+
+    const UP: &str = "111";
+    const DOWN: &str = "222";
+
+    fn strategy(api: Arc<MockApi>, tweak: impl FnOnce(&mut crate::config::StrategyConfig)) -> ArbStrategy {
+        let mut config = Config::default();
+        config.strategy.symbols = vec!["btc".to_string()];
+        config.strategy.sweep_enabled = true;
+        tweak(&mut config.strategy);
+        let mirror = Arc::new(OrderbookMirror::new(&config.polymarket.ws_url));
+        ArbStrategy::new(
+            api,
+            config,
+            LogBuffer::new(),
+            Notifier::new(None, Duration::from_secs(1)),
+            TradingControl::new(),
+            Metrics::new(),
+            mirror,
+        )
+    }
+
+    async fn set_price(s: &ArbStrategy, price: f64) {
+        s.latest_prices
+            .write()
+            .await
+            .insert("btc".to_string(), (Decimal::from_f64(price).unwrap(), Utc::now().timestamp_millis(), String::new()));
+    }
+
+    fn btc_round() -> SymbolRound {
+        SymbolRound {
+            symbol: "btc".to_string(),
+            condition_id: "cond".to_string(),
+            up_token: UP.to_string(),
+            down_token: DOWN.to_string(),
+            period_5: 0,
+            price_to_beat: Decimal::ONE_HUNDRED,
+            close_time: Utc::now().timestamp(),
+        }
+    }
+
+    async fn set_asks(s: &ArbStrategy, token: &str, asks: &[(&str, &str)]) {
+        let asks = asks
+            .iter()
+            .map(|(p, sz)| OrderBookEntry {
+                price: Decimal::from_str(p).unwrap(),
+                size: Decimal::from_str(sz).unwrap(),
+            })
+            .collect();
+        s.orderbook_mirror.set_orderbook(token, OrderBook { bids: vec![], asks }).await;
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn rest_poller_seeds_mirror_until_unsubscribed() {
+        let book = OrderBook {
+            bids: vec![],
+            asks: vec![OrderBookEntry { price: Decimal::from_str("0.95").unwrap(), size: Decimal::from_str("100").unwrap() }],
+        };
+        let mut mock = MockApi::new();
+        mock.books.insert(UP.to_string(), book);
+        let api = Arc::new(mock);
+        let s = strategy(api.clone(), |_| {});
+        let poller = spawn_book_poller(
+            api.clone(),
+            Arc::clone(&s.orderbook_mirror),
+            vec![UP.to_string(), DOWN.to_string()],
+            Duration::from_secs(10),
+        );
+        s.orderbook_mirror.track_task(poller);
+        assert!(s.orderbook_mirror.wait_for_update(Duration::from_secs(1)).await);
+        assert!(s.orderbook_mirror.get_orderbook(DOWN).await.is_none(), "a token without a REST book stays empty");
+
+        set_price(&s, 101.0).await;
+        let (orders, _, _) = s.sweep_stale_asks(&btc_round(), &HashMap::new()).await.unwrap().totals();
+        assert_eq!(orders, 1, "the sweep uses the REST-seeded book");
+
+        s.orderbook_mirror.unsubscribe_all().await;
+        sleep(Duration::from_secs(30)).await;
+        assert!(s.orderbook_mirror.snapshot_all().await.is_empty(), "unsubscribe stops the poller");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn crossed_book_waits_for_the_next_update() {
+        let api = Arc::new(MockApi::new());
+        let s = Arc::new(strategy(api.clone(), |_| {}));
+        set_price(&s, 101.0).await;
+        let entry = |p: &str| OrderBookEntry { price: Decimal::from_str(p).unwrap(), size: Decimal::from_str("100").unwrap() };
+        s.orderbook_mirror
+            .set_orderbook(UP, OrderBook { bids: vec![entry("0.98")], asks: vec![entry("0.90")] })
+            .await;
+        let fixer = Arc::clone(&s);
+        tokio::spawn(async move {
+            sleep(Duration::from_secs(1)).await;
+            fixer.orderbook_mirror.set_orderbook(UP, OrderBook { bids: vec![entry("0.80")], asks: vec![entry("0.95")] }).await;
+        });
+
+        let (orders, _, _) = s.sweep_stale_asks(&btc_round(), &HashMap::new()).await.unwrap().totals();
+
+        let placed = api.placed_orders();
+        assert_eq!(orders, 1);
+        assert_eq!(placed[0].price, 0.95, "nothing is sent at the crossed ask");
+        let warnings = s.log_buffer.snapshot().await.into_iter().filter(|e| e.message.contains("crossed book")).count();
+        assert_eq!(warnings, 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn non_ascii_winning_token_is_skipped_without_panicking() {
+        let api = Arc::new(MockApi::new());
+        let s = strategy(api.clone(), |_| {});
+        set_price(&s, 101.0).await;
+        let token = "ÜP-éééééééééééééééé";
+        set_asks(&s, token, &[("0.95", "100")]).await;
+        let round = SymbolRound { up_token: token.to_string(), ..btc_round() };
+
+        let outcome = s.sweep_stale_asks(&round, &HashMap::new()).await.unwrap();
+
+        assert_eq!(outcome.skipped, Some(SkipReason::InvalidToken));
+        assert!(api.placed_orders().is_empty());
+        let logged = s.log_buffer.snapshot().await;
+        assert!(logged.iter().any(|e| e.level == "error" && e.message.contains("invalid Up token ID")));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn budget_caps_total_cost() {
+        let api = Arc::new(MockApi::new());
+        let s = strategy(api.clone(), |c| c.max_sweep_cost = 50.0);
+        set_price(&s, 101.0).await;
+        set_asks(&s, UP, &[("0.90", "100"), ("0.95", "100")]).await;
+
+        let (orders, shares, cost) = s.sweep_stale_asks(&btc_round(), &HashMap::new()).await.unwrap().totals();
+
+        let placed = api.placed_orders();
+        assert_eq!(placed.len(), 1, "remaining budget after the first level is below the min order");
+        assert_eq!(placed[0].token_id, UP);
+        assert_eq!(placed[0].price, 0.95);
+        assert_eq!(placed[0].size, 52.63);
+        assert_eq!(orders, 1);
+        assert_eq!(shares, 52.63);
+        assert!(cost <= 50.0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn budget_override_replaces_configured_cost() {
+        let api = Arc::new(MockApi::new());
+        let s = strategy(api.clone(), |c| c.max_sweep_cost = 500.0);
+        s.control.set_budget_override(Some(20.0));
+        set_price(&s, 101.0).await;
+        set_asks(&s, UP, &[("0.95", "100")]).await;
+
+        let (_, _, cost) = s.sweep_stale_asks(&btc_round(), &HashMap::new()).await.unwrap().totals();
+
+        assert_eq!(api.placed_orders()[0].size, 21.05);
+        assert!(cost <= 20.0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn existing_holdings_reduce_budget() {
+        let api = Arc::new(MockApi::new());
+        let s = strategy(api.clone(), |c| {
+            c.max_sweep_cost = 50.0;
+            c.subtract_holdings_from_budget = true;
+        });
+        set_price(&s, 101.0).await;
+        set_asks(&s, UP, &[("0.95", "100")]).await;
+        let holdings = HashMap::from([(UP.to_string(), 30.0)]);
+
+        let (_, _, cost) = s.sweep_stale_asks(&btc_round(), &holdings).await.unwrap().totals();
+
+        assert_eq!(api.placed_orders()[0].size, 21.05);
+        assert!(cost <= 20.0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn per_symbol_override_applies_to_sweep() {
+        let api = Arc::new(MockApi::new());
+        let s = strategy(api.clone(), |c| {
+            c.max_sweep_cost = 500.0;
+            c.per_symbol.insert(
+                "BTC".to_string(),
+                crate::config::PartialStrategyConfig { max_sweep_cost: Some(19.0), ..Default::default() },
+            );
+        });
+        set_price(&s, 101.0).await;
+        set_asks(&s, UP, &[("0.95", "100")]).await;
+
+        let (_, _, cost) = s.sweep_stale_asks(&btc_round(), &HashMap::new()).await.unwrap().totals();
+
+        assert_eq!(api.placed_orders()[0].size, 20.0);
+        assert!(cost <= 19.0);
+        assert_eq!(s.settings().for_symbol("eth").max_sweep_cost, 500.0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn orderbook_is_kept_until_resolution_when_configured() {
+        // Set by a hot reload, not at startup, so the live settings are what's read.
+        let book = OrderBook {
+            bids: vec![],
+            asks: vec![OrderBookEntry { price: Decimal::from_str("0.95").unwrap(), size: Decimal::from_str("100").unwrap() }],
+        };
+        for keep in [false, true] {
+            let mut mock = MockApi::new();
+            mock.books.insert(UP.to_string(), book.clone());
+            let api = Arc::new(mock);
+            let s = strategy(api.clone(), |_| {});
+            let mut reloaded = s.settings().base.clone();
+            reloaded.keep_orderbook_until_resolved = keep;
+            *s.settings.write().unwrap() = Arc::new(StrategySettings::new(reloaded));
+            let cfg = s.settings().base.clone();
+            let poller = spawn_book_poller(api.clone(), Arc::clone(&s.orderbook_mirror), vec![UP.to_string()], Duration::from_secs(10));
+            s.orderbook_mirror.track_task(poller);
+            let round = btc_round();
+            s.publish_schedule(round.period_5, std::slice::from_ref(&round), true).await;
+            assert!(s.orderbook_mirror.wait_for_update(Duration::from_secs(1)).await);
+
+            set_price(&s, 101.0).await;
+            s.sweep_stale_asks(&round, &HashMap::new()).await.unwrap();
+            s.release_orderbook(round.period_5, std::slice::from_ref(&round), false, &cfg).await;
+            sleep(Duration::from_secs(30)).await;
+            let subscribed = !s.orderbook_mirror.snapshot_all().await.is_empty();
+            assert_eq!(subscribed, keep, "after the sweep (keep={})", keep);
+            assert_eq!(s.metrics.schedule()[0].subscribed, keep);
+
+            s.release_orderbook(round.period_5, std::slice::from_ref(&round), true, &cfg).await;
+            sleep(Duration::from_secs(30)).await;
+            assert!(s.orderbook_mirror.snapshot_all().await.is_empty(), "after resolution (keep={})", keep);
+            assert!(!s.metrics.schedule()[0].subscribed);
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn schedule_reports_undiscovered_and_subscribed_symbols() {
+        let s = strategy(Arc::new(MockApi::new()), |c| c.symbols = vec!["btc".to_string(), "eth".to_string()]);
+        let round = btc_round();
+        let (period_5, close_time) = (round.period_5, round.close_time);
+        s.publish_schedule(period_5, &[round], true).await;
+
+        let schedule = s.metrics.schedule();
+        assert_eq!(schedule.len(), 2);
+        assert_eq!(schedule[0].symbol, "btc");
+        assert!(schedule[0].has_price_to_beat && schedule[0].subscribed);
+        assert_eq!(schedule[0].close_time, close_time);
+        assert!(!schedule[1].has_price_to_beat && !schedule[1].subscribed);
+        assert_eq!(schedule[1].close_time, period_5 + MARKET_5M_DURATION_SECS);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn logs_price_improvement_per_round() {
+        // Asked for 10 @ 0.95, paid 9.00 in total.
+        let api = Arc::new(MockApi::new().with_order_results(vec![MockApi::filled_at("a", 10.0, 9.0)]));
+        let s = strategy(api.clone(), |c| c.log_price_improvement = true);
+        set_price(&s, 101.0).await;
+        set_asks(&s, UP, &[("0.95", "10")]).await;
+
+        s.sweep_stale_asks(&btc_round(), &HashMap::new()).await.unwrap();
+
+        let logs = s.log_buffer.snapshot().await;
+        assert!(
+            logs.iter().any(|e| e.message == "price improvement $0.5000 over 1 fills"),
+            "{:?}",
+            logs.iter().map(|e| &e.message).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn writes_winner_book_keyed_by_symbol_and_period() {
+        let dir = std::env::temp_dir().join(format!("book_snapshots_{}", std::process::id()));
+        let s = strategy(Arc::new(MockApi::new()), |_| {});
+        let round = SymbolRound { period_5: 1_750_000_200, ..btc_round() };
+        let books = HashMap::from([(DOWN.to_string(), OrderBook { bids: vec![], asks: vec![] })]);
+
+        s.write_winner_book(dir.to_str().unwrap(), &[round], "btc", "Down", &books, 42);
+
+        let path = dir.join("2025-06-15").join("btc-1750000200.json");
+        let saved: BookSnapshot = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved.token_id, DOWN);
+        assert_eq!(saved.winner, "Down");
+        assert!(saved.book.is_some());
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn thin_book_skips_sweep() {
+        let api = Arc::new(MockApi::new());
+        let s = strategy(api.clone(), |c| c.min_sweepable_notional = 25.0);
+        set_price(&s, 101.0).await;
+        // $19 eligible; the 0.9995 level is above sweep_max_price and doesn't count.
+        set_asks(&s, UP, &[("0.95", "20"), ("0.9995", "100")]).await;
+
+        let outcome = s.sweep_stale_asks(&btc_round(), &HashMap::new()).await.unwrap();
+        assert_eq!(outcome.orders, 0);
+        assert_eq!(outcome.skipped, Some(SkipReason::ThinBook));
+        assert!(api.placed_orders().is_empty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn later_symbols_decide_from_their_price_at_close() {
+        let api = Arc::new(MockApi::new());
+        let mut s = strategy(api.clone(), |c| {
+            c.symbols = vec!["btc".to_string(), "eth".to_string()];
+            c.sweep_inter_order_delay_ms = 4_000;
+        });
+        let dir = std::env::temp_dir().join(format!("close_ticks_{}", std::process::id()));
+        s.paper_trader = s.paper_trader.clone().in_dir(&dir);
+        let s = Arc::new(s);
+        let btc = btc_round();
+        let eth = SymbolRound { symbol: "eth".to_string(), up_token: "333".to_string(), down_token: "444".to_string(), ..btc_round() };
+        let close_ms = btc.close_time * 1000;
+        let tick = |price: f64, ts: i64| (Decimal::from_f64(price).unwrap(), ts, String::new());
+        s.latest_prices.write().await.extend([
+            ("btc".to_string(), tick(101.0, close_ms)),
+            ("eth".to_string(), tick(101.0, close_ms)),
+        ]);
+        // Three btc orders 4s apart: eth's feed moves on well past the 5s skew window meanwhile.
+        set_asks(&s, UP, &[("0.95", "10"), ("0.94", "10"), ("0.93", "10")]).await;
+        set_asks(&s, "333", &[("0.95", "10")]).await;
+        let feed = Arc::clone(&s);
+        tokio::spawn(async move {
+            sleep(Duration::from_secs(1)).await;
+            feed.latest_prices.write().await.insert("eth".to_string(), tick(99.0, close_ms + 8_000));
+        });
+
+        let (predictions, sweeps) = s.log_and_sweep(&[&btc, &eth], &HashMap::new(), &s.settings()).await;
+        assert_eq!(sweeps["btc"].orders, 3);
+        assert_eq!(sweeps["eth"].skipped, None, "eth is decided from its price at close, not the later one");
+        assert_eq!(sweeps["eth"].orders, 1);
+        let eth_order = api.placed_orders().into_iter().last().unwrap();
+        assert_eq!(eth_order.token_id, "333", "eth's Up won at close");
+        assert!(predictions.iter().all(|p| p.prediction == "Up" && p.skip_reason.is_none()));
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn edge_recheck_stops_the_sweep_when_the_price_comes_back() {
+        let api = Arc::new(MockApi::new());
+        let s = Arc::new(strategy(api.clone(), |c| {
+            c.min_edge_at_fill_pct = Some(0.005);
+            c.sweep_inter_order_delay_ms = 100;
+        }));
+        set_price(&s, 101.0).await;
+        set_asks(&s, UP, &[("0.95", "20"), ("0.94", "20"), ("0.93", "20")]).await;
+        let mover = Arc::clone(&s);
+        tokio::spawn(async move {
+            sleep(Duration::from_millis(150)).await;
+            set_price(&mover, 100.4).await;
+        });
+
+        let outcome = s.sweep_stale_asks(&btc_round(), &HashMap::new()).await.unwrap();
+        let prices: Vec<f64> = api.placed_orders().iter().map(|o| o.price).collect();
+        assert_eq!(prices, vec![0.95, 0.94], "the third FOK sees a 0.4% edge and is not sent");
+        assert_eq!(outcome.skipped, None, "orders were placed, so the round isn't a skip");
+
+        // Already inside the edge at the first FOK: nothing is sent.
+        let api = Arc::new(MockApi::new());
+        let s = strategy(api.clone(), |c| c.min_edge_at_fill_pct = Some(0.02));
+        set_price(&s, 101.0).await;
+        set_asks(&s, UP, &[("0.95", "20")]).await;
+        let outcome = s.sweep_stale_asks(&btc_round(), &HashMap::new()).await.unwrap();
+        assert!(api.placed_orders().is_empty());
+        assert_eq!(outcome.skipped, Some(SkipReason::EdgeLost));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn empty_passes_wait_configured_interval() {
+        let api = Arc::new(MockApi::new());
+        let s = strategy(api.clone(), |c| {
+            c.sweep_empty_pass_limit = 2;
+            c.sweep_empty_pass_wait_ms = 10_000;
+        });
+        set_price(&s, 101.0).await;
+        set_asks(&s, UP, &[("0.9995", "100")]).await;
+
+        let started = tokio::time::Instant::now();
+        let (orders, _, _) = s.sweep_stale_asks(&btc_round(), &HashMap::new()).await.unwrap().totals();
+        assert_eq!(orders, 0);
+        // One wait after the first empty pass; the second hits the limit and stops.
+        assert_eq!(started.elapsed(), Duration::from_secs(10));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn monitor_only_without_authentication() {
+        let mut mock = MockApi::new();
+        mock.unauthenticated = true;
+        let api = Arc::new(mock);
+        let s = strategy(api.clone(), |_| {});
+        set_price(&s, 101.0).await;
+        set_asks(&s, UP, &[("0.90", "10")]).await;
+
+        let (orders, _, _) = s.sweep_stale_asks(&btc_round(), &HashMap::new()).await.unwrap().totals();
+        assert_eq!(orders, 0);
+        assert!(api.placed_orders().is_empty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn auto_discovery_trades_symbols_with_an_aggregator() {
+        let mut mock = MockApi::new();
+        mock.updown_symbols = vec!["btc".to_string(), "doge".to_string(), "eth".to_string()];
+        let s = strategy(Arc::new(mock), |c| c.auto_discover_symbols = true);
+        assert_eq!(s.symbols(), ["btc"]);
+
+        s.refresh_symbols().await;
+        assert_eq!(s.symbols(), ["btc", "eth"], "doge has no Chainlink aggregator");
+        s.publish_schedule(0, &[], false).await;
+        let scheduled: Vec<String> = s.metrics.schedule().into_iter().map(|e| e.symbol).collect();
+        assert_eq!(scheduled, ["btc", "eth"]);
+
+        // Nothing usable found: keep trading what we have.
+        let s = strategy(Arc::new(MockApi::new()), |c| c.auto_discover_symbols = true);
+        s.refresh_symbols().await;
+        assert_eq!(s.symbols(), ["btc"]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn below_threshold_markets_are_not_traded() {
+        use crate::discovery::build_5m_slug;
+        use crate::models::{Market, MarketDetails, MarketToken};
+
+        let period_5 = 1_767_726_000;
+        let discover = |question: &str| {
+            let mut mock = MockApi::new();
+            let slug = build_5m_slug("btc", period_5);
+            let market = Market {
+                condition_id: "cond".to_string(),
+                market_id: None,
+                question: question.to_string(),
+                slug: slug.clone(),
+                end_date_iso: None,
+                active: true,
+                closed: false,
+                price_to_beat: None,
+            };
+            mock.markets_by_slug.insert(slug, market);
+            let token = |outcome: &str, token_id: &str| MarketToken {
+                outcome: outcome.to_string(),
+                token_id: token_id.to_string(),
+                winner: false,
+            };
+            mock.markets.insert(
+                "cond".to_string(),
+                MarketDetails {
+                    condition_id: "cond".to_string(),
+                    question: question.to_string(),
+                    tokens: vec![token("Up", UP), token("Down", DOWN)],
+                    active: true,
+                    closed: false,
+                    end_date_iso: String::new(),
+                    neg_risk: false,
+                },
+            );
+            async move { strategy(Arc::new(mock), |_| {}).discover_symbol("btc", period_5).await.unwrap() }
+        };
+
+        let round = discover("Will Bitcoin be above $97,500 at 6:40PM ET?").await.expect("above market is traded");
+        assert_eq!(round.price_to_beat, Decimal::from(97_500));
+        assert_eq!((round.up_token.as_str(), round.down_token.as_str()), (UP, DOWN));
+        assert!(discover("Will Bitcoin be below $97,500 at 6:40PM ET?").await.is_none());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn monitor_mode_simulates_the_sweep_without_signing() {
+        let mut mock = MockApi::new();
+        mock.no_private_key = true;
+        let api = Arc::new(mock);
+        // simulation_mode is off: the missing key alone keeps orders from being sent.
+        let s = strategy(api.clone(), |c| c.presign_orders = 3);
+        set_price(&s, 101.0).await;
+        set_asks(&s, UP, &[("0.90", "10")]).await;
+
+        s.warm_order_cache(&[("btc", UP)]).await;
+        s.presign_sweep_orders(&[btc_round()], &HashMap::new()).await;
+        let outcome = s.sweep_stale_asks(&btc_round(), &HashMap::new()).await.unwrap();
+        assert_eq!(outcome.orders, 1, "would-be fills are still tracked");
+        assert!((outcome.cost - 9.0).abs() < 1e-9);
+        assert_eq!(outcome.skipped, None);
+        assert!(api.placed_orders().is_empty());
+        assert!(api.presigned_orders().is_empty());
+        assert!(s.precisions.read().await.is_empty(), "no order cache warm-up without a key");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn warmup_rounds_are_simulated_then_live() {
+        let api = Arc::new(MockApi::new());
+        let s = strategy(api.clone(), |c| c.live_after_rounds = 2);
+        set_price(&s, 101.0).await;
+        set_asks(&s, UP, &[("0.90", "10")]).await;
+
+        for _ in 0..2 {
+            let outcome = s.sweep_stale_asks(&btc_round(), &HashMap::new()).await.unwrap();
+            assert_eq!(outcome.orders, 1, "warmup fills are simulated");
+            assert!(api.placed_orders().is_empty());
+            s.finish_warmup_round().await;
+        }
+
+        s.sweep_stale_asks(&btc_round(), &HashMap::new()).await.unwrap();
+        assert_eq!(api.placed_orders().len(), 1);
+        s.finish_warmup_round().await;
+        assert!(!s.warming_up());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn rtds_and_rpc_close_prices_feed_agreement_stats() {
+        let prediction = |close: &str| PredictionRecord {
+            symbol: "btc".to_string(),
+            period_5: 0,
+            period_str: String::new(),
+            price_to_beat: Decimal::ONE_HUNDRED,
+            close_price: Decimal::from_str(close).unwrap(),
+            prediction: "Up".to_string(),
+            condition_id: "cond".to_string(),
+            close_rtds_ts_ms: 300_000,
+            system_read_ts_ms: 300_000,
+            age_s: 0,
+            diff: Decimal::ONE,
+            diff_pct: 1.0,
+            raw_rtds_json: String::new(),
+            status: crate::paper_trade::STATUS_PREDICTED,
+            skip_reason: None,
+        };
+        let mut mock = MockApi::new();
+        mock.chainlink_price = Some(Decimal::from(101));
+        let s = strategy(Arc::new(mock), |c| c.price_agreement_tolerance_pct = 0.1);
+
+        s.record_price_agreement(&[prediction("101.05")]).await;
+        s.record_price_agreement(&[prediction("102.01")]).await;
+
+        let report = s.metrics.price_agreement.report();
+        let btc = &report["btc"];
+        assert_eq!(btc.rounds, 2);
+        assert_eq!(btc.disagreements, 1);
+        assert!((btc.max_abs_diff_pct - 1.0).abs() < 1e-9);
+        assert!((btc.last_abs_diff_pct - 1.0).abs() < 1e-9);
+        assert!((btc.mean_abs_diff_pct - (5.0 / 101.0 + 1.0) / 2.0).abs() < 1e-9);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn live_orders_need_the_rpc_price_on_the_same_side() {
+        for (rpc, placed) in [(Some(100.5), 1), (Some(99.5), 0), (None, 0)] {
+            let mut mock = MockApi::new();
+            mock.chainlink_price = rpc.map(|p| Decimal::from_f64(p).unwrap());
+            let api = Arc::new(mock);
+            let s = strategy(api.clone(), |c| c.require_source_agreement_for_live = true);
+            set_price(&s, 101.0).await;
+            set_asks(&s, UP, &[("0.90", "10")]).await;
+
+            let outcome = s.sweep_stale_asks(&btc_round(), &HashMap::new()).await.unwrap();
+
+            assert_eq!(api.placed_orders().len(), placed, "rpc price {:?}", rpc);
+            if placed == 0 {
+                assert_eq!(outcome.skipped, Some(SkipReason::SourcesDisagree));
+            }
+        }
+
+        // Simulated sweeps don't need the second source.
+        let api = Arc::new(MockApi::new());
+        let s = strategy(api.clone(), |c| {
+            c.require_source_agreement_for_live = true;
+            c.simulation_mode = true;
+        });
+        set_price(&s, 101.0).await;
+        set_asks(&s, UP, &[("0.90", "10")]).await;
+        assert_eq!(s.sweep_stale_asks(&btc_round(), &HashMap::new()).await.unwrap().orders, 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn skips_price_far_from_close() {
+        let api = Arc::new(MockApi::new());
+        let s = strategy(api.clone(), |c| c.winner_price_max_skew_ms = 5_000);
+        set_asks(&s, UP, &[("0.90", "10")]).await;
+        let close = Utc::now().timestamp();
+        s.latest_prices
+            .write()
+            .await
+            .insert("btc".to_string(), (Decimal::from(101), (close - 30) * 1000, String::new()));
+
+        let outcome = s.sweep_stale_asks(&btc_round(), &HashMap::new()).await.unwrap();
+        assert_eq!(outcome.orders, 0, "tick 30s before close arrived late but must not pick the winner");
+        assert_eq!(outcome.skipped, Some(SkipReason::RtdsStale));
+        assert!(api.placed_orders().is_empty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn presigns_likely_winner_levels_within_budget() {
+        let api = Arc::new(MockApi::new());
+        let s = strategy(api.clone(), |c| {
+            c.presign_orders = 3;
+            c.max_sweep_cost = 19.0;
+        });
+        set_price(&s, 101.0).await;
+        set_asks(&s, UP, &[("0.95", "10"), ("0.90", "10"), ("0.85", "10")]).await;
+        set_asks(&s, DOWN, &[("0.05", "10")]).await;
+
+        s.presign_sweep_orders(&[btc_round()], &HashMap::new()).await;
+
+        let presigned = api.presigned_orders();
+        assert_eq!(presigned.len(), 2, "third level is past the budget");
+        assert!(presigned.iter().all(|o| o.token_id == UP));
+        assert_eq!((presigned[0].price, presigned[0].size), (0.95, 10.0));
+        assert_eq!((presigned[1].price, presigned[1].size), (0.90, 10.0));
+    }
+
+    #[test]
+    fn order_strings_follow_tick_size() {
+        let milli = OrderPrecision::from_tick(Decimal::from_str("0.001").unwrap());
+        assert_eq!(milli.price_str(Decimal::from_str("0.997").unwrap()), "0.997");
+        assert_eq!(milli.price_str(Decimal::from_str("0.99").unwrap()), "0.990");
+        assert_eq!(milli.size_str(milli.floor_size(12.3456)), "12.34");
+
+        let cent = OrderPrecision::default();
+        assert_eq!(cent.price_str(Decimal::from_str("0.95").unwrap()), "0.95");
+        assert_eq!(cent.price_str(Decimal::from_str("0.9").unwrap()), "0.90");
+    }
+
+    #[test]
+    fn fok_limit_is_buffered_above_the_ask_up_to_the_cap() {
+        let d = |v: &str| Decimal::from_str(v).unwrap();
+        let milli = OrderPrecision::from_tick(d("0.001"));
+        assert_eq!(milli.limit_price(d("0.99"), 0.0, 0.999), d("0.99"));
+        assert_eq!(milli.limit_price(d("0.99"), 0.002, 0.999), d("0.992"));
+        assert_eq!(milli.limit_price(d("0.99"), 0.002, 0.991), d("0.991"), "capped at sweep_max_price");
+        assert_eq!(milli.limit_price(d("0.99"), 0.0025, 0.999), d("0.992"), "rounded down to the tick");
+
+        let cent = OrderPrecision::default();
+        assert_eq!(cent.limit_price(d("0.95"), 0.002, 0.999), d("0.95"), "a buffer under one tick is a no-op");
+        assert_eq!(cent.limit_price(d("0.95"), 0.02, 0.999), d("0.97"));
+        assert_eq!(cent.limit_price(d("0.95"), 0.02, 0.9555), d("0.95"), "never below the ask");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn sweep_sends_buffered_fok_prices() {
+        let mut mock = MockApi::new();
+        mock.tick_sizes.insert(UP.to_string(), Decimal::from_str("0.001").unwrap());
+        let api = Arc::new(mock);
+        let s = strategy(api.clone(), |c| {
+            c.fok_price_buffer = 0.002;
+            c.sweep_max_price = 0.995;
+        });
+        s.warm_order_cache(&[("btc", UP)]).await;
+        set_price(&s, 101.0).await;
+        set_asks(&s, UP, &[("0.99", "10"), ("0.994", "10")]).await;
+
+        let outcome = s.sweep_stale_asks(&btc_round(), &HashMap::new()).await.unwrap();
+
+        let prices: Vec<f64> = api.placed_orders().iter().map(|o| o.price).collect();
+        assert_eq!(prices, vec![0.995, 0.992], "0.994 + 0.002 is capped at 0.995");
+        assert!((outcome.cost - 19.87).abs() < 1e-9, "unreported cost is counted at the limit: {}", outcome.cost);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn buffered_fills_stay_within_the_budget() {
+        let api = Arc::new(MockApi::new());
+        let s = strategy(api.clone(), |c| {
+            c.max_sweep_cost = 20.0;
+            c.fok_price_buffer = 0.05;
+            c.sweep_max_price = 0.99;
+        });
+        set_price(&s, 101.0).await;
+        set_asks(&s, UP, &[("0.90", "10"), ("0.80", "100")]).await;
+
+        let outcome = s.sweep_stale_asks(&btc_round(), &HashMap::new()).await.unwrap();
+
+        // 10 @ 0.95 leaves $10.50, not the $11.00 left at the observed 0.90, so the second
+        // order is 12.35 @ 0.85 rather than 12.94 (which would spend $20.50 at the limits).
+        let placed = api.placed_orders();
+        let sizes: Vec<(f64, f64)> = placed.iter().map(|o| (o.size, o.price)).collect();
+        assert_eq!(sizes, vec![(10.0, 0.95), (12.35, 0.85)]);
+        let at_limits: f64 = placed.iter().map(|o| o.size * o.price).sum();
+        assert!(at_limits <= 20.0, "spend at the limits {}", at_limits);
+        assert!((outcome.cost - at_limits).abs() < 1e-9, "{}", outcome.cost);
+    }
+
+    #[test]
+    fn sizes_floor_to_the_lot_without_float_drift() {
+        assert_eq!(floor_to_lot(99.999999, 2), 99.99);
+        assert_eq!(floor_to_lot(1.15, 2), 1.15, "1.15 * 100.0 is 114.999..., a float floor loses a lot");
+        assert_eq!(floor_to_lot(0.29, 2), 0.29);
+        assert_eq!(floor_to_lot(52.631578947368425, 2), 52.63);
+        assert_eq!(floor_to_lot(12.3456, 3), 12.345);
+        assert_eq!(floor_to_lot(12.3456, 1), 12.3);
+        assert_eq!(floor_to_lot(7.99, 0), 7.0);
+        assert_eq!(floor_to_lot(0.009, 2), 0.0);
+        assert_eq!(floor_to_lot(f64::NAN, 2), 0.0);
+        assert_eq!(OrderPrecision::default().size_str(OrderPrecision::default().floor_size(4.35)), "4.35");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn sweep_prices_fractional_cent_ticks() {
+        let mut mock = MockApi::new();
+        mock.tick_sizes.insert(UP.to_string(), Decimal::from_str("0.001").unwrap());
+        let api = Arc::new(mock);
+        let s = strategy(api.clone(), |_| {});
+        s.warm_order_cache(&[("btc", UP)]).await;
+        set_price(&s, 101.0).await;
+        set_asks(&s, UP, &[("0.997", "5.5")]).await;
+
+        s.sweep_stale_asks(&btc_round(), &HashMap::new()).await.unwrap();
+
+        let placed = api.placed_orders();
+        assert_eq!(placed.len(), 1);
+        assert_eq!(placed[0].price, 0.997);
+        assert_eq!(placed[0].size, 5.5);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn hard_deadline_stops_sweep() {
+        let api = Arc::new(MockApi::new());
+        let s = strategy(api.clone(), |c| {
+            c.sweep_hard_deadline_ms = Some(2_000);
+            c.winner_price_max_skew_ms = 0;
+        });
+        set_price(&s, 101.0).await;
+        set_asks(&s, UP, &[("0.90", "10")]).await;
+
+        let late = SymbolRound { close_time: Utc::now().timestamp() - 5, ..btc_round() };
+        let (orders, _, _) = s.sweep_stale_asks(&late, &HashMap::new()).await.unwrap().totals();
+        assert_eq!(orders, 0);
+        assert!(api.placed_orders().is_empty());
+
+        let (orders, _, _) = s.sweep_stale_asks(&btc_round(), &HashMap::new()).await.unwrap().totals();
+        assert_eq!(orders, 1, "still inside the deadline");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn likely_winner_follows_latest_price() {
+        let s = strategy(Arc::new(MockApi::new()), |_| {});
+        let round = btc_round();
+        assert_eq!(s.likely_winner(&round).await, None, "no RTDS price yet");
+
+        set_price(&s, 99.0).await;
+        let (token, margin) = s.likely_winner(&round).await.unwrap();
+        assert_eq!(token, DOWN);
+        assert!((margin - 0.01).abs() < 1e-9);
+
+        set_price(&s, 100.0).await;
+        assert_eq!(s.likely_winner(&round).await, None, "tied");
+    }
+
+    #[test]
+    fn reload_keeps_restart_only_fields() {
+        let running = Config::default().strategy;
+        let mut edited = running.clone();
+        edited.symbols = vec!["btc".to_string()];
+        edited.ptb_capture_window_secs = 9;
+        edited.max_sweep_cost = 7.0;
+
+        let applied = keep_restart_only_fields(&running, edited);
+
+        assert_eq!(applied.symbols, running.symbols);
+        assert_eq!(applied.ptb_capture_window_secs, running.ptb_capture_window_secs);
+        assert_eq!(applied.max_sweep_cost, 7.0);
+    }
+
+    #[test]
+    fn holding_cap_limits_budget() {
+        let mut cfg = Config::default().strategy;
+        cfg.max_sweep_cost = 100.0;
+        cfg.max_holding_notional_per_market = Some(60.0);
+        assert_eq!(effective_sweep_budget(&cfg, cfg.max_sweep_cost, 0.0), 60.0);
+        assert_eq!(effective_sweep_budget(&cfg, cfg.max_sweep_cost, 45.0), 15.0);
+        assert_eq!(effective_sweep_budget(&cfg, cfg.max_sweep_cost, 80.0), 0.0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn diff_exactly_at_min_margin_sweeps() {
+        // In f64, 100.1 - 100.0 = 0.0999999999999943 < 0.001 * 100.0 and the round was skipped.
+        let api = Arc::new(MockApi::new());
+        let s = strategy(api.clone(), |c| c.sweep_min_margin_pct = 0.001);
+        set_price(&s, 100.1).await;
+        set_asks(&s, UP, &[("0.95", "10")]).await;
+
+        let outcome = s.sweep_stale_asks(&btc_round(), &HashMap::new()).await.unwrap();
+        assert_eq!(outcome.skipped, None);
+        assert_eq!(outcome.orders, 1);
+    }
+
+    #[tokio::test]
+    async fn rtds_prices_are_exact_decimals() {
+        let s = strategy(Arc::new(MockApi::new()), |_| {});
+        let symbols: HashSet<String> = ["btc".to_string()].into();
+        let msg = r#"{"topic":"crypto_prices_chainlink","payload":{"symbol":"btc/usd","timestamp":1771820400000,"value":0.3}}"#;
+        apply_chainlink_message(msg, &symbols, &s.ptb_capture, &s.price_cache_5, &s.latest_prices).await;
+
+        let (price, _, _) = s.latest_prices.read().await.get("btc").cloned().unwrap();
+        // 0.1 + 0.2 != 0.3 in f64; a price-to-beat built from those parts must still tie.
+        assert_eq!(price, Decimal::from_str("0.1").unwrap() + Decimal::from_str("0.2").unwrap());
+        assert_eq!(s.price_cache_5.read().await["btc"][&1_771_820_400], price);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn skips_when_diff_below_min_margin() {
+        let api = Arc::new(MockApi::new());
+        let s = strategy(api.clone(), |c| c.sweep_min_margin_pct = 0.01);
+        set_price(&s, 100.5).await;
+        set_asks(&s, UP, &[("0.95", "100")]).await;
+
+        let result = s.sweep_stale_asks(&btc_round(), &HashMap::new()).await.unwrap();
+
+        assert_eq!(result, SweepOutcome::skipped(SkipReason::BelowMargin));
+        assert!(api.placed_orders().is_empty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn denylisted_market_places_no_orders() {
+        let denylists: [fn(&mut StrategyConfig); 2] = [
+            |c| c.skip_condition_ids = vec!["COND".to_string()],
+            |c| c.skip_token_ids = vec![DOWN.to_string()],
+        ];
+        for deny in denylists {
+            let api = Arc::new(MockApi::new());
+            let s = strategy(api.clone(), deny);
+            set_price(&s, 101.0).await;
+            set_asks(&s, UP, &[("0.95", "100")]).await;
+
+            let result = s.sweep_stale_asks(&btc_round(), &HashMap::new()).await.unwrap();
+
+            assert_eq!(result, SweepOutcome::skipped(SkipReason::Denylisted));
+            assert!(api.placed_orders().is_empty());
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn per_symbol_max_price_caps_the_sweep() {
+        let api = Arc::new(MockApi::new());
+        let s = strategy(api.clone(), |c| {
+            c.per_symbol.insert(
+                "btc".to_string(),
+                crate::config::PartialStrategyConfig { sweep_max_price: Some(0.97), ..Default::default() },
+            );
+        });
+        set_price(&s, 101.0).await;
+        set_asks(&s, UP, &[("0.95", "10"), ("0.97", "10"), ("0.99", "100")]).await;
+
+        s.sweep_stale_asks(&btc_round(), &HashMap::new()).await.unwrap();
+
+        let prices: Vec<f64> = api.placed_orders().iter().map(|o| o.price).collect();
+        assert_eq!(prices, vec![0.97, 0.95]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn down_winner_respects_max_price() {
+        let api = Arc::new(MockApi::new());
+        let s = strategy(api.clone(), |c| c.sweep_max_price = 0.99);
+        set_price(&s, 99.0).await;
+        set_asks(&s, DOWN, &[("0.995", "10"), ("0.98", "10")]).await;
+        set_asks(&s, UP, &[("0.50", "10")]).await;
+
+        s.sweep_stale_asks(&btc_round(), &HashMap::new()).await.unwrap();
+
+        let placed = api.placed_orders();
+        assert_eq!(placed.len(), 1);
+        assert_eq!(placed[0].token_id, DOWN);
+        assert_eq!(placed[0].price, 0.98);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn ambiguous_network_error_halts_sweep() {
+        let api = Arc::new(
+            MockApi::new().with_order_results(vec![Err(ApiError::NetworkAmbiguous("timeout".to_string()))]),
+        );
+        let s = strategy(api.clone(), |_| {});
+        set_price(&s, 101.0).await;
+        set_asks(&s, UP, &[("0.95", "10"), ("0.90", "10")]).await;
+
+        let (orders, _, _) = s.sweep_stale_asks(&btc_round(), &HashMap::new()).await.unwrap().totals();
+
+        assert_eq!(api.placed_orders().len(), 1);
+        assert_eq!(orders, 0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn network_errors_across_rounds_open_the_circuit_breaker() {
+        let timeout = || Err(ApiError::NetworkAmbiguous("timeout".to_string()));
+        let api = Arc::new(MockApi::new().with_order_results(vec![timeout(), Ok(None), timeout(), timeout()]));
+        let s = strategy(api.clone(), |c| {
+            c.network_error_breaker_threshold = 2;
+            c.network_error_breaker_cooldown_secs = 600;
+        });
+        set_price(&s, 101.0).await;
+        set_asks(&s, UP, &[("0.95", "10")]).await;
+
+        // Error, then a killed FOK: the CLOB answered, so the count starts over.
+        s.sweep_stale_asks(&btc_round(), &HashMap::new()).await.unwrap();
+        s.sweep_stale_asks(&btc_round(), &HashMap::new()).await.unwrap();
+        assert_eq!(s.control.consecutive_network_errors(), 0);
+
+        s.sweep_stale_asks(&btc_round(), &HashMap::new()).await.unwrap();
+        assert!(s.control.breaker_open_until(Utc::now().timestamp()).is_none());
+        s.sweep_stale_asks(&btc_round(), &HashMap::new()).await.unwrap();
+        assert!(s.control.breaker_open_until(Utc::now().timestamp()).is_some(), "second error in a row trips it");
+
+        let outcome = s.sweep_stale_asks(&btc_round(), &HashMap::new()).await.unwrap();
+        assert_eq!(outcome.skipped, Some(SkipReason::CircuitOpen));
+        assert_eq!(api.placed_orders().len(), 4);
+        assert!(s.control.breaker_open_until(Utc::now().timestamp() + 601).is_none(), "closes after the cool-down");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn rejected_order_continues_to_next_level() {
+        let api = Arc::new(
+            MockApi::new().with_order_results(vec![Err(ApiError::Rejected("not enough balance".to_string()))]),
+        );
+        let s = strategy(api.clone(), |_| {});
+        set_price(&s, 101.0).await;
+        set_asks(&s, UP, &[("0.95", "10"), ("0.90", "10")]).await;
+
+        let (orders, shares, _) = s.sweep_stale_asks(&btc_round(), &HashMap::new()).await.unwrap().totals();
+
+        let placed = api.placed_orders();
+        assert_eq!(placed.len(), 2);
+        assert_eq!(placed[1].price, 0.90);
+        assert_eq!(orders, 1);
+        assert_eq!(shares, 10.0);
+    }
+
+    /// Local webhook receiver: its URL and the JSON bodies posted to it.
+    async fn webhook() -> (String, Arc<std::sync::Mutex<Vec<serde_json::Value>>>) {
+        use axum::{routing::post, Json, Router};
+        let bodies = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let received = Arc::clone(&bodies);
+        let app = Router::new().route(
+            "/hook",
+            post(move |Json(body): Json<serde_json::Value>| {
+                let received = Arc::clone(&received);
+                async move { received.lock().unwrap().push(body) }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.ok();
+        });
+        (format!("http://{}/hook", addr), bodies)
+    }
+
+    async fn next_webhook(bodies: &std::sync::Mutex<Vec<serde_json::Value>>) -> serde_json::Value {
+        for _ in 0..200 {
+            if let Some(body) = bodies.lock().unwrap().pop() {
+                return body;
+            }
+            sleep(Duration::from_millis(10)).await;
+        }
+        panic!("no webhook received");
+    }
+
+    #[tokio::test]
+    async fn live_sweep_sends_a_sweep_executed_webhook() {
+        let (url, bodies) = webhook().await;
+        let api = Arc::new(MockApi::new());
+        let mut s = strategy(api.clone(), |c| c.sweep_empty_pass_wait_ms = 0);
+        s.notifier = Notifier::new(Some(url), Duration::from_secs(1));
+        set_price(&s, 101.0).await;
+        set_asks(&s, UP, &[("0.95", "10")]).await;
+
+        s.sweep_stale_asks(&btc_round(), &HashMap::new()).await.unwrap();
+
+        let body = next_webhook(&bodies).await;
+        assert_eq!(body["event_type"], "sweep_executed");
+        assert_eq!(body["symbol"], "btc");
+        assert!(body["message"].as_str().unwrap().contains("btc swept Up"), "{}", body);
+        assert!(body["timestamp"].is_string());
+        assert_eq!(body["details"], serde_json::json!({"winner": "Up", "orders": 1, "shares": 10.0, "cost": 9.5}));
+    }
+
+    #[tokio::test]
+    async fn daily_loss_limit_pauses_trading_and_alerts_once() {
+        let (url, bodies) = webhook().await;
+        let mut s = strategy(Arc::new(MockApi::new()), |c| c.daily_loss_limit = 15.0);
+        s.notifier = Notifier::new(Some(url), Duration::from_secs(1));
+        let cfg = s.settings().base.clone();
+        let sweep = SweepOutcome { orders: 1, shares: 10.0, cost: 9.9, skipped: None, live: true };
+
+        s.record_sweep_pnl("btc", &sweep, true, &cfg).await;
+        s.record_sweep_pnl("btc", &sweep, false, &cfg).await;
+        s.record_sweep_pnl("btc", &SweepOutcome { live: false, ..sweep }, false, &cfg).await;
+        assert!(!s.control.is_paused(), "net -9.8, and simulated sweeps don't count");
+
+        s.record_sweep_pnl("btc", &sweep, false, &cfg).await;
+        assert!(s.control.is_paused());
+        let body = next_webhook(&bodies).await;
+        assert_eq!(body["event_type"], "daily_loss_limit");
+        assert_eq!(body["symbol"], "btc");
+        assert!(body["message"].as_str().unwrap().contains("trading paused"), "{}", body);
+        let details = &body["details"];
+        assert!((details["day_pnl"].as_f64().unwrap() + 19.7).abs() < 1e-9, "{}", body);
+        assert_eq!(details["limit"], 15.0);
+
+        s.record_sweep_pnl("btc", &sweep, false, &cfg).await;
+        sleep(Duration::from_millis(100)).await;
+        assert!(bodies.lock().unwrap().is_empty(), "only the sweep that crosses the limit alerts");
+    }
 }
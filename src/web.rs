@@ -1,27 +1,92 @@
 //! Built-in web dashboard: serves a single HTML page with live-updating logs via SSE.
 
-use crate::log_buffer::LogBuffer;
-use axum::extract::State;
+use crate::api::PolymarketApi;
+use crate::config::DashboardConfig;
+use crate::control::SharedControl;
+use crate::log_buffer::{LogBuffer, LogEntry, LogLevel, SSE_LOG_TARGET};
+use crate::metrics::SharedMetrics;
+use crate::orderbook_ws::OrderbookMirror;
+use crate::strategy::SharedSettings;
+use axum::extract::{FromRef, Query, State};
+use axum::http::{HeaderMap, StatusCode};
 use axum::response::sse::{Event, KeepAlive, Sse};
-use axum::response::Html;
-use axum::routing::get;
-use axum::Router;
+use axum::http::header::{CACHE_CONTROL, ETAG, IF_NONE_MATCH};
+use axum::response::{Html, IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
 use futures_util::stream::Stream;
 use log::info;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use crate::{decision, paper_trade};
+use std::collections::BTreeMap;
 use std::convert::Infallible;
+use std::sync::{Arc, LazyLock};
+use tower_http::compression::CompressionLayer;
 
-/// Spawn the web dashboard server as a background task.
-pub async fn spawn_dashboard(log_buffer: LogBuffer) {
+/// Shared state for all dashboard routes.
+#[derive(Clone)]
+struct AppState {
+    log_buffer: LogBuffer,
+    api: Arc<PolymarketApi>,
+    control: SharedControl,
+    metrics: SharedMetrics,
+    /// The strategy's orderbook mirror, for `/orderbook`.
+    orderbook: Arc<OrderbookMirror>,
+    /// Bearer token for control routes; None disables them.
+    token: Option<String>,
+    /// Trailing `/pnl` windows in hours (all-time is always reported).
+    pnl_window_hours: Vec<u64>,
+    /// Idle time on `/events` before a keep-alive comment.
+    sse_keep_alive: Duration,
+    /// Interval between `heartbeat` events on `/events`; None disables them.
+    sse_heartbeat: Option<Duration>,
+    /// Entries below this level are left out of `/events` and `/snapshot`.
+    min_level: LogLevel,
+    /// Live strategy settings (hot reloads included), for `/replay` and the configured budget on
+    /// `/control`.
+    settings: SharedSettings,
+    /// Upper bound for `POST /control/budget`.
+    max_budget_override: f64,
+}
+
+impl FromRef<AppState> for LogBuffer {
+    fn from_ref(state: &AppState) -> Self {
+        state.log_buffer.clone()
+    }
+}
+
+/// Spawn the web dashboard server as a background task. Fails if neither `PORT` nor `PORT + 1`
+/// can be bound.
+pub async fn spawn_dashboard(
+    log_buffer: LogBuffer,
+    api: Arc<PolymarketApi>,
+    control: SharedControl,
+    metrics: SharedMetrics,
+    orderbook: Arc<OrderbookMirror>,
+    dashboard: DashboardConfig,
+    settings: SharedSettings,
+) -> anyhow::Result<()> {
     let port: u16 = std::env::var("PORT")
         .ok()
         .and_then(|p| p.parse().ok())
         .unwrap_or(3000);
 
-    let app = Router::new()
-        .route("/", get(index_handler))
-        .route("/events", get(sse_handler))
-        .route("/snapshot", get(snapshot_handler))
-        .with_state(log_buffer);
+    let app = router(AppState {
+        log_buffer,
+        api,
+        control,
+        metrics,
+        orderbook,
+        token: dashboard.token,
+        pnl_window_hours: dashboard.pnl_window_hours,
+        sse_keep_alive: Duration::from_secs(dashboard.sse_keep_alive_secs),
+        sse_heartbeat: (dashboard.sse_heartbeat_secs > 0)
+            .then(|| Duration::from_secs(dashboard.sse_heartbeat_secs)),
+        min_level: dashboard.min_level,
+        settings,
+        max_budget_override: dashboard.max_budget_override,
+    });
 
     let listener = match tokio::net::TcpListener::bind(format!("0.0.0.0:{}", port)).await {
         Ok(l) => l,
@@ -29,7 +94,7 @@ pub async fn spawn_dashboard(log_buffer: LogBuffer) {
             log::warn!("Dashboard port {} unavailable ({}), trying {}", port, e, port + 1);
             tokio::net::TcpListener::bind(format!("0.0.0.0:{}", port + 1))
                 .await
-                .expect("failed to bind dashboard on fallback port too")
+                .map_err(|e| anyhow::anyhow!("failed to bind dashboard on {} or {}: {}", port, port + 1, e))?
         }
     };
     let actual_port = listener.local_addr().map(|a| a.port()).unwrap_or(port);
@@ -37,27 +102,453 @@ pub async fn spawn_dashboard(log_buffer: LogBuffer) {
     tokio::spawn(async move {
         axum::serve(listener, app).await.ok();
     });
+    Ok(())
+}
+
+/// All dashboard routes over `state`.
+fn router(state: AppState) -> Router {
+    Router::new()
+        // Only the page is compressed; `/events` must stream unbuffered.
+        .route("/", get(index_handler).layer(CompressionLayer::new()))
+        .route("/events", get(sse_handler))
+        .route("/snapshot", get(snapshot_handler))
+        .route("/health", get(health_handler))
+        .route("/fills", get(fills_handler))
+        .route("/trades", get(trades_handler))
+        .route("/schedule", get(schedule_handler))
+        .route("/orderbook", get(orderbook_handler))
+        .route("/pnl", get(pnl_handler))
+        .route("/replay", get(replay_handler))
+        .route("/orders", get(orders_handler))
+        .route("/positions", get(positions_handler))
+        .route("/redeem", post(redeem_handler))
+        .route("/control", get(control_status_handler))
+        .route("/control/pause", post(pause_handler))
+        .route("/control/resume", post(resume_handler))
+        .route("/control/budget", post(budget_handler))
+        .with_state(state)
+}
+
+/// Strong ETag for `DASHBOARD_HTML`, which only changes with the binary.
+static DASHBOARD_ETAG: LazyLock<String> = LazyLock::new(|| {
+    use std::hash::{DefaultHasher, Hash, Hasher};
+    let mut hasher = DefaultHasher::new();
+    DASHBOARD_HTML.hash(&mut hasher);
+    format!("\"{:016x}\"", hasher.finish())
+});
+
+/// GET / — the dashboard page. Browsers revalidate on every load (`no-cache`) and get a 304
+/// while the ETag matches, so a repeat load costs one round trip.
+async fn index_handler(headers: HeaderMap) -> Response {
+    let etag = DASHBOARD_ETAG.as_str();
+    let cache_headers = [(ETAG, etag), (CACHE_CONTROL, "no-cache")];
+    let matches = headers
+        .get(IF_NONE_MATCH)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.split(',').any(|tag| tag.trim() == etag || tag.trim() == "*"));
+    if matches {
+        return (StatusCode::NOT_MODIFIED, cache_headers).into_response();
+    }
+    (cache_headers, Html(DASHBOARD_HTML)).into_response()
+}
+
+async fn snapshot_handler(State(state): State<AppState>) -> axum::Json<Vec<LogEntry>> {
+    let mut entries = state.log_buffer.snapshot().await;
+    entries.retain(|e| e.at_least(state.min_level));
+    axum::Json(entries)
+}
+
+/// Check `Authorization: Bearer <token>` against the configured dashboard token.
+fn check_token(state: &AppState, headers: &HeaderMap) -> Result<(), (StatusCode, Json<Value>)> {
+    let expected = match &state.token {
+        Some(t) if !t.is_empty() => t,
+        _ => {
+            return Err((
+                StatusCode::FORBIDDEN,
+                Json(json!({"error": "control routes disabled: set dashboard.token in config"})),
+            ))
+        }
+    };
+    let provided = headers
+        .get(axum::http::header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+    if !provided.is_some_and(|p| tokens_match(p.as_bytes(), expected.as_bytes())) {
+        return Err((StatusCode::UNAUTHORIZED, Json(json!({"error": "invalid token"}))));
+    }
+    Ok(())
+}
+
+/// Constant-time token comparison: every byte is compared, so the response time doesn't reveal
+/// how long a matching prefix was. Only the length can leak.
+fn tokens_match(provided: &[u8], expected: &[u8]) -> bool {
+    provided.len() == expected.len() && provided.iter().zip(expected).fold(0u8, |acc, (a, b)| acc | (a ^ b)) == 0
+}
+
+/// GET /health — liveness plus API/feed counters.
+async fn health_handler(State(state): State<AppState>) -> Json<Value> {
+    Json(json!({
+        "status": "ok",
+        "authenticated": state.api.is_authenticated(),
+        "monitor": !state.api.has_private_key(),
+        "paused": state.control.is_paused(),
+        "api": {
+            "rate_limited_total": state.api.rate_limited_count(),
+        },
+        "rtds": state.metrics.rtds.report(),
+        "price_agreement": state.metrics.price_agreement.report(),
+        "dashboard": {
+            "sse_lagged_total": state.log_buffer.lagged_total(),
+        },
+    }))
+}
+
+/// GET /fills — live FOK attempted vs filled counts per ask price bucket, across all rounds.
+async fn fills_handler(State(state): State<AppState>) -> Json<Value> {
+    let report = state.metrics.fills.lock().unwrap().report();
+    Json(json!({"buckets": report}))
+}
+
+/// GET /trades — recent RTDS last-trade prints for the traded tokens, newest first.
+async fn trades_handler(State(state): State<AppState>) -> Json<Value> {
+    Json(json!({"trades": state.metrics.recent_trades()}))
+}
+
+/// GET /schedule — per symbol: current period, seconds to close, PTB presence, subscription.
+async fn schedule_handler(State(state): State<AppState>) -> Json<Value> {
+    let now = chrono::Utc::now().timestamp();
+    let symbols: Vec<Value> = state
+        .metrics
+        .schedule()
+        .into_iter()
+        .map(|s| {
+            json!({
+                "symbol": s.symbol,
+                "period_start": s.period_start,
+                "close_time": s.close_time,
+                "seconds_to_close": s.close_time - now,
+                "has_price_to_beat": s.has_price_to_beat,
+                "subscribed": s.subscribed,
+                "price": state.metrics.price(&s.symbol),
+            })
+        })
+        .collect();
+    Json(json!({"now": now, "symbols": symbols}))
+}
+
+/// GET /orderbook — best bid/ask, mid and spread per token of each symbol's current round, from
+/// the orderbook mirror. Price fields are null while the book (or that side) is empty.
+async fn orderbook_handler(State(state): State<AppState>) -> Json<Value> {
+    let mut tokens = Vec::new();
+    for s in state.metrics.schedule() {
+        for (side, token) in [("Up", s.up_token), ("Down", s.down_token)] {
+            let Some(token) = token else { continue };
+            let book = state.orderbook.get_orderbook(&token).await;
+            tokens.push(json!({
+                "symbol": s.symbol,
+                "side": side,
+                "token_id": token,
+                "best_bid": book.as_ref().and_then(|b| b.best_bid()),
+                "best_ask": book.as_ref().and_then(|b| b.best_ask()),
+                "mid": book.as_ref().and_then(|b| b.mid()),
+                "spread": book.as_ref().and_then(|b| b.spread()),
+                "bid_levels": book.as_ref().map_or(0, |b| b.bids.len()),
+                "ask_levels": book.as_ref().map_or(0, |b| b.asks.len()),
+            }));
+        }
+    }
+    Json(json!({"tokens": tokens}))
+}
+
+/// GET /pnl — sweep P&L from `paper_trades.jsonl`, per symbol and total, for each configured
+/// trailing window plus all-time.
+async fn pnl_handler(State(state): State<AppState>) -> (StatusCode, Json<Value>) {
+    let path = std::path::Path::new(paper_trade::PAPER_TRADES_JSONL);
+    let rows = match tokio::task::spawn_blocking(move || paper_trade::load_history(path)).await {
+        Ok(Ok(rows)) => rows,
+        // No history yet: report empty windows.
+        Ok(Err(_)) => Vec::new(),
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": e.to_string()}))),
+    };
+    let now = chrono::Utc::now().timestamp();
+    let mut windows: Vec<(String, Option<i64>)> = state
+        .pnl_window_hours
+        .iter()
+        .map(|h| (format!("{}h", h), Some(now - *h as i64 * 3600)))
+        .collect();
+    windows.push(("all".to_string(), None));
+
+    let report: Vec<Value> = windows
+        .into_iter()
+        .map(|(label, since)| {
+            let in_window: Vec<&paper_trade::PaperTradeRow> =
+                rows.iter().filter(|r| since.is_none_or(|t| r.period_5 >= t)).collect();
+            let mut by_symbol: BTreeMap<&str, Vec<&paper_trade::PaperTradeRow>> = BTreeMap::new();
+            for row in &in_window {
+                by_symbol.entry(row.symbol.as_str()).or_default().push(row);
+            }
+            let symbols: BTreeMap<&str, paper_trade::PnlSummary> = by_symbol
+                .into_iter()
+                .map(|(symbol, rows)| (symbol, paper_trade::pnl_summary(rows)))
+                .collect();
+            json!({
+                "window": label,
+                "total": paper_trade::pnl_summary(in_window),
+                "symbols": symbols,
+            })
+        })
+        .collect();
+    (StatusCode::OK, Json(json!({"windows": report})))
+}
+
+#[derive(Deserialize)]
+struct ReplayQuery {
+    symbol: String,
+    n: Option<usize>,
+}
+
+/// GET /replay?symbol=BTC&n=5 — re-run the winner/margin decision for the symbol's last `n`
+/// rounds (default 5, max 100) from `paper_trades.jsonl` and the `book_snapshot_dir` close
+/// books, with the strategy config currently in effect. Read-only: nothing touches the network.
+async fn replay_handler(State(state): State<AppState>, Query(q): Query<ReplayQuery>) -> (StatusCode, Json<Value>) {
+    let n = q.n.unwrap_or(5).min(100);
+    let symbol = q.symbol.to_lowercase();
+    let settings = Arc::clone(&state.settings.read().unwrap());
+    let replayed = tokio::task::spawn_blocking(move || {
+        let rows = paper_trade::load_history(std::path::Path::new(paper_trade::PAPER_TRADES_JSONL))?;
+        let cfg = settings.for_symbol(&symbol);
+        let book_dir = cfg.book_snapshot_dir.as_deref().map(std::path::Path::new);
+        anyhow::Ok(decision::replay(&rows, &symbol, n, cfg, book_dir))
+    })
+    .await;
+    match replayed {
+        Ok(Ok(rounds)) => (StatusCode::OK, Json(json!({"symbol": q.symbol.to_lowercase(), "rounds": rounds}))),
+        Ok(Err(e)) => (StatusCode::NOT_FOUND, Json(json!({"error": format!("{:#}", e)}))),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": e.to_string()}))),
+    }
+}
+
+/// `monitor` is set when there's no private key: orders are never sent and pausing is moot.
+/// `budget.effective` is the override if set, else the live `max_sweep_cost` (before
+/// `per_symbol` overrides). `circuit_breaker.open_until` is set while network errors keep the
+/// bot monitor-only.
+fn control_status(state: &AppState) -> Json<Value> {
+    let budget_override = state.control.budget_override();
+    let configured = configured_budget(state);
+    Json(json!({
+        "paused": state.control.is_paused(),
+        "circuit_breaker": {
+            "consecutive_network_errors": state.control.consecutive_network_errors(),
+            "open_until": state.control.breaker_open_until(chrono::Utc::now().timestamp()),
+        },
+        "monitor": !state.api.has_private_key(),
+        "budget": {
+            "override": budget_override,
+            "configured": configured,
+            "effective": budget_override.unwrap_or(configured),
+            "max": state.max_budget_override,
+        },
+    }))
+}
+
+/// Base `max_sweep_cost` of the live settings, so a hot reload shows up without a restart.
+fn configured_budget(state: &AppState) -> f64 {
+    state.settings.read().unwrap().base.max_sweep_cost
 }
 
-async fn index_handler() -> Html<&'static str> {
-    Html(DASHBOARD_HTML)
+/// GET /control — current runtime control state (read-only, no token).
+async fn control_status_handler(State(state): State<AppState>) -> Json<Value> {
+    control_status(&state)
 }
 
-async fn snapshot_handler(State(buf): State<LogBuffer>) -> axum::Json<Vec<crate::log_buffer::LogEntry>> {
-    axum::Json(buf.snapshot().await)
+/// POST /control/pause — stop placing live orders; rounds keep running and paper-logging.
+async fn pause_handler(State(state): State<AppState>, headers: HeaderMap) -> (StatusCode, Json<Value>) {
+    set_paused(state, headers, true).await
 }
 
-async fn sse_handler(State(buf): State<LogBuffer>) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
-    let rx = buf.subscribe();
+/// POST /control/resume — resume live orders.
+async fn resume_handler(State(state): State<AppState>, headers: HeaderMap) -> (StatusCode, Json<Value>) {
+    set_paused(state, headers, false).await
+}
+
+async fn set_paused(state: AppState, headers: HeaderMap, paused: bool) -> (StatusCode, Json<Value>) {
+    if let Err(rejection) = check_token(&state, &headers) {
+        return rejection;
+    }
+    if state.control.set_paused(paused) != paused {
+        let msg = if paused { "trading paused from dashboard" } else { "trading resumed from dashboard" };
+        log::warn!("{}", msg);
+        state.log_buffer.push("SYS", "warn", msg.to_string()).await;
+    }
+    (StatusCode::OK, control_status(&state))
+}
+
+#[derive(Deserialize)]
+struct BudgetRequest {
+    /// New `max_sweep_cost` in USD; null clears the override.
+    max_sweep_cost: Option<f64>,
+}
+
+/// POST /control/budget — override `max_sweep_cost` for every symbol from the next sweep on,
+/// up to `dashboard.max_budget_override`. `{"max_sweep_cost": null}` goes back to the config.
+async fn budget_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<BudgetRequest>,
+) -> (StatusCode, Json<Value>) {
+    if let Err(rejection) = check_token(&state, &headers) {
+        return rejection;
+    }
+    if let Some(budget) = req.max_sweep_cost.filter(|b| !(*b >= 0.0 && *b <= state.max_budget_override)) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": format!("max_sweep_cost must be in [0, {}], got {}", state.max_budget_override, budget)})),
+        );
+    }
+    if state.control.set_budget_override(req.max_sweep_cost) != req.max_sweep_cost {
+        let msg = match req.max_sweep_cost {
+            Some(b) => format!("sweep budget set to ${:.2} from dashboard", b),
+            None => format!("sweep budget override cleared, back to ${:.2}", configured_budget(&state)),
+        };
+        log::warn!("{}", msg);
+        state.log_buffer.push("SYS", "warn", msg).await;
+    }
+    (StatusCode::OK, control_status(&state))
+}
+
+/// GET /orders — our resting CLOB orders. Token-gated: it reveals account state.
+async fn orders_handler(State(state): State<AppState>, headers: HeaderMap) -> (StatusCode, Json<Value>) {
+    if let Err(rejection) = check_token(&state, &headers) {
+        return rejection;
+    }
+    if !state.api.has_private_key() {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(json!({"error": "no private key configured, open orders unavailable"})),
+        );
+    }
+    match state.api.get_open_orders(None).await {
+        Ok(orders) => (StatusCode::OK, Json(json!({"orders": orders}))),
+        Err(e) => (StatusCode::BAD_GATEWAY, Json(json!({"error": e.to_string()}))),
+    }
+}
+
+/// GET /positions — current holdings of the trading wallet from the data API. Token-gated.
+async fn positions_handler(State(state): State<AppState>, headers: HeaderMap) -> (StatusCode, Json<Value>) {
+    if let Err(rejection) = check_token(&state, &headers) {
+        return rejection;
+    }
+    let Some(wallet) = state.api.wallet_address() else {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(json!({"error": "no wallet configured, positions unavailable"})),
+        );
+    };
+    match state.api.get_positions(&wallet, false).await {
+        Ok(positions) => (StatusCode::OK, Json(json!({"wallet": wallet, "positions": positions}))),
+        Err(e) => (StatusCode::BAD_GATEWAY, Json(json!({"error": e.to_string()}))),
+    }
+}
+
+#[derive(Deserialize)]
+struct RedeemRequest {
+    condition_id: String,
+    /// Winning outcome label; looked up from the market when omitted.
+    outcome: Option<String>,
+}
+
+/// POST /redeem — redeem a resolved condition. Progress is pushed to the log stream;
+/// the final result is returned as JSON.
+async fn redeem_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<RedeemRequest>,
+) -> (StatusCode, Json<Value>) {
+    if let Err(rejection) = check_token(&state, &headers) {
+        return rejection;
+    }
+    if !state.api.has_private_key() {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(json!({"error": "no private key configured, redemption unavailable"})),
+        );
+    }
+
+    let cid = if req.condition_id.starts_with("0x") {
+        req.condition_id.clone()
+    } else {
+        format!("0x{}", req.condition_id)
+    };
+    let outcome = match req.outcome {
+        Some(o) => o,
+        None => match state.api.get_market(&cid).await {
+            Ok(m) => match m.tokens.iter().find(|t| t.winner) {
+                Some(t) => t.outcome.clone(),
+                None => {
+                    return (
+                        StatusCode::CONFLICT,
+                        Json(json!({"error": "market has no winner yet", "condition_id": cid})),
+                    )
+                }
+            },
+            Err(e) => {
+                return (
+                    StatusCode::BAD_GATEWAY,
+                    Json(json!({"error": format!("market lookup failed: {}", e)})),
+                )
+            }
+        },
+    };
+
+    if let Err(e) = state.api.verify_resolution(&cid, &outcome).await {
+        state.log_buffer.push("SYS", "warn", format!("manual redeem {} not sent: {}", cid, e)).await;
+        return (StatusCode::CONFLICT, Json(json!({"condition_id": cid, "error": e.to_string()})));
+    }
+    state.log_buffer.push("SYS", "info", format!("manual redeem {} ({}) started", cid, outcome)).await;
+    match state.api.redeem_tokens(&cid, &outcome, false).await {
+        Ok(resp) => {
+            state.log_buffer.push("SYS", "info", format!("manual redeem {} ok: {:?}", cid, resp.transaction_hash)).await;
+            (StatusCode::OK, Json(json!({"condition_id": cid, "outcome": outcome, "result": resp})))
+        }
+        Err(e) => {
+            state.log_buffer.push("SYS", "error", format!("manual redeem {} failed: {}", cid, e)).await;
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"condition_id": cid, "error": e.to_string()})))
+        }
+    }
+}
+
+/// GET /events — log entries as they are pushed, plus a `heartbeat` event (unix seconds) every
+/// `sse_heartbeat_secs` so intermediaries see traffic during quiet stretches between rounds.
+async fn sse_handler(State(state): State<AppState>) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let rx = state.log_buffer.subscribe();
+    let log_buffer = state.log_buffer.clone();
+    let heartbeat = state.sse_heartbeat;
+    let min_level = state.min_level;
     let stream = async_stream::stream! {
         let mut rx = rx;
+        let mut ticker = heartbeat.map(|period| interval_at(Instant::now() + period, period));
         loop {
-            match rx.recv().await {
+            let received = tokio::select! {
+                received = rx.recv() => received,
+                _ = async {
+                    match ticker.as_mut() {
+                        Some(ticker) => { ticker.tick().await; }
+                        None => std::future::pending().await,
+                    }
+                } => {
+                    yield Ok(Event::default().event("heartbeat").data(chrono::Utc::now().timestamp().to_string()));
+                    continue;
+                }
+            };
+            match received {
+                Ok(entry) if !entry.at_least(min_level) => {}
                 Ok(entry) => {
                     let data = serde_json::to_string(&entry).unwrap_or_default();
                     yield Ok(Event::default().data(data));
                 }
                 Err(broadcast::error::RecvError::Lagged(n)) => {
+                    log::warn!(target: SSE_LOG_TARGET, "Dashboard SSE subscriber fell behind, skipped {} events", n);
+                    log_buffer.record_lagged(n);
                     let msg = format!("{{\"timestamp\":\"\",\"symbol\":\"SYS\",\"level\":\"warn\",\"message\":\"skipped {} events\"}}",n);
                     yield Ok(Event::default().data(msg));
                 }
@@ -65,10 +556,11 @@ async fn sse_handler(State(buf): State<LogBuffer>) -> Sse<impl Stream<Item = Res
             }
         }
     };
-    Sse::new(stream).keep_alive(KeepAlive::default())
+    Sse::new(stream).keep_alive(KeepAlive::new().interval(state.sse_keep_alive))
 }
 
 use tokio::sync::broadcast;
+use tokio::time::{interval_at, Duration, Instant};
 
 const DASHBOARD_HTML: &str = r##"<!DOCTYPE html>
 <html lang="en">
@@ -98,6 +590,12 @@ const DASHBOARD_HTML: &str = r##"<!DOCTYPE html>
   .status { display: flex; align-items: center; gap: 6px; font-size: 12px; color: var(--text-dim); }
   .status-dot { width: 8px; height: 8px; border-radius: 50%; background: var(--green); }
   .status-dot.disconnected { background: var(--error); }
+  .badge {
+    font-size: 11px; padding: 2px 8px; border-radius: 10px; cursor: pointer;
+    border: 1px solid var(--green); color: var(--green);
+  }
+  .badge.paused { border-color: var(--warn); color: var(--warn); }
+  .badge.monitor { border-color: var(--text-dim); color: var(--text-dim); cursor: default; }
   .filters {
     display: flex; gap: 6px; margin-left: auto;
   }
@@ -132,13 +630,43 @@ const DASHBOARD_HTML: &str = r##"<!DOCTYPE html>
   .log-symbol.XRP { color: var(--xrp); }
   .log-symbol.SYS { color: var(--text-dim); }
   .log-msg { white-space: pre-wrap; word-break: break-word; }
+  .log-entry.debug .log-msg { color: var(--text-dim); }
   .log-entry.warn .log-msg { color: var(--warn); }
   .log-entry.error .log-msg { color: var(--error); }
+  #schedule {
+    display: flex; gap: 8px; padding: 8px 20px; flex-shrink: 0; flex-wrap: wrap;
+    border-bottom: 1px solid var(--border);
+  }
+  #schedule:empty { display: none; }
+  .sched-cell {
+    background: var(--surface); border: 1px solid var(--border); border-radius: 6px;
+    padding: 4px 10px; display: flex; gap: 8px; align-items: baseline; font-size: 12px;
+  }
+  .sched-sym { font-weight: 600; }
+  .sched-cd { min-width: 40px; text-align: right; }
+  .sched-price { color: var(--text-dim); }
+  .sched-flag { color: var(--text-dim); }
+  .sched-flag.on { color: var(--green); }
+  #account {
+    display: none; gap: 24px; padding: 8px 20px; flex-shrink: 0; flex-wrap: wrap;
+    border-bottom: 1px solid var(--border); font-size: 11px;
+  }
+  #account.shown { display: flex; }
+  #account caption { text-align: left; color: var(--text-dim); padding-bottom: 2px; }
+  #account table { border-collapse: collapse; }
+  #account th, #account td { padding: 1px 8px; text-align: right; }
+  #account th { color: var(--text-dim); font-weight: normal; }
+  #account td:first-child, #account th:first-child { text-align: left; }
   footer {
     background: var(--surface); border-top: 1px solid var(--border);
     padding: 6px 20px; font-size: 11px; color: var(--text-dim);
-    display: flex; justify-content: space-between; flex-shrink: 0;
+    display: flex; justify-content: space-between; align-items: flex-end; gap: 16px; flex-shrink: 0;
   }
+  #pnl { border-collapse: collapse; font-size: 11px; }
+  #pnl th, #pnl td { padding: 1px 8px; text-align: right; }
+  #pnl th { color: var(--text-dim); font-weight: normal; }
+  #pnl td.pos { color: var(--green); }
+  #pnl td.neg { color: var(--error); }
 </style>
 </head>
 <body>
@@ -148,17 +676,27 @@ const DASHBOARD_HTML: &str = r##"<!DOCTYPE html>
     <div class="status-dot" id="status-dot"></div>
     <span id="status-text">connecting...</span>
   </div>
+  <span class="badge" id="trading-badge" onclick="togglePause()" title="Click to pause/resume live orders">active</span>
+  <span class="badge" id="budget-badge" onclick="setBudget()" title="Click to change the sweep budget">budget</span>
   <div class="filters">
     <button class="filter-btn active" data-symbol="ALL" onclick="toggleFilter(this)">All</button>
     <button class="filter-btn active" data-symbol="BTC" onclick="toggleFilter(this)">BTC</button>
     <button class="filter-btn active" data-symbol="ETH" onclick="toggleFilter(this)">ETH</button>
     <button class="filter-btn active" data-symbol="SOL" onclick="toggleFilter(this)">SOL</button>
     <button class="filter-btn active" data-symbol="XRP" onclick="toggleFilter(this)">XRP</button>
+    <button class="filter-btn" id="account-btn" onclick="toggleAccount()">Account</button>
+    <button class="filter-btn" onclick="redeem()">Redeem</button>
   </div>
 </header>
+<div id="schedule"></div>
+<div id="account">
+  <table id="orders-table"><caption>Open orders</caption></table>
+  <table id="positions-table"><caption>Positions</caption></table>
+</div>
 <div id="log-container"></div>
 <footer>
   <span id="entry-count">0 entries</span>
+  <table id="pnl"></table>
   <span>Polymarket 5m Arbitrage Bot</span>
 </footer>
 <script>
@@ -258,7 +796,417 @@ function connect() {
   };
 }
 connect();
+
+// Round schedule: refreshed from the server, counted down locally every second. Hovering a
+// cell shows each token's best bid/ask and spread.
+var schedule = [], scheduleOffset = 0, quotes = {};
+function fmtCountdown(s) {
+  if (s <= 0) return 'closed';
+  return Math.floor(s / 60) + ':' + String(s % 60).padStart(2, '0');
+}
+function renderSchedule() {
+  var el = document.getElementById('schedule');
+  var now = Math.floor(Date.now() / 1000) + scheduleOffset;
+  el.innerHTML = '';
+  schedule.forEach(function(s) {
+    var sym = s.symbol.toUpperCase();
+    var cell = document.createElement('div');
+    cell.className = 'sched-cell';
+    cell.title = 'period ' + new Date(s.period_start * 1000).toLocaleTimeString();
+    (quotes[s.symbol] || []).forEach(function(q) {
+      cell.title += '\n' + q.side + ': bid ' + (q.best_bid || '-') + ' ask ' + (q.best_ask || '-') +
+        (q.spread ? ' spread ' + q.spread : '');
+    });
+    [[sym, 'sched-sym ' + sym.replace(/[^A-Z0-9]/g, '')],
+     [fmtCountdown(s.close_time - now), 'sched-cd'],
+     [s.price != null ? '$' + s.price : '', 'sched-price'],
+     ['PTB', 'sched-flag' + (s.has_price_to_beat ? ' on' : '')],
+     ['WS', 'sched-flag' + (s.subscribed ? ' on' : '')]].forEach(function(p) {
+      var span = document.createElement('span');
+      span.textContent = p[0];
+      span.className = p[1];
+      cell.appendChild(span);
+    });
+    cell.firstChild.classList.add('log-symbol');
+    el.appendChild(cell);
+  });
+}
+function refreshSchedule() {
+  fetch('/schedule').then(function(r) { return r.json(); }).then(function(res) {
+    scheduleOffset = res.now - Math.floor(Date.now() / 1000);
+    schedule = res.symbols || [];
+    renderSchedule();
+  }).catch(function() {});
+  fetch('/orderbook').then(function(r) { return r.json(); }).then(function(res) {
+    quotes = {};
+    (res.tokens || []).forEach(function(q) { (quotes[q.symbol] = quotes[q.symbol] || []).push(q); });
+  }).catch(function() {});
+}
+refreshSchedule();
+setInterval(refreshSchedule, 5000);
+setInterval(renderSchedule, 1000);
+
+// Sweep P&L per window: total, then one column per symbol (hover for details).
+function pnlCell(tr, s) {
+  var td = document.createElement('td');
+  if (!s || !s.rounds) { td.textContent = '-'; tr.appendChild(td); return; }
+  td.textContent = (s.pnl >= 0 ? '+$' : '-$') + Math.abs(s.pnl).toFixed(2);
+  td.className = s.pnl >= 0 ? 'pos' : 'neg';
+  td.title = s.rounds + ' rounds, ' + (s.win_rate * 100).toFixed(0) + '% win, avg cost $' + s.avg_cost.toFixed(2);
+  tr.appendChild(td);
+}
+function renderPnl(res) {
+  var table = document.getElementById('pnl');
+  var windows = res.windows || [];
+  var symbols = [];
+  windows.forEach(function(w) {
+    Object.keys(w.symbols).forEach(function(s) { if (symbols.indexOf(s) < 0) symbols.push(s); });
+  });
+  symbols.sort();
+  table.innerHTML = '';
+  var head = document.createElement('tr');
+  [monitorMode ? 'Would-be P&L' : 'P&L', 'Total'].concat(symbols.map(function(s) { return s.toUpperCase(); })).forEach(function(h) {
+    var th = document.createElement('th');
+    th.textContent = h;
+    head.appendChild(th);
+  });
+  table.appendChild(head);
+  windows.forEach(function(w) {
+    var tr = document.createElement('tr');
+    var label = document.createElement('th');
+    label.textContent = w.window;
+    tr.appendChild(label);
+    pnlCell(tr, w.total);
+    symbols.forEach(function(s) { pnlCell(tr, w.symbols[s]); });
+    table.appendChild(tr);
+  });
+}
+function refreshPnl() {
+  fetch('/pnl').then(function(r) { return r.json(); }).then(renderPnl).catch(function() {});
+}
+refreshPnl();
+setInterval(refreshPnl, 60000);
+
+var tradingPaused = false, monitorMode = false, budgetMax = 0;
+function renderControl(c) {
+  tradingPaused = !!c.paused;
+  var badge = document.getElementById('trading-badge');
+  if (!!c.monitor !== monitorMode) {
+    monitorMode = !!c.monitor;
+    refreshPnl();
+  }
+  var breakerUntil = c.circuit_breaker && c.circuit_breaker.open_until;
+  badge.textContent = monitorMode ? 'monitor' : tradingPaused ? 'paused' : breakerUntil ? 'breaker' : 'active';
+  badge.title = monitorMode ? 'No private key: sweeps are simulated, no orders are sent'
+    : breakerUntil ? 'Network errors: live orders stopped until ' + new Date(breakerUntil * 1000).toLocaleTimeString()
+    : 'Click to pause/resume live orders';
+  badge.classList.toggle('monitor', monitorMode);
+  badge.classList.toggle('paused', (tradingPaused || !!breakerUntil) && !monitorMode);
+  if (c.budget) {
+    budgetMax = c.budget.max;
+    var b = document.getElementById('budget-badge');
+    b.textContent = 'budget $' + c.budget.effective.toFixed(2) + (c.budget.override != null ? ' (override)' : '');
+    b.classList.toggle('paused', c.budget.override != null);
+  }
+}
+function refreshControl() {
+  fetch('/control').then(function(r) { return r.json(); }).then(renderControl).catch(function() {});
+}
+refreshControl();
+setInterval(refreshControl, 5000);
+
+function authedFetch(path, init) {
+  var token = sessionStorage.getItem('dashToken') || prompt('Dashboard token');
+  if (!token) return Promise.reject('no token');
+  init.headers = Object.assign({ 'Authorization': 'Bearer ' + token }, init.headers);
+  return fetch(path, init).then(function(r) {
+    if (r.status === 401) sessionStorage.removeItem('dashToken');
+    else sessionStorage.setItem('dashToken', token);
+    return r.json();
+  });
+}
+function authedPost(path, body) {
+  return authedFetch(path, {
+    method: 'POST',
+    headers: { 'Content-Type': 'application/json' },
+    body: JSON.stringify(body || {})
+  });
+}
+
+// Account panel: resting orders and positions, token-gated, refreshed while it is open.
+var accountTimer = null;
+function fillTable(id, caption, head, rows, error) {
+  var table = document.getElementById(id);
+  table.innerHTML = '';
+  table.createCaption().textContent = caption + (error ? ': ' + error : rows ? ' (' + rows.length + ')' : '');
+  if (!rows || !rows.length) return;
+  [head].concat(rows).forEach(function(cells, i) {
+    var tr = document.createElement('tr');
+    cells.forEach(function(c) {
+      var td = document.createElement(i === 0 ? 'th' : 'td');
+      td.textContent = c;
+      tr.appendChild(td);
+    });
+    table.appendChild(tr);
+  });
+}
+function refreshAccount() {
+  authedFetch('/orders', {}).then(function(res) {
+    fillTable('orders-table', 'Open orders', ['Token', 'Outcome', 'Side', 'Price', 'Size', 'Filled'],
+      (res.orders || []).map(function(o) {
+        return [o.token_id.slice(0, 10) + '…', o.outcome, o.side, o.price.toFixed(2), o.size.toFixed(2), o.filled.toFixed(2)];
+      }), res.error);
+    return authedFetch('/positions', {});
+  }).then(function(res) {
+    fillTable('positions-table', 'Positions', ['Market', 'Outcome', 'Size', 'Avg', 'Value'],
+      (res.positions || []).map(function(p) {
+        return [p.slug || p.title, p.outcome, p.size.toFixed(2), p.avgPrice.toFixed(3), '$' + p.currentValue.toFixed(2)];
+      }), res.error);
+  }).catch(function(e) { if (e === 'no token' && accountTimer) toggleAccount(); });
+}
+function toggleAccount() {
+  var shown = document.getElementById('account').classList.toggle('shown');
+  document.getElementById('account-btn').classList.toggle('active', shown);
+  clearInterval(accountTimer);
+  accountTimer = null;
+  if (shown) {
+    refreshAccount();
+    accountTimer = setInterval(refreshAccount, 15000);
+  }
+}
+
+function togglePause() {
+  if (monitorMode) return;
+  var path = tradingPaused ? '/control/resume' : '/control/pause';
+  if (!confirm(tradingPaused ? 'Resume live trading?' : 'Pause live trading?')) return;
+  authedPost(path)
+    .then(function(res) { if (res.error) alert(res.error); else renderControl(res); })
+    .catch(function() {});
+}
+
+function setBudget() {
+  var v = prompt('Sweep budget (max_sweep_cost) in USD, up to ' + budgetMax + '. Leave empty to use the config value.');
+  if (v === null) return;
+  var budget = v.trim() === '' ? null : Number(v);
+  if (budget !== null && !(budget >= 0 && budget <= budgetMax)) { alert('Enter a number between 0 and ' + budgetMax); return; }
+  authedPost('/control/budget', { max_sweep_cost: budget })
+    .then(function(res) { if (res.error) alert(res.error); else renderControl(res); })
+    .catch(function() {});
+}
+
+function redeem() {
+  var cid = prompt('Condition ID to redeem');
+  if (!cid) return;
+  authedPost('/redeem', { condition_id: cid })
+    .then(function(res) { alert(res.error ? 'Redeem failed: ' + res.error : 'Redeemed ' + res.condition_id); })
+    .catch(function(e) { alert('Redeem request failed: ' + e); });
+}
 </script>
 </body>
 </html>
 "##;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::strategy::StrategySettings;
+
+    #[test]
+    fn tokens_match_only_on_identical_bytes() {
+        assert!(tokens_match(b"secret", b"secret"));
+        assert!(!tokens_match(b"secreT", b"secret"));
+        assert!(!tokens_match(b"secret", b"secret2"));
+        assert!(!tokens_match(b"", b"secret"));
+    }
+
+    fn state(log_buffer: LogBuffer, min_level: LogLevel) -> AppState {
+        AppState {
+            log_buffer,
+            api: Arc::new(PolymarketApi::new(&Config::default().polymarket)),
+            control: crate::control::TradingControl::new(),
+            metrics: crate::metrics::Metrics::new(),
+            orderbook: Arc::new(OrderbookMirror::new("")),
+            token: None,
+            pnl_window_hours: vec![],
+            sse_keep_alive: Duration::from_secs(15),
+            sse_heartbeat: None,
+            min_level,
+            settings: Arc::new(std::sync::RwLock::new(Arc::new(StrategySettings::new(Config::default().strategy)))),
+            max_budget_override: Config::default().dashboard.max_budget_override,
+        }
+    }
+
+    async fn serve_state(state: AppState) -> String {
+        let app = router(state);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.ok();
+        });
+        format!("http://{}", addr)
+    }
+
+    async fn serve(min_level: LogLevel) -> (String, LogBuffer) {
+        serve_with(LogBuffer::new(), min_level).await
+    }
+
+    async fn serve_with(log_buffer: LogBuffer, min_level: LogLevel) -> (String, LogBuffer) {
+        (serve_state(state(log_buffer.clone(), min_level)).await, log_buffer)
+    }
+
+    #[tokio::test]
+    async fn page_is_compressed_and_revalidated_but_events_are_not_compressed() {
+        let (base, _) = serve(LogLevel::Debug).await;
+        let client = reqwest::Client::new();
+
+        let page = client.get(&base).header("accept-encoding", "gzip").send().await.unwrap();
+        assert_eq!(page.headers()["content-encoding"], "gzip");
+        assert_eq!(page.headers()["cache-control"], "no-cache");
+        let etag = page.headers()["etag"].to_str().unwrap().to_string();
+
+        let again = client.get(&base).header("if-none-match", &etag).send().await.unwrap();
+        assert_eq!(again.status(), reqwest::StatusCode::NOT_MODIFIED);
+
+        let events = client
+            .get(format!("{}/events", base))
+            .header("accept-encoding", "gzip")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(events.headers()["content-type"], "text/event-stream");
+        assert!(events.headers().get("content-encoding").is_none());
+    }
+
+    #[tokio::test]
+    async fn min_level_filters_snapshot_but_buffer_keeps_everything() {
+        let (base, log_buffer) = serve(LogLevel::Info).await;
+        log_buffer.push("BTC", "debug", "first FOK 12ms after close".to_string()).await;
+        log_buffer.push("BTC", "info", "sweep done: 2 orders".to_string()).await;
+        log_buffer.push("BTC", "warn", "PTB mismatch".to_string()).await;
+
+        let shown: Vec<Value> = reqwest::get(format!("{}/snapshot", base)).await.unwrap().json().await.unwrap();
+        let levels: Vec<&str> = shown.iter().map(|e| e["level"].as_str().unwrap()).collect();
+        assert_eq!(levels, vec!["info", "warn"]);
+        assert_eq!(log_buffer.snapshot().await.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn budget_override_is_bounded_and_reported() {
+        let mut st = state(LogBuffer::new(), LogLevel::Debug);
+        st.token = Some("t".into());
+        st.max_budget_override = 100.0;
+        let settings = st.settings.clone();
+        let base = serve_state(st).await;
+        let client = reqwest::Client::new();
+        let set = |body: Value| client.post(format!("{}/control/budget", base)).bearer_auth("t").json(&body).send();
+
+        let unauthorized = client
+            .post(format!("{}/control/budget", base))
+            .json(&serde_json::json!({"max_sweep_cost": 10.0}))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(unauthorized.status(), reqwest::StatusCode::UNAUTHORIZED);
+
+        let res: Value = set(serde_json::json!({"max_sweep_cost": 25.0})).await.unwrap().json().await.unwrap();
+        assert_eq!(res["budget"]["override"], 25.0);
+        assert_eq!(res["budget"]["effective"], 25.0);
+
+        let too_big = set(serde_json::json!({"max_sweep_cost": 150.0})).await.unwrap();
+        assert_eq!(too_big.status(), reqwest::StatusCode::BAD_REQUEST);
+
+        let res: Value = set(serde_json::json!({"max_sweep_cost": null})).await.unwrap().json().await.unwrap();
+        assert!(res["budget"]["override"].is_null());
+        assert_eq!(res["budget"]["effective"], res["budget"]["configured"]);
+
+        let mut reloaded = Config::default().strategy;
+        reloaded.max_sweep_cost = 42.0;
+        *settings.write().unwrap() = Arc::new(StrategySettings::new(reloaded));
+        let res: Value = reqwest::get(format!("{}/control", base)).await.unwrap().json().await.unwrap();
+        assert_eq!(res["budget"]["configured"], 42.0);
+        assert_eq!(res["budget"]["effective"], 42.0);
+    }
+
+    #[tokio::test]
+    async fn orders_and_positions_need_the_token() {
+        let data_api = Router::new().route(
+            "/positions",
+            get(|| async { Json(json!([{"asset": "111", "size": "5", "avgPrice": 0.97, "outcome": "Up"}])) }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let data_api_url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            axum::serve(listener, data_api).await.ok();
+        });
+
+        let mut config = Config::default().polymarket;
+        config.data_api_url = data_api_url;
+        config.proxy_wallet_address = Some("0x00000000000000000000000000000000000000aa".into());
+        let mut st = state(LogBuffer::new(), LogLevel::Debug);
+        st.api = Arc::new(PolymarketApi::new(&config));
+        st.token = Some("t".into());
+        let base = serve_state(st).await;
+        let client = reqwest::Client::new();
+
+        for path in ["orders", "positions"] {
+            let res = client.get(format!("{}/{}", base, path)).send().await.unwrap();
+            assert_eq!(res.status(), reqwest::StatusCode::UNAUTHORIZED, "{}", path);
+        }
+
+        let res: Value = client.get(format!("{}/positions", base)).bearer_auth("t").send().await.unwrap().json().await.unwrap();
+        assert_eq!(res["positions"][0]["asset"], "111");
+        assert_eq!(res["positions"][0]["size"], 5.0);
+
+        let orders = client.get(format!("{}/orders", base)).bearer_auth("t").send().await.unwrap();
+        assert_eq!(orders.status(), reqwest::StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[tokio::test]
+    async fn lagging_subscriber_is_counted_in_health() {
+        let (base, log_buffer) = serve_with(LogBuffer::with_capacity(2), LogLevel::Debug).await;
+        let mut events = reqwest::get(format!("{}/events", base)).await.unwrap();
+        for i in 0..10 {
+            log_buffer.push("BTC", "debug", format!("ask {}", i)).await;
+        }
+
+        let mut body = String::new();
+        while !body.contains("skipped") {
+            body.push_str(&String::from_utf8_lossy(&events.chunk().await.unwrap().unwrap()));
+        }
+        assert!(body.contains("skipped 8 events"), "{}", body);
+        let health: Value = reqwest::get(format!("{}/health", base)).await.unwrap().json().await.unwrap();
+        assert_eq!(health["dashboard"]["sse_lagged_total"], 8);
+    }
+
+    #[tokio::test]
+    async fn orderbook_reports_quotes_per_round_token() {
+        use crate::models::{OrderBook, OrderBookEntry};
+        let st = state(LogBuffer::new(), LogLevel::Debug);
+        st.metrics.set_schedule(vec![crate::metrics::SymbolSchedule {
+            symbol: "btc".into(),
+            period_start: 0,
+            close_time: 300,
+            has_price_to_beat: true,
+            subscribed: true,
+            up_token: Some("up".into()),
+            down_token: Some("down".into()),
+        }]);
+        let level = |p: &str| OrderBookEntry { price: p.parse().unwrap(), size: "10".parse().unwrap() };
+        let book = OrderBook { bids: vec![level("0.40"), level("0.47")], asks: vec![level("0.55"), level("0.51")] };
+        st.orderbook.set_orderbook("up", book).await;
+        let base = serve_state(st).await;
+
+        let res: Value = reqwest::get(format!("{}/orderbook", base)).await.unwrap().json().await.unwrap();
+        let up = &res["tokens"][0];
+        assert_eq!((up["symbol"].as_str(), up["side"].as_str()), (Some("btc"), Some("Up")));
+        assert_eq!(up["best_bid"], "0.47");
+        assert_eq!(up["best_ask"], "0.51");
+        assert_eq!(up["mid"], "0.49");
+        assert_eq!(up["spread"], "0.04");
+        assert_eq!(up["ask_levels"], 2);
+        let down = &res["tokens"][1];
+        assert_eq!(down["side"], "Down");
+        assert!(down["mid"].is_null(), "no book yet");
+    }
+}